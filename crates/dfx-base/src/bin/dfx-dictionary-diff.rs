@@ -0,0 +1,39 @@
+//! Prints the differences between two data dictionaries.
+//!
+//! ```text
+//! dfx-dictionary-diff <old.xml> <new.xml>
+//! ```
+//!
+//! Exits with status 1 when the new dictionary contains breaking changes.
+use std::process::ExitCode;
+
+use dfx_base::data_dictionary::{diff, DataDictionary};
+
+fn load(path: &str) -> Result<DataDictionary, String> {
+    let contents = std::fs::read_to_string(path).map_err(|e| format!("couldn't read {path}: {e}"))?;
+    DataDictionary::load_from_string(&contents).map_err(|e| format!("couldn't parse {path}: {e:?}"))
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() != 3 {
+        eprintln!("usage: {} <old.xml> <new.xml>", args[0]);
+        return ExitCode::from(2);
+    }
+
+    let (old, new) = match (load(&args[1]), load(&args[2])) {
+        (Ok(old), Ok(new)) => (old, new),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("{e}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let report = diff(&old, &new);
+    print!("{report}");
+    if report.is_breaking() {
+        ExitCode::from(1)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
@@ -23,6 +23,12 @@ use std::ops::Deref;
 use std::ops::DerefMut;
 use std::path::Path;
 
+mod diff;
+pub use diff::*;
+mod export;
+mod builder;
+pub use builder::*;
+
 #[derive(Clone, Debug)]
 pub enum MessageValidationError {
     UnsupportedVersion { expected: String, actual: String },
@@ -119,6 +125,9 @@ pub enum DataDictionaryError {
     ParseError(ParseError),
     Missing { entry_type: Arc<str>, name: Arc<str> },
     InvalidVersionType { version_type: Arc<str> },
+    InvalidRequiredCondition { condition: Arc<str> },
+    /// The component contains itself, directly or through other components.
+    ComponentCycle { name: Arc<str> },
     ParseIntError(ParseIntError),
 }
 
@@ -153,6 +162,7 @@ pub struct DataDictionary {
     fields_by_tag: BTreeMap<Tag, Field>,
     fields_by_name: BTreeMap<Arc<str>, Field>,
     messages: BTreeMap<Arc<str>, DDMap>,
+    components: BTreeMap<Arc<str>, DDMap>,
     header: DDMap,
     trailer: DDMap,
 }
@@ -169,6 +179,7 @@ impl Default for DataDictionary {
             fields_by_tag: Default::default(),
             fields_by_name: Default::default(),
             messages: Default::default(),
+            components: Default::default(),
             header: DDMap::new("header".into()),
             trailer: DDMap::new("trailer".into())
         }
@@ -242,11 +253,11 @@ impl DataDictionary {
         }
 
         if let Some(dictionary) = session_data_dictionary {
-            dictionary.iterate(message.header(), msg_type)?;
-            dictionary.iterate(message.trailer(), msg_type)?;
+            dictionary.iterate(message.header(), &[], msg_type)?;
+            dictionary.iterate(message.trailer(), &[message.header()], msg_type)?;
         }
 
-        app_data_dictionary.iterate(message, msg_type)?;
+        app_data_dictionary.iterate(message, &[message.header()], msg_type)?;
         Ok(())
     }
 
@@ -280,6 +291,21 @@ impl DataDictionary {
                 return Err(MessageValidationError::TagException(TagException::required_tag_missing(*field)));
             }
         }
+
+        let header = message.header();
+        DataDictionary::check_has_conditionally_required(&[header], &self.header)?;
+        DataDictionary::check_has_conditionally_required(&[message.trailer(), header], &self.trailer)?;
+        DataDictionary::check_has_conditionally_required(&[message, header], &self.messages[msg_type])?;
+        Ok(())
+    }
+    /// `scopes` holds the map to check followed by the maps enclosing it, the conditions are
+    /// resolved against the innermost one setting their field.
+    fn check_has_conditionally_required(scopes: &[&FieldMap], dd_map: &DDMap) -> Result<(), MessageValidationError> {
+        for (field, condition) in dd_map.conditionally_required_fields() {
+            if condition.is_met_in(scopes) && !scopes[0].is_field_set(*field) {
+                return Err(MessageValidationError::TagException(TagException::required_tag_missing(*field)));
+            }
+        }
         Ok(())
     }
     fn check_has_no_repeated_tags(map: &FieldMap) -> Result<(), MessageValidationError> {
@@ -430,7 +456,8 @@ impl DataDictionary {
         true
     }
 
-    fn iterate(&self, message: &FieldMap, msg_type: &str) -> Result<(), MessageValidationError> {
+    /// `enclosing` holds the maps around `message` the conditions of its groups may refer to.
+    fn iterate(&self, message: &FieldMap, enclosing: &[&FieldMap], msg_type: &str) -> Result<(), MessageValidationError> {
         DataDictionary::check_has_no_repeated_tags(message)?;
 
         // check non-group fields
@@ -463,11 +490,12 @@ impl DataDictionary {
         }
 
         // check contents of each group
+        let scopes: Vec<&FieldMap> = std::iter::once(message).chain(enclosing.iter().copied()).collect();
         for tag in message.group_tags() {
             for i in 1..=message.group_count(*tag)? {
                 let g = message.get_group(i as u32, *tag)?;
                 let ddg = self.messages[msg_type].get_group(*tag);
-                self.iterate_group(g, ddg, msg_type)?;
+                self.iterate_group(g, ddg, &scopes, msg_type)?;
             }
         }
 
@@ -478,11 +506,14 @@ impl DataDictionary {
         &self,
         group: &Group,
         group_definition: Option<&ArcGroup>,
+        enclosing: &[&FieldMap],
         msg_type: &str,
     ) -> Result<(), MessageValidationError> {
         match group_definition {
             Some(group_definition) => {
                 DataDictionary::check_has_no_repeated_tags(group)?;
+                let scopes: Vec<&FieldMap> = std::iter::once(&**group).chain(enclosing.iter().copied()).collect();
+                DataDictionary::check_has_conditionally_required(&scopes, group_definition)?;
 
                 let mut last_field = 0;
                 for (_, v) in group.entries() {
@@ -512,7 +543,7 @@ impl DataDictionary {
                     for i in 1..=group.group_count(*tag)? {
                         let g = group.get_group(i as u32, *tag)?;
                         let ddg = group_definition.get_group(*tag);
-                        self.iterate_group(g, ddg, msg_type)?;
+                        self.iterate_group(g, ddg, &scopes, msg_type)?;
                     }
                 }
 
@@ -546,6 +577,14 @@ impl DataDictionary {
         &self.messages
     }
 
+    pub fn components(&self) -> &BTreeMap<Arc<str>, DDMap> {
+        &self.components
+    }
+
+    pub fn fields_by_tag(&self) -> &BTreeMap<Tag, Field> {
+        &self.fields_by_tag
+    }
+
 
     pub fn check_fields_have_values(&self) -> bool {
         self.check_fields_have_values
@@ -592,11 +631,21 @@ impl DataDictionary {
     }
 }
 
+/// A field, group or component reference of a message, component or group, in declaration order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DDEntry {
+    Field(Tag),
+    Group(Tag),
+    Component { name: Arc<str>, required: bool },
+}
+
 #[derive(Debug, Clone)]
 pub struct DDMap {
     fields: BTreeMap<Tag, Field>,
+    layout: Vec<DDEntry>,
     groups: BTreeMap<Tag, ArcGroup>,
     required_fields: BTreeSet<Tag>,
+    conditionally_required_fields: BTreeMap<Tag, RequiredCondition>,
     name: Arc<str>,
     msg_type: Arc<str>,
     admin: bool,
@@ -605,8 +654,10 @@ impl DDMap {
     pub fn new(name: Arc<str>) -> Self {
         DDMap {
             fields: BTreeMap::default(),
+            layout: Vec::new(),
             groups: BTreeMap::default(),
             required_fields: BTreeSet::default(),
+            conditionally_required_fields: BTreeMap::default(),
             name,
             msg_type: "".into(),
             admin: false,
@@ -615,8 +666,10 @@ impl DDMap {
     pub fn new_with_values(name: Arc<str>, msg_type: Arc<str>, admin: bool) -> Self {
         DDMap {
             fields: BTreeMap::default(),
+            layout: Vec::new(),
             groups: BTreeMap::default(),
             required_fields: BTreeSet::default(),
+            conditionally_required_fields: BTreeMap::default(),
             name,
             msg_type,
             admin,
@@ -649,12 +702,22 @@ impl DDMap {
     pub fn add_required_field(&mut self, tag: Tag) {
         self.required_fields.insert(tag);
     }
+    pub fn conditionally_required_fields(&self) -> &BTreeMap<Tag, RequiredCondition> {
+        &self.conditionally_required_fields
+    }
+    pub fn add_conditionally_required_field(&mut self, tag: Tag, condition: RequiredCondition) {
+        self.conditionally_required_fields.insert(tag, condition);
+    }
     pub fn name(&self) -> &Arc<str> {
         &self.name
     }
     pub fn fields(&self) -> &BTreeMap<Tag, Field> {
         &self.fields
     }
+    /// The entries as declared in the dictionary or the builder, components not expanded, empty for maps filled with `add_field`.
+    pub fn layout(&self) -> &[DDEntry] {
+        &self.layout
+    }
     pub fn groups(&self) -> &BTreeMap<Tag, ArcGroup> {
         &self.groups
     }
@@ -688,6 +751,39 @@ impl<D: DerefMut<Target = DDMap>> AsDDMap for D {
     }
 }
 
+/// Condition of a `required_if="OrdType=2"` attribute.
+///
+/// The field is required when `tag` is set to one of `values`, alternatives are separated with `|`
+/// in the dictionary, e.g. `required_if="OrdType=2|4"`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequiredCondition {
+    tag: Tag,
+    values: Vec<Arc<str>>,
+}
+impl RequiredCondition {
+    pub fn new(tag: Tag, values: Vec<Arc<str>>) -> Self {
+        RequiredCondition { tag, values }
+    }
+    pub fn tag(&self) -> Tag {
+        self.tag
+    }
+    pub fn values(&self) -> &[Arc<str>] {
+        &self.values
+    }
+    pub fn is_met(&self, map: &FieldMap) -> bool {
+        self.is_met_in(&[map])
+    }
+    /// Whether the condition holds in a map nested in others, `scopes` lists the map first and
+    /// then the maps enclosing it, e.g. a group, the message body and the header. The innermost
+    /// map setting the field decides.
+    pub fn is_met_in(&self, scopes: &[&FieldMap]) -> bool {
+        match scopes.iter().find_map(|map| map.get_string(self.tag).ok()) {
+            Some(value) => self.values.iter().any(|v| v.as_ref() == value),
+            None => false,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum DictionaryError {
     ParseError(Arc<str>),
@@ -703,8 +799,10 @@ pub struct DDField {
 }
 impl DDField {
 
-    pub fn from_xml_str(xml_str: &str) -> Self {
-        todo!("DataDictionary::from_xml_str({xml_str})")
+    /// Parses a single `<field number=".." name=".." type="..">` element, including its `<value>` children.
+    pub fn from_xml_str(xml_str: &str) -> Result<Self, DataDictionaryError> {
+        let field_node = Element::parse(xml_str.as_bytes())?;
+        parse_field(&field_node)
     }
 
     pub fn new(
@@ -825,6 +923,7 @@ impl DataDictionary {
             fields_by_tag: BTreeMap::new(),
             fields_by_name: BTreeMap::new(),
             messages: BTreeMap::new(),
+            components: BTreeMap::new(),
             check_fields_out_of_order: true,
             check_fields_have_values: true,
             check_user_defined_fields: true,
@@ -849,26 +948,40 @@ impl DataDictionary {
         let (fields_by_tag, fields_by_name) = parse_fields(&root_doc)?;
         let components_by_name = cache_components(&root_doc)?;
         let messages = parse_messages(&root_doc, &fields_by_name, &components_by_name)?;
+        let components = parse_components(&fields_by_name, &components_by_name);
         let header = parse_header(&root_doc, &fields_by_name, &components_by_name)?;
         let trailer = parse_trailer(&root_doc, &fields_by_name, &components_by_name)?;
 
+        Ok(DataDictionary::from_parts(Some(version), fields_by_tag, fields_by_name, messages, components, header, trailer))
+    }
+
+    fn from_parts(
+        version: Option<Arc<str>>,
+        fields_by_tag: BTreeMap<Tag, Field>,
+        fields_by_name: BTreeMap<Arc<str>, Field>,
+        messages: BTreeMap<Arc<str>, DDMap>,
+        components: BTreeMap<Arc<str>, DDMap>,
+        header: DDMap,
+        trailer: DDMap,
+    ) -> DataDictionary {
         let length_fields = fields_by_tag.iter()
             .filter_map(|(tag, f)| if f.is_length_field() { Some(*tag) } else { None } )
             .collect();
 
-        Ok(DataDictionary {
-            version: Some(version),
+        DataDictionary {
+            version,
             length_fields,
             fields_by_tag,
             fields_by_name,
             messages,
+            components,
             check_fields_out_of_order: true,
             check_fields_have_values: true,
             check_user_defined_fields: true,
             allow_unknown_message_fields: false,
             header,
             trailer,
-        })
+        }
     }
 
 }
@@ -899,47 +1012,38 @@ fn parse_fields(doc: &Element) -> Result<(BTreeMap<i32, Field>, BTreeMap<Arc<str
         .filter(|node| node.name == "field");
 
     for field_node in field_nodes {
-        let tag_str = field_node.attributes.get("number")
-            .ok_or(DataDictionaryError::Missing { entry_type: "attribute".into(), name: "major".into() })?;
-        let name = field_node.attributes.get("name")
-            .ok_or(DataDictionaryError::Missing { entry_type: "attribute".into(), name: "name".into() })?;
-        let field_type = field_node.attributes.get("type")
-            .ok_or(DataDictionaryError::Missing { entry_type: "attribute".into(), name: "type".into() })?;
-
-        let tag = tag_str.parse::<i32>()?;
-        let mut enums = BTreeMap::new();
-        for enum_node in field_node.children.iter()
-            .filter_map(|c| c.as_element())
-            .filter(|c| c.name == "value")
-        {
-            let enum_value = enum_node.attributes.get("enum")
-                .ok_or(DataDictionaryError::Missing { entry_type: "attribute".into(), name: "enum".into() })?.clone();
-            let description = enum_node.attributes.get("description").map(|s| s.clone()).unwrap_or_default();
-            enums.insert(enum_value.into(), description.into());
-        }
-
-        let is_multiple_value_field_with_enums = matches!(
-            field_type.as_str(),
-            "MULTIPLEVALUESTRING" | "MULTIPLESTRINGVALUE" | "MULTIPLECHARVALUE"
-        );
-
-        let dd_field = DDField {
-            tag,
-            name: name.clone().into(),
-            enum_dictionary: enums,
-            field_type: field_type.clone().into(),
-            is_multiple_value_field_with_enums
-        };
-        let dd_field = Arc::new(dd_field);
-
-        fields_by_tag.insert(tag, dd_field.clone());
-        fields_by_name.insert(name.clone().into(), dd_field);
+        let dd_field = Arc::new(parse_field(field_node)?);
+        fields_by_tag.insert(dd_field.tag, dd_field.clone());
+        fields_by_name.insert(dd_field.name.clone(), dd_field);
     }
     return Ok((fields_by_tag, fields_by_name));
 }
 
-fn cache_components(doc: &Element) -> Result<BTreeMap<Arc<str>, Element>, DataDictionaryError> {
-    let mut components_by_name: BTreeMap<Arc<str>, Element> = BTreeMap::new();
+fn parse_field(field_node: &Element) -> Result<DDField, DataDictionaryError> {
+    let tag_str = field_node.attributes.get("number")
+        .ok_or(DataDictionaryError::Missing { entry_type: "attribute".into(), name: "number".into() })?;
+    let name = field_node.attributes.get("name")
+        .ok_or(DataDictionaryError::Missing { entry_type: "attribute".into(), name: "name".into() })?;
+    let field_type = field_node.attributes.get("type")
+        .ok_or(DataDictionaryError::Missing { entry_type: "attribute".into(), name: "type".into() })?;
+
+    let tag = tag_str.parse::<i32>()?;
+    let mut enums = BTreeMap::new();
+    for enum_node in field_node.children.iter()
+        .filter_map(|c| c.as_element())
+        .filter(|c| c.name == "value")
+    {
+        let enum_value = enum_node.attributes.get("enum")
+            .ok_or(DataDictionaryError::Missing { entry_type: "attribute".into(), name: "enum".into() })?.clone();
+        let description = enum_node.attributes.get("description").map(|s| s.clone()).unwrap_or_default();
+        enums.insert(enum_value.into(), description.into());
+    }
+
+    Ok(DDField::new(tag, name.clone().into(), enums, field_type.clone().into()))
+}
+
+fn cache_components(doc: &Element) -> Result<BTreeMap<Arc<str>, MapBuilder>, DataDictionaryError> {
+    let mut components_by_name: BTreeMap<Arc<str>, MapBuilder> = BTreeMap::new();
     let component_nodes = doc
         .children.iter()
         .filter_map(|c| c.as_element())
@@ -951,12 +1055,12 @@ fn cache_components(doc: &Element) -> Result<BTreeMap<Arc<str>, Element>, DataDi
     for component_node in component_nodes {
         let name = component_node.attributes.get("name")
             .ok_or(DataDictionaryError::Missing { entry_type: "attribute".into(), name: "name".into() })?.clone();
-        components_by_name.insert(name.into(), component_node.clone());
+        components_by_name.insert(name.into(), map_builder(component_node)?);
     }
     Ok(components_by_name)
 }
 
-fn parse_messages(doc: &Element, fields_by_name: &BTreeMap<Arc<str>, Field>, components_by_name: &BTreeMap<Arc<str>, Element>) -> Result<BTreeMap<Arc<str>, DDMap>, DataDictionaryError> {
+fn parse_messages(doc: &Element, fields_by_name: &BTreeMap<Arc<str>, Field>, components_by_name: &BTreeMap<Arc<str>, MapBuilder>) -> Result<BTreeMap<Arc<str>, DDMap>, DataDictionaryError> {
     let mut messages: BTreeMap<Arc<str>, DDMap> = BTreeMap::new();
     let message_nodes = doc
         .children.iter()
@@ -969,27 +1073,41 @@ fn parse_messages(doc: &Element, fields_by_name: &BTreeMap<Arc<str>, Field>, com
     for message_node in message_nodes {
         let name: Arc<str> = message_node.attributes.get("name")
             .ok_or(DataDictionaryError::Missing { entry_type: "attribute".into(), name: "name".into() })?.clone().into();
-        let mut dd_map = DDMap::new(name);
-        parse_msg_element(&message_node, &mut dd_map, fields_by_name, components_by_name)?;
         let msg_type: Arc<str> = message_node.attributes.get("msgtype")
             .ok_or(DataDictionaryError::Missing { entry_type: "attribute".into(), name: "msgtype".into() })?.clone().into();
+        let admin = message_node.attributes.get("msgcat").map(|c| c == "admin").unwrap_or(false);
+        let mut dd_map = DDMap::new_with_values(name, msg_type.clone(), admin);
+        build_map(&map_builder(message_node)?, &mut dd_map, fields_by_name, components_by_name)?;
         messages.insert(msg_type, dd_map);
     }
     Ok(messages)
 }
 
-fn parse_header(doc: &Element, fields_by_name: &BTreeMap<Arc<str>, Field>, components_by_name: &BTreeMap<Arc<str>, Element>) -> Result<DDMap, DataDictionaryError> {
+/// Components used by messages were already parsed with them, one that doesn't parse is not used and
+/// is left out the way it is ignored when loading the messages.
+fn parse_components(fields_by_name: &BTreeMap<Arc<str>, Field>, components_by_name: &BTreeMap<Arc<str>, MapBuilder>) -> BTreeMap<Arc<str>, DDMap> {
+    let mut components: BTreeMap<Arc<str>, DDMap> = BTreeMap::new();
+    for (name, component) in components_by_name {
+        let mut dd_map = DDMap::new(name.clone());
+        if build_map(component, &mut dd_map, fields_by_name, components_by_name).is_ok() {
+            components.insert(name.clone(), dd_map);
+        }
+    }
+    components
+}
+
+fn parse_header(doc: &Element, fields_by_name: &BTreeMap<Arc<str>, Field>, components_by_name: &BTreeMap<Arc<str>, MapBuilder>) -> Result<DDMap, DataDictionaryError> {
     let mut dd_map = DDMap::new("header".into());
     if let Some(header_node) = doc.get_child("header") {
-        parse_msg_element(&header_node, &mut dd_map, fields_by_name, components_by_name)?;
+        build_map(&map_builder(&header_node)?, &mut dd_map, fields_by_name, components_by_name)?;
     }
     Ok(dd_map)
 }
 
-fn parse_trailer(doc: &Element, fields_by_name: &BTreeMap<Arc<str>, Field>, components_by_name: &BTreeMap<Arc<str>, Element>) -> Result<DDMap, DataDictionaryError> {
+fn parse_trailer(doc: &Element, fields_by_name: &BTreeMap<Arc<str>, Field>, components_by_name: &BTreeMap<Arc<str>, MapBuilder>) -> Result<DDMap, DataDictionaryError> {
     let mut dd_map = DDMap::new("trailer".into());
     if let Some(trailer_node) = doc.get_child("trailer") {
-        parse_msg_element(&trailer_node, &mut dd_map, fields_by_name, components_by_name)?;
+        build_map(&map_builder(&trailer_node)?, &mut dd_map, fields_by_name, components_by_name)?;
     }
     Ok(dd_map)
 }
@@ -1014,107 +1132,214 @@ fn verify_child_node(child_node: &Element, parent_node: &Element) {
     }
 }
 
-fn parse_msg_element(
-    node: &Element,
-    dd_map: &mut DDMap,
-    fields_by_name: &BTreeMap<Arc<str>, Field>,
-    components_by_name: &BTreeMap<Arc<str>, Element>,
-) -> Result<(), DataDictionaryError> {
-    parse_msg_element_inner(node, &mut GoM::Map(dd_map), fields_by_name, components_by_name, None)
+fn parse_required_condition(condition: &str, fields_by_name: &BTreeMap<Arc<str>, Field>) -> Result<RequiredCondition, DataDictionaryError> {
+    let invalid = || DataDictionaryError::InvalidRequiredCondition { condition: condition.into() };
+    let (name, values) = condition.split_once('=').ok_or_else(invalid)?;
+    let field = fields_by_name.get(name.trim()).ok_or_else(invalid)?;
+    let values: Vec<Arc<str>> = values.split('|').map(|v| v.trim().into()).collect();
+    if values.iter().any(|v| v.is_empty()) {
+        return Err(invalid());
+    }
+    Ok(RequiredCondition::new(field.tag(), values))
 }
 
-fn parse_msg_element_inner(
-    node: &Element,
-    dd_map: &mut GoM<'_>,
-    fields_by_name: &BTreeMap<Arc<str>, Field>,
-    components_by_name: &BTreeMap<Arc<str>, Element>,
-    component_required: Option<bool>,
-) -> Result<(), DataDictionaryError> {
+/// The entries of a `<message>`, `<component>`, `<group>`, `<header>` or `<trailer>` element.
+fn map_builder(node: &Element) -> Result<MapBuilder, DataDictionaryError> {
     let message_type_name = node
         .attributes
         .get("name")
         .map(|s| s.clone())
         .unwrap_or_else(|| node.name.clone());
 
-    if node.children.is_empty() {
-        return Ok(());
-    }
-
+    let mut map = MapBuilder::new();
     for child_node in node.children.iter() {
         if let Some(child_node) = child_node.as_element() {
             verify_child_node(child_node, node);
 
-            let name_attribute: Arc<str> = child_node.attributes.get("name")
+            let name: Arc<str> = child_node.attributes.get("name")
                 .ok_or(DataDictionaryError::Missing { entry_type: "attribute".into(), name: "name".into() })?.clone().into();
+            let required = child_node.attributes.get("required").map(|v| v == "Y").unwrap_or(false);
 
-            match child_node.name.as_str() {
-                "field" | "group" => {
-                    if !fields_by_name.contains_key(&name_attribute) {
-                        panic!(
-                            "Field '{}' is not defined in <fields> section.",
-                            name_attribute
-                        );
-                    }
-                    let dd_field = fields_by_name.get(&name_attribute)
-                        .ok_or(DataDictionaryError::Missing { entry_type: "field".into(), name: name_attribute.clone() })?.clone();
-                    let required = child_node.attributes.get("required").map(|v| v == "Y").unwrap_or(false)
-                        && component_required.unwrap_or(true);
-
-                    if required {
-                        dd_map.required_fields.insert(dd_field.tag);
-                    }
-
-                    if !dd_map.is_field(dd_field.tag) {
-                        dd_map.fields.insert(dd_field.tag, dd_field.clone());
-                    }
-
-                    //TODO check if ddmap is a ddgroup and set delim!
-                    if let GoM::Group(grp) = dd_map {
-                        if grp.delim == 0 {
-                            grp.delim = dd_field.tag;
-                        }
-                    }
-
-                    if child_node.name == "group" {
-                        let mut dd_grp = DDGroup::new();
-                        dd_grp.num_fld = dd_field.tag;
-
-                        if required {
-                            dd_grp.required = true;
-                        }
-
-                        {
-                            let mut dd_map = GoM::Group(&mut dd_grp);
-                            parse_msg_element_inner(child_node, &mut dd_map, fields_by_name, components_by_name, None)?;
-                        }
-
-                        dd_map.groups.insert(dd_field.tag, dd_grp.into());
-                    }
-                }
-                "component" => {
-                    let component_node = components_by_name
-                        .get(&name_attribute)
-                        .ok_or(DataDictionaryError::Missing { entry_type: "component".into(), name: name_attribute.clone().into() })?
-                        .clone();
-
-                    let required = child_node.attributes.get("required").map(|v| v == "Y").unwrap_or(false);
-                    parse_msg_element_inner(&component_node, dd_map, fields_by_name, components_by_name, Some(required))?;
-                }
+            map.entries.push(match child_node.name.as_str() {
+                "field" => MapEntry::Field {
+                    name,
+                    required,
+                    required_if: child_node.attributes.get("required_if").map(|c| c.as_str().into()),
+                },
+                "group" => MapEntry::Group { name, required, map: map_builder(child_node)? },
+                "component" => MapEntry::Component { name, required },
                 _ => panic!(
                     "Malformed data dictionary: child node type should be one of {{field,group,component}} but is '{}' within parent '{}/{}'",
                     child_node.name,
                     node.name,
                     message_type_name
                 ),
-            }
+            });
         }
     }
-    Ok(())
+    Ok(map)
 }
 
 #[cfg(test)]
 mod tests {
     use super::DataDictionary;
+    use super::DataDictionaryError;
+    use super::MessageValidationError;
+    use crate::field_map::Group;
+    use crate::message::Message;
+    use crate::tags;
+
+    const REQUIRED_IF_DICTIONARY: &str = r#"<fix major="4" minor="4">
+  <header>
+    <field name="BeginString" required="Y"/>
+    <field name="MsgType" required="Y"/>
+  </header>
+  <trailer/>
+  <messages>
+    <message name="NewOrderSingle" msgtype="D" msgcat="app">
+      <field name="OrdType" required="Y"/>
+      <field name="Price" required="N" required_if="OrdType=2|4"/>
+      <field name="StopPx" required="N" required_if="OrdType=3"/>
+    </message>
+  </messages>
+  <components/>
+  <fields>
+    <field number="8" name="BeginString" type="STRING"/>
+    <field number="35" name="MsgType" type="STRING"/>
+    <field number="40" name="OrdType" type="CHAR"/>
+    <field number="44" name="Price" type="PRICE"/>
+    <field number="99" name="StopPx" type="PRICE"/>
+  </fields>
+</fix>"#;
+
+    #[test]
+    pub fn required_if() {
+        let dd = DataDictionary::load_from_string(REQUIRED_IF_DICTIONARY).unwrap();
+        let conditions = dd.messages()["D"].conditionally_required_fields();
+        assert_eq!(conditions[&tags::Price].tag(), tags::OrdType);
+        assert_eq!(conditions[&tags::Price].values().len(), 2);
+
+        let mut message = Message::default();
+        message.header_mut().set_tag_value(tags::BeginString, "FIX.4.4");
+        message.header_mut().set_tag_value(tags::MsgType, "D");
+        message.set_tag_value(tags::OrdType, "1");
+        assert!(DataDictionary::validate(&message, None, &dd, "FIX.4.4", "D").is_ok());
+
+        message.set_tag_value(tags::OrdType, "4");
+        match DataDictionary::validate(&message, None, &dd, "FIX.4.4", "D") {
+            Err(MessageValidationError::TagException(e)) => assert_eq!(e.field(), tags::Price),
+            other => panic!("expected required tag missing, got {:?}", other),
+        }
+
+        message.set_tag_value(tags::Price, "10.5");
+        assert!(DataDictionary::validate(&message, None, &dd, "FIX.4.4", "D").is_ok());
+    }
+
+    const REQUIRED_IF_ENCLOSING_DICTIONARY: &str = r#"<fix major="4" minor="4">
+  <header>
+    <field name="BeginString" required="Y"/>
+    <field name="MsgType" required="Y"/>
+    <field name="OnBehalfOfCompID" required="N"/>
+  </header>
+  <trailer/>
+  <messages>
+    <message name="NewOrderSingle" msgtype="D" msgcat="app">
+      <field name="OrdType" required="Y"/>
+      <field name="Account" required="N" required_if="OnBehalfOfCompID=BROKER"/>
+      <group name="NoPartyIDs" required="N">
+        <field name="PartyID" required="N"/>
+        <field name="PartyIDSource" required="N" required_if="OrdType=4"/>
+        <field name="PartyRole" required="N"/>
+      </group>
+    </message>
+  </messages>
+  <components/>
+  <fields>
+    <field number="1" name="Account" type="STRING"/>
+    <field number="8" name="BeginString" type="STRING"/>
+    <field number="35" name="MsgType" type="STRING"/>
+    <field number="40" name="OrdType" type="CHAR"/>
+    <field number="115" name="OnBehalfOfCompID" type="STRING"/>
+    <field number="447" name="PartyIDSource" type="CHAR"/>
+    <field number="448" name="PartyID" type="STRING"/>
+    <field number="452" name="PartyRole" type="INT"/>
+    <field number="453" name="NoPartyIDs" type="NUMINGROUP"/>
+  </fields>
+</fix>"#;
+
+    fn required_tag_missing(message: &Message, dd: &DataDictionary) -> Option<super::Tag> {
+        match DataDictionary::validate(message, None, dd, "FIX.4.4", "D") {
+            Ok(()) => None,
+            Err(MessageValidationError::TagException(e)) => Some(e.field()),
+            Err(other) => panic!("expected required tag missing, got {:?}", other),
+        }
+    }
+
+    #[test]
+    pub fn required_if_header_field() {
+        let dd = DataDictionary::load_from_string(REQUIRED_IF_ENCLOSING_DICTIONARY).unwrap();
+        let mut message = Message::default();
+        message.header_mut().set_tag_value(tags::BeginString, "FIX.4.4");
+        message.header_mut().set_tag_value(tags::MsgType, "D");
+        message.set_tag_value(tags::OrdType, "1");
+        assert_eq!(required_tag_missing(&message, &dd), None);
+
+        message.header_mut().set_tag_value(tags::OnBehalfOfCompID, "BROKER");
+        assert_eq!(required_tag_missing(&message, &dd), Some(tags::Account));
+
+        message.set_tag_value(tags::Account, "ACC");
+        assert_eq!(required_tag_missing(&message, &dd), None);
+    }
+
+    #[test]
+    pub fn required_if_message_field_in_group() {
+        let dd = DataDictionary::load_from_string(REQUIRED_IF_ENCLOSING_DICTIONARY).unwrap();
+        let mut message = Message::default();
+        message.header_mut().set_tag_value(tags::BeginString, "FIX.4.4");
+        message.header_mut().set_tag_value(tags::MsgType, "D");
+        message.set_tag_value(tags::OrdType, "1");
+        let mut party = Group::new(tags::NoPartyIDs, tags::PartyID);
+        party.set_tag_value(tags::PartyID, "P1");
+        message.add_group(tags::NoPartyIDs, &party, Some(true));
+        assert_eq!(required_tag_missing(&message, &dd), None);
+
+        message.set_tag_value(tags::OrdType, "4");
+        assert_eq!(required_tag_missing(&message, &dd), Some(tags::PartyIDSource));
+
+        let mut message = Message::default();
+        message.header_mut().set_tag_value(tags::BeginString, "FIX.4.4");
+        message.header_mut().set_tag_value(tags::MsgType, "D");
+        message.set_tag_value(tags::OrdType, "4");
+        party.set_tag_value(tags::PartyIDSource, "D");
+        message.add_group(tags::NoPartyIDs, &party, Some(true));
+        assert_eq!(required_tag_missing(&message, &dd), None);
+    }
+
+    #[test]
+    pub fn required_if_unknown_field() {
+        let xml = REQUIRED_IF_DICTIONARY.replace("OrdType=3", "Unknown=3");
+        let result = DataDictionary::load_from_string(&xml);
+        assert!(matches!(result, Err(DataDictionaryError::InvalidRequiredCondition { .. })));
+    }
+
+    #[test]
+    pub fn dangling_component() {
+        let unused = r#"<components>
+    <component name="Unused">
+      <field name="Unknown" required="N"/>
+    </component>
+  </components>"#;
+        let xml = REQUIRED_IF_DICTIONARY.replace("<components/>", unused);
+        let dd = DataDictionary::load_from_string(&xml).unwrap();
+        assert!(dd.components().is_empty());
+
+        let used = xml.replace(
+            r#"<field name="OrdType" required="Y"/>"#,
+            r#"<field name="OrdType" required="Y"/><component name="Unused" required="N"/>"#,
+        );
+        let result = DataDictionary::load_from_string(&used);
+        assert!(matches!(result, Err(DataDictionaryError::Missing { .. })));
+    }
 
     #[test]
     pub fn fix40() {
@@ -0,0 +1,413 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::field_map::Tag;
+
+use super::parse_required_condition;
+use super::DDEntry;
+use super::DDField;
+use super::DDGroup;
+use super::DDMap;
+use super::DataDictionary;
+use super::DataDictionaryError;
+use super::Field;
+use super::GoM;
+
+#[derive(Clone, Debug)]
+pub(super) enum MapEntry {
+    Field { name: Arc<str>, required: bool, required_if: Option<Arc<str>> },
+    Group { name: Arc<str>, required: bool, map: MapBuilder },
+    Component { name: Arc<str>, required: bool },
+}
+
+/// Content of a message, component, group, header or trailer for [`DataDictionaryBuilder`].
+///
+/// Fields, groups and components are referenced by name and resolved in [`DataDictionaryBuilder::build`],
+/// the same way `<field>`, `<group>` and `<component>` elements are in the XML format.
+#[derive(Clone, Debug, Default)]
+pub struct MapBuilder {
+    pub(super) entries: Vec<MapEntry>,
+}
+
+impl MapBuilder {
+    pub fn new() -> Self {
+        MapBuilder::default()
+    }
+
+    pub fn field(mut self, name: &str, required: bool) -> Self {
+        self.entries.push(MapEntry::Field { name: name.into(), required, required_if: None });
+        self
+    }
+
+    /// Optional field which is required when `condition` holds, e.g. `"OrdType=2|4"`.
+    pub fn field_required_if(mut self, name: &str, condition: &str) -> Self {
+        self.entries.push(MapEntry::Field { name: name.into(), required: false, required_if: Some(condition.into()) });
+        self
+    }
+
+    /// Repeating group counted by the field `name`, the first entry of `group` is the delimiter.
+    pub fn group(mut self, name: &str, required: bool, group: MapBuilder) -> Self {
+        self.entries.push(MapEntry::Group { name: name.into(), required, map: group });
+        self
+    }
+
+    pub fn component(mut self, name: &str, required: bool) -> Self {
+        self.entries.push(MapEntry::Component { name: name.into(), required });
+        self
+    }
+}
+
+#[derive(Clone, Debug)]
+struct MessageDefinition {
+    name: Arc<str>,
+    msg_type: Arc<str>,
+    admin: bool,
+    map: MapBuilder,
+}
+
+/// Builds a [`DataDictionary`] without going through XML.
+///
+/// ```
+/// use dfx_base::data_dictionary::{DataDictionaryBuilder, MapBuilder};
+///
+/// let dd = DataDictionaryBuilder::new("FIX.4.4")
+///     .field(35, "MsgType", "STRING")
+///     .field_with_enums(40, "OrdType", "CHAR", &[("1", "MARKET"), ("2", "LIMIT")])
+///     .field(44, "Price", "PRICE")
+///     .field(448, "PartyID", "STRING")
+///     .field(453, "NoPartyIDs", "NUMINGROUP")
+///     .header(MapBuilder::new().field("MsgType", true))
+///     .component("Parties", MapBuilder::new()
+///         .group("NoPartyIDs", false, MapBuilder::new().field("PartyID", true)))
+///     .message("NewOrderSingle", "D", MapBuilder::new()
+///         .field("OrdType", true)
+///         .field_required_if("Price", "OrdType=2")
+///         .component("Parties", false))
+///     .build()
+///     .unwrap();
+/// assert!(dd.messages()["D"].is_group(453));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct DataDictionaryBuilder {
+    version: Option<Arc<str>>,
+    fields: Vec<DDField>,
+    components: BTreeMap<Arc<str>, MapBuilder>,
+    messages: Vec<MessageDefinition>,
+    header: MapBuilder,
+    trailer: MapBuilder,
+}
+
+impl DataDictionaryBuilder {
+    /// `version` is the BeginString, e.g. `FIX.4.4` or `FIXT.1.1`.
+    pub fn new(version: &str) -> Self {
+        DataDictionaryBuilder {
+            version: Some(version.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Builder for a dictionary without version, which only checks the fields it knows about.
+    pub fn without_version() -> Self {
+        DataDictionaryBuilder::default()
+    }
+
+    pub fn field(self, tag: Tag, name: &str, field_type: &str) -> Self {
+        self.add_field(DDField::new(tag, name.into(), BTreeMap::new(), field_type.into()))
+    }
+
+    /// Field restricted to `enums`, given as `(value, description)` pairs.
+    pub fn field_with_enums(self, tag: Tag, name: &str, field_type: &str, enums: &[(&str, &str)]) -> Self {
+        let enums = enums.iter().map(|(value, description)| ((*value).into(), (*description).into())).collect();
+        self.add_field(DDField::new(tag, name.into(), enums, field_type.into()))
+    }
+
+    pub fn add_field(mut self, field: DDField) -> Self {
+        self.fields.push(field);
+        self
+    }
+
+    pub fn component(mut self, name: &str, component: MapBuilder) -> Self {
+        self.components.insert(name.into(), component);
+        self
+    }
+
+    pub fn message(mut self, name: &str, msg_type: &str, message: MapBuilder) -> Self {
+        self.messages.push(MessageDefinition { name: name.into(), msg_type: msg_type.into(), admin: false, map: message });
+        self
+    }
+
+    pub fn admin_message(mut self, name: &str, msg_type: &str, message: MapBuilder) -> Self {
+        self.messages.push(MessageDefinition { name: name.into(), msg_type: msg_type.into(), admin: true, map: message });
+        self
+    }
+
+    pub fn header(mut self, header: MapBuilder) -> Self {
+        self.header = header;
+        self
+    }
+
+    pub fn trailer(mut self, trailer: MapBuilder) -> Self {
+        self.trailer = trailer;
+        self
+    }
+
+    /// Resolves all references, fails with [`DataDictionaryError::Missing`] for unknown fields or components
+    /// and with [`DataDictionaryError::ComponentCycle`] for a component containing itself.
+    pub fn build(self) -> Result<DataDictionary, DataDictionaryError> {
+        let mut fields_by_tag: BTreeMap<Tag, Field> = BTreeMap::new();
+        let mut fields_by_name: BTreeMap<Arc<str>, Field> = BTreeMap::new();
+        for field in self.fields {
+            let field = Arc::new(field);
+            fields_by_tag.insert(field.tag(), field.clone());
+            fields_by_name.insert(field.name().clone(), field);
+        }
+
+        let mut messages = BTreeMap::new();
+        for message in self.messages.iter() {
+            let mut dd_map = DDMap::new_with_values(message.name.clone(), message.msg_type.clone(), message.admin);
+            build_map(&message.map, &mut dd_map, &fields_by_name, &self.components)?;
+            messages.insert(message.msg_type.clone(), dd_map);
+        }
+
+        let mut components = BTreeMap::new();
+        for (name, component) in self.components.iter() {
+            let mut dd_map = DDMap::new(name.clone());
+            build_map(component, &mut dd_map, &fields_by_name, &self.components)?;
+            components.insert(name.clone(), dd_map);
+        }
+
+        let mut header = DDMap::new("header".into());
+        build_map(&self.header, &mut header, &fields_by_name, &self.components)?;
+        let mut trailer = DDMap::new("trailer".into());
+        build_map(&self.trailer, &mut trailer, &fields_by_name, &self.components)?;
+
+        Ok(DataDictionary::from_parts(self.version, fields_by_tag, fields_by_name, messages, components, header, trailer))
+    }
+}
+
+/// Fills `dd_map` with the entries of `builder`, used for dictionaries built in code and loaded from XML.
+pub(super) fn build_map(
+    builder: &MapBuilder,
+    dd_map: &mut DDMap,
+    fields_by_name: &BTreeMap<Arc<str>, Field>,
+    components: &BTreeMap<Arc<str>, MapBuilder>,
+) -> Result<(), DataDictionaryError> {
+    build_map_inner(builder, &mut GoM::Map(dd_map), fields_by_name, components, None, &mut Vec::new())
+}
+
+/// `expanding` holds the components being expanded around `builder`.
+fn build_map_inner(
+    builder: &MapBuilder,
+    dd_map: &mut GoM<'_>,
+    fields_by_name: &BTreeMap<Arc<str>, Field>,
+    components: &BTreeMap<Arc<str>, MapBuilder>,
+    component_required: Option<bool>,
+    expanding: &mut Vec<Arc<str>>,
+) -> Result<(), DataDictionaryError> {
+    for entry in builder.entries.iter() {
+        let (name, required, required_if, group) = match entry {
+            MapEntry::Field { name, required, required_if } => (name, *required, required_if.as_ref(), None),
+            MapEntry::Group { name, required, map } => (name, *required, None, Some(map)),
+            MapEntry::Component { name, required } => {
+                let component = components.get(name)
+                    .ok_or(DataDictionaryError::Missing { entry_type: "component".into(), name: name.clone() })?;
+                if expanding.contains(name) {
+                    return Err(DataDictionaryError::ComponentCycle { name: name.clone() });
+                }
+                if component_required.is_none() {
+                    dd_map.layout.push(DDEntry::Component { name: name.clone(), required: *required });
+                }
+                expanding.push(name.clone());
+                build_map_inner(component, dd_map, fields_by_name, components, Some(*required), expanding)?;
+                expanding.pop();
+                continue;
+            }
+        };
+
+        let dd_field = fields_by_name.get(name)
+            .ok_or(DataDictionaryError::Missing { entry_type: "field".into(), name: name.clone() })?.clone();
+        let required = required && component_required.unwrap_or(true);
+        if required {
+            dd_map.required_fields.insert(dd_field.tag());
+        }
+        if let Some(condition) = required_if {
+            let condition = parse_required_condition(condition, fields_by_name)?;
+            dd_map.conditionally_required_fields.insert(dd_field.tag(), condition);
+        }
+        if !dd_map.is_field(dd_field.tag()) {
+            dd_map.fields.insert(dd_field.tag(), dd_field.clone());
+        }
+        // fields of an expanded component are laid out under the component reference
+        if component_required.is_none() {
+            dd_map.layout.push(match group {
+                Some(_) => DDEntry::Group(dd_field.tag()),
+                None => DDEntry::Field(dd_field.tag()),
+            });
+        }
+        if let GoM::Group(grp) = dd_map {
+            if grp.delim == 0 {
+                grp.delim = dd_field.tag();
+            }
+        }
+
+        if let Some(group) = group {
+            let mut dd_grp = DDGroup::new();
+            dd_grp.num_fld = dd_field.tag();
+            dd_grp.name = name.clone();
+            dd_grp.required = required;
+            build_map_inner(group, &mut GoM::Group(&mut dd_grp), fields_by_name, components, None, expanding)?;
+            dd_map.groups.insert(dd_field.tag(), dd_grp.into());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DataDictionaryBuilder;
+    use super::MapBuilder;
+    use crate::data_dictionary::diff;
+    use crate::data_dictionary::DDEntry;
+    use crate::data_dictionary::DDField;
+    use crate::data_dictionary::DataDictionary;
+    use crate::data_dictionary::DataDictionaryError;
+    use crate::message::Message;
+    use crate::tags;
+
+    const XML: &str = r#"<fix major="4" minor="4">
+  <header>
+    <field name="BeginString" required="Y"/>
+    <field name="MsgType" required="Y"/>
+  </header>
+  <trailer/>
+  <messages>
+    <message name="NewOrderSingle" msgtype="D" msgcat="app">
+      <field name="OrdType" required="Y"/>
+      <field name="Price" required="N" required_if="OrdType=2"/>
+      <component name="Parties" required="N"/>
+    </message>
+  </messages>
+  <components>
+    <component name="Parties">
+      <group name="NoPartyIDs" required="N">
+        <field name="PartyID" required="Y"/>
+        <field name="PartyIDSource" required="N"/>
+      </group>
+    </component>
+  </components>
+  <fields>
+    <field number="8" name="BeginString" type="STRING"/>
+    <field number="35" name="MsgType" type="STRING"/>
+    <field number="40" name="OrdType" type="CHAR">
+      <value enum="1" description="MARKET"/>
+      <value enum="2" description="LIMIT"/>
+    </field>
+    <field number="44" name="Price" type="PRICE"/>
+    <field number="447" name="PartyIDSource" type="CHAR"/>
+    <field number="448" name="PartyID" type="STRING"/>
+    <field number="453" name="NoPartyIDs" type="NUMINGROUP"/>
+  </fields>
+</fix>"#;
+
+    fn builder() -> DataDictionaryBuilder {
+        DataDictionaryBuilder::new("FIX.4.4")
+            .field(8, "BeginString", "STRING")
+            .field(35, "MsgType", "STRING")
+            .field_with_enums(40, "OrdType", "CHAR", &[("1", "MARKET"), ("2", "LIMIT")])
+            .field(44, "Price", "PRICE")
+            .field(447, "PartyIDSource", "CHAR")
+            .field(448, "PartyID", "STRING")
+            .field(453, "NoPartyIDs", "NUMINGROUP")
+            .header(MapBuilder::new().field("BeginString", true).field("MsgType", true))
+            .component("Parties", MapBuilder::new()
+                .group("NoPartyIDs", false, MapBuilder::new()
+                    .field("PartyID", true)
+                    .field("PartyIDSource", false)))
+            .message("NewOrderSingle", "D", MapBuilder::new()
+                .field("OrdType", true)
+                .field_required_if("Price", "OrdType=2")
+                .component("Parties", false))
+    }
+
+    #[test]
+    pub fn matches_xml() {
+        let built = builder().build().unwrap();
+        let parsed = DataDictionary::load_from_string(XML).unwrap();
+        assert_eq!(built.version(), parsed.version());
+        assert!(diff(&parsed, &built).is_empty(), "{}", diff(&parsed, &built));
+
+        let group = built.messages()["D"].get_group(tags::NoPartyIDs).unwrap();
+        assert_eq!(group.delim(), tags::PartyID);
+        // required inside an optional component is not enforced on the message
+        assert!(!group.required());
+        assert!(group.required_fields().contains(&tags::PartyID));
+    }
+
+    #[test]
+    pub fn validates_messages() {
+        let dd = builder().build().unwrap();
+        let mut message = Message::default();
+        message.header_mut().set_tag_value(tags::BeginString, "FIX.4.4");
+        message.header_mut().set_tag_value(tags::MsgType, "D");
+        message.set_tag_value(tags::OrdType, "2");
+        assert!(DataDictionary::validate(&message, None, &dd, "FIX.4.4", "D").is_err());
+        message.set_tag_value(tags::Price, "1.25");
+        assert!(DataDictionary::validate(&message, None, &dd, "FIX.4.4", "D").is_ok());
+    }
+
+    #[test]
+    pub fn unknown_references() {
+        let result = builder().message("Unknown", "U", MapBuilder::new().field("Missing", true)).build();
+        assert!(matches!(result, Err(DataDictionaryError::Missing { name, .. }) if name.as_ref() == "Missing"));
+        let result = builder().message("Unknown", "U", MapBuilder::new().component("Missing", true)).build();
+        assert!(matches!(result, Err(DataDictionaryError::Missing { entry_type, .. }) if entry_type.as_ref() == "component"));
+    }
+
+    #[test]
+    pub fn keeps_declaration_order() {
+        let built = builder()
+            .message("OrderCancelRequest", "F", MapBuilder::new()
+                .field("Price", false)
+                .component("Parties", true)
+                .field("OrdType", true))
+            .build()
+            .unwrap();
+        assert_eq!(built.messages()["F"].layout(), &[
+            DDEntry::Field(tags::Price),
+            DDEntry::Component { name: "Parties".into(), required: true },
+            DDEntry::Field(tags::OrdType),
+        ]);
+
+        let reloaded = DataDictionary::load_from_string(&built.to_xml_string()).unwrap();
+        assert_eq!(built.messages()["F"].layout(), reloaded.messages()["F"].layout());
+        assert!(diff(&built, &reloaded).is_empty(), "{}", diff(&built, &reloaded));
+    }
+
+    #[test]
+    pub fn component_cycle() {
+        let result = builder()
+            .component("Outer", MapBuilder::new().field("Price", false).component("Inner", false))
+            .component("Inner", MapBuilder::new()
+                .group("NoPartyIDs", false, MapBuilder::new().field("PartyID", true).component("Outer", false)))
+            .message("Cycle", "U", MapBuilder::new().component("Outer", true))
+            .build();
+        assert!(matches!(result, Err(DataDictionaryError::ComponentCycle { name }) if name.as_ref() == "Outer"));
+
+        let xml = XML.replace(
+            r#"<field name="PartyIDSource" required="N"/>"#,
+            r#"<field name="PartyIDSource" required="N"/>
+        <component name="Parties" required="N"/>"#,
+        );
+        let result = DataDictionary::load_from_string(&xml);
+        assert!(matches!(result, Err(DataDictionaryError::ComponentCycle { name }) if name.as_ref() == "Parties"));
+    }
+
+    #[test]
+    pub fn field_from_xml_str() {
+        let field = DDField::from_xml_str(r#"<field number="40" name="OrdType" type="CHAR"><value enum="1" description="MARKET"/></field>"#).unwrap();
+        assert_eq!(field.tag(), tags::OrdType);
+        assert_eq!(field.name().as_ref(), "OrdType");
+        assert_eq!(field.enums().len(), 1);
+        assert!(DDField::from_xml_str(r#"<field name="OrdType" type="CHAR"/>"#).is_err());
+    }
+}
@@ -0,0 +1,531 @@
+use std::collections::BTreeSet;
+use std::fmt::Display;
+use std::sync::Arc;
+
+use crate::field_map::Tag;
+
+use super::DDEntry;
+use super::DDMap;
+use super::DataDictionary;
+use super::RequiredCondition;
+
+/// Whether a [`DictionaryChange`] can cause messages that were valid under the old dictionary to be
+/// rejected under the new one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Compatibility {
+    NonBreaking,
+    Breaking,
+}
+
+impl Display for Compatibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Compatibility::NonBreaking => f.write_str("non-breaking"),
+            Compatibility::Breaking => f.write_str("breaking"),
+        }
+    }
+}
+
+/// A single difference between two dictionaries.
+///
+/// `location` is `header`, `trailer` or the MsgType, followed by the group counter names for
+/// nested groups, e.g. `D/NoPartyIDs`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DictionaryChange {
+    FieldAdded { tag: Tag, name: Arc<str> },
+    FieldRemoved { tag: Tag, name: Arc<str> },
+    FieldRenamed { tag: Tag, old_name: Arc<str>, new_name: Arc<str> },
+    FieldRetyped { tag: Tag, name: Arc<str>, old_type: Arc<str>, new_type: Arc<str> },
+    EnumAdded { tag: Tag, name: Arc<str>, value: Arc<str>, restricted: bool },
+    EnumRemoved { tag: Tag, name: Arc<str>, value: Arc<str> },
+    MessageAdded { msg_type: Arc<str>, name: Arc<str> },
+    MessageRemoved { msg_type: Arc<str>, name: Arc<str> },
+    MessageFieldAdded { location: Arc<str>, tag: Tag, required: bool },
+    MessageFieldRemoved { location: Arc<str>, tag: Tag },
+    RequiredChanged { location: Arc<str>, tag: Tag, required: bool },
+    ConditionChanged {
+        location: Arc<str>,
+        tag: Tag,
+        old: Option<RequiredCondition>,
+        new: Option<RequiredCondition>,
+    },
+    /// `tag` became a repeating group counter (`group: true`) or a plain field (`group: false`).
+    GroupChanged { location: Arc<str>, tag: Tag, group: bool },
+    /// The first field of the entries of group `tag` changed.
+    DelimiterChanged { location: Arc<str>, tag: Tag, old_delim: Tag, new_delim: Tag },
+    /// The fields present in both dictionaries are declared in a different order, `old` and `new`
+    /// list them in their respective order.
+    FieldOrderChanged { location: Arc<str>, old: Vec<Tag>, new: Vec<Tag> },
+}
+
+impl DictionaryChange {
+    pub fn compatibility(&self) -> Compatibility {
+        let breaking = match self {
+            DictionaryChange::FieldAdded { .. } => false,
+            DictionaryChange::FieldRemoved { .. } => true,
+            DictionaryChange::FieldRenamed { .. } => false,
+            DictionaryChange::FieldRetyped { .. } => true,
+            // adding the first enum value restricts a previously free field
+            DictionaryChange::EnumAdded { restricted, .. } => *restricted,
+            DictionaryChange::EnumRemoved { .. } => true,
+            DictionaryChange::MessageAdded { .. } => false,
+            DictionaryChange::MessageRemoved { .. } => true,
+            DictionaryChange::MessageFieldAdded { required, .. } => *required,
+            DictionaryChange::MessageFieldRemoved { .. } => true,
+            DictionaryChange::RequiredChanged { required, .. } => *required,
+            DictionaryChange::ConditionChanged { new, .. } => new.is_some(),
+            DictionaryChange::GroupChanged { .. } => true,
+            DictionaryChange::DelimiterChanged { .. } => true,
+            // rejected by dictionaries checking the field order
+            DictionaryChange::FieldOrderChanged { .. } => true,
+        };
+        if breaking {
+            Compatibility::Breaking
+        } else {
+            Compatibility::NonBreaking
+        }
+    }
+
+    pub fn is_breaking(&self) -> bool {
+        self.compatibility() == Compatibility::Breaking
+    }
+}
+
+fn write_condition(f: &mut std::fmt::Formatter<'_>, condition: &Option<RequiredCondition>) -> std::fmt::Result {
+    match condition {
+        Some(condition) => f.write_fmt(format_args!("{}={}", condition.tag(), condition.values().join("|"))),
+        None => f.write_str("none"),
+    }
+}
+
+impl Display for DictionaryChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DictionaryChange::FieldAdded { tag, name } => {
+                f.write_fmt(format_args!("field {tag} {name} added"))
+            }
+            DictionaryChange::FieldRemoved { tag, name } => {
+                f.write_fmt(format_args!("field {tag} {name} removed"))
+            }
+            DictionaryChange::FieldRenamed { tag, old_name, new_name } => {
+                f.write_fmt(format_args!("field {tag} renamed {old_name} -> {new_name}"))
+            }
+            DictionaryChange::FieldRetyped { tag, name, old_type, new_type } => {
+                f.write_fmt(format_args!("field {tag} {name} retyped {old_type} -> {new_type}"))
+            }
+            DictionaryChange::EnumAdded { tag, name, value, .. } => {
+                f.write_fmt(format_args!("field {tag} {name} enum value '{value}' added"))
+            }
+            DictionaryChange::EnumRemoved { tag, name, value } => {
+                f.write_fmt(format_args!("field {tag} {name} enum value '{value}' removed"))
+            }
+            DictionaryChange::MessageAdded { msg_type, name } => {
+                f.write_fmt(format_args!("message {msg_type} {name} added"))
+            }
+            DictionaryChange::MessageRemoved { msg_type, name } => {
+                f.write_fmt(format_args!("message {msg_type} {name} removed"))
+            }
+            DictionaryChange::MessageFieldAdded { location, tag, required } => {
+                let required = if *required { "required" } else { "optional" };
+                f.write_fmt(format_args!("{location}: {required} field {tag} added"))
+            }
+            DictionaryChange::MessageFieldRemoved { location, tag } => {
+                f.write_fmt(format_args!("{location}: field {tag} removed"))
+            }
+            DictionaryChange::RequiredChanged { location, tag, required } => {
+                let required = if *required { "required" } else { "optional" };
+                f.write_fmt(format_args!("{location}: field {tag} is now {required}"))
+            }
+            DictionaryChange::ConditionChanged { location, tag, old, new } => {
+                f.write_fmt(format_args!("{location}: field {tag} required_if changed "))?;
+                write_condition(f, old)?;
+                f.write_str(" -> ")?;
+                write_condition(f, new)
+            }
+            DictionaryChange::GroupChanged { location, tag, group } => {
+                let group = if *group { "a repeating group" } else { "no longer a repeating group" };
+                f.write_fmt(format_args!("{location}: field {tag} is now {group}"))
+            }
+            DictionaryChange::DelimiterChanged { location, tag, old_delim, new_delim } => {
+                f.write_fmt(format_args!("{location}: group {tag} delimiter changed {old_delim} -> {new_delim}"))
+            }
+            DictionaryChange::FieldOrderChanged { location, old, new } => {
+                f.write_fmt(format_args!("{location}: field order changed {old:?} -> {new:?}"))
+            }
+        }
+    }
+}
+
+/// Result of [`diff`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DictionaryDiff {
+    changes: Vec<DictionaryChange>,
+}
+
+impl DictionaryDiff {
+    pub fn changes(&self) -> &[DictionaryChange] {
+        &self.changes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    pub fn is_breaking(&self) -> bool {
+        self.changes.iter().any(DictionaryChange::is_breaking)
+    }
+
+    pub fn breaking(&self) -> impl Iterator<Item = &DictionaryChange> {
+        self.changes.iter().filter(|c| c.is_breaking())
+    }
+
+    pub fn non_breaking(&self) -> impl Iterator<Item = &DictionaryChange> {
+        self.changes.iter().filter(|c| !c.is_breaking())
+    }
+}
+
+impl Display for DictionaryDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_empty() {
+            return f.write_str("No changes\n");
+        }
+        let breaking = self.breaking().count();
+        f.write_fmt(format_args!(
+            "{} changes, {} breaking, {} non-breaking\n",
+            self.changes.len(),
+            breaking,
+            self.changes.len() - breaking
+        ))?;
+        for change in self.changes.iter() {
+            f.write_fmt(format_args!("[{}] {}\n", change.compatibility(), change))?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares `old` against `new` and classifies every change as breaking or non-breaking.
+pub fn diff(old: &DataDictionary, new: &DataDictionary) -> DictionaryDiff {
+    let mut changes = Vec::new();
+    diff_fields(old, new, &mut changes);
+    diff_map("header", (old, &old.header), (new, &new.header), &mut changes);
+    diff_map("trailer", (old, &old.trailer), (new, &new.trailer), &mut changes);
+    diff_messages(old, new, &mut changes);
+    DictionaryDiff { changes }
+}
+
+fn diff_fields(old: &DataDictionary, new: &DataDictionary, changes: &mut Vec<DictionaryChange>) {
+    let tags: BTreeSet<Tag> = old.fields_by_tag.keys().chain(new.fields_by_tag.keys()).copied().collect();
+    for tag in tags {
+        match (old.fields_by_tag.get(&tag), new.fields_by_tag.get(&tag)) {
+            (Some(old_field), None) => changes.push(DictionaryChange::FieldRemoved { tag, name: old_field.name().clone() }),
+            (None, Some(new_field)) => changes.push(DictionaryChange::FieldAdded { tag, name: new_field.name().clone() }),
+            (Some(old_field), Some(new_field)) => {
+                let name = new_field.name().clone();
+                if old_field.name() != new_field.name() {
+                    changes.push(DictionaryChange::FieldRenamed {
+                        tag,
+                        old_name: old_field.name().clone(),
+                        new_name: name.clone(),
+                    });
+                }
+                if old_field.field_type() != new_field.field_type() {
+                    changes.push(DictionaryChange::FieldRetyped {
+                        tag,
+                        name: name.clone(),
+                        old_type: old_field.field_type().clone(),
+                        new_type: new_field.field_type().clone(),
+                    });
+                }
+                let restricted = !old_field.has_enums();
+                for value in new_field.enums().keys().filter(|v| !old_field.enums().contains_key(*v)) {
+                    changes.push(DictionaryChange::EnumAdded { tag, name: name.clone(), value: value.clone(), restricted });
+                }
+                for value in old_field.enums().keys().filter(|v| !new_field.enums().contains_key(*v)) {
+                    changes.push(DictionaryChange::EnumRemoved { tag, name: name.clone(), value: value.clone() });
+                }
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+fn diff_messages(old: &DataDictionary, new: &DataDictionary, changes: &mut Vec<DictionaryChange>) {
+    let msg_types: BTreeSet<&Arc<str>> = old.messages.keys().chain(new.messages.keys()).collect();
+    for msg_type in msg_types {
+        match (old.messages.get(msg_type), new.messages.get(msg_type)) {
+            (Some(old_map), None) => changes.push(DictionaryChange::MessageRemoved { msg_type: msg_type.clone(), name: old_map.name().clone() }),
+            (None, Some(new_map)) => changes.push(DictionaryChange::MessageAdded { msg_type: msg_type.clone(), name: new_map.name().clone() }),
+            (Some(old_map), Some(new_map)) => diff_map(msg_type, (old, old_map), (new, new_map), changes),
+            (None, None) => {}
+        }
+    }
+}
+
+/// Compares the maps at `location`, each given with the dictionary holding its components.
+fn diff_map(
+    location: &str,
+    (old_dd, old): (&DataDictionary, &DDMap),
+    (new_dd, new): (&DataDictionary, &DDMap),
+    changes: &mut Vec<DictionaryChange>,
+) {
+    let tags: BTreeSet<Tag> = old.fields().keys().chain(new.fields().keys()).copied().collect();
+    for tag in tags {
+        match (old.fields().get(&tag), new.fields().get(&tag)) {
+            (Some(_), None) => changes.push(DictionaryChange::MessageFieldRemoved { location: location.into(), tag }),
+            (None, Some(_)) => changes.push(DictionaryChange::MessageFieldAdded {
+                location: location.into(),
+                tag,
+                required: new.required_fields().contains(&tag),
+            }),
+            (Some(field), Some(_)) => {
+                let required = new.required_fields().contains(&tag);
+                if old.required_fields().contains(&tag) != required {
+                    changes.push(DictionaryChange::RequiredChanged { location: location.into(), tag, required });
+                }
+                let old_condition = old.conditionally_required_fields().get(&tag);
+                let new_condition = new.conditionally_required_fields().get(&tag);
+                if old_condition != new_condition {
+                    changes.push(DictionaryChange::ConditionChanged {
+                        location: location.into(),
+                        tag,
+                        old: old_condition.cloned(),
+                        new: new_condition.cloned(),
+                    });
+                }
+                match (old.get_group(tag), new.get_group(tag)) {
+                    (Some(old_group), Some(new_group)) => {
+                        if old_group.delim() != new_group.delim() {
+                            changes.push(DictionaryChange::DelimiterChanged {
+                                location: location.into(),
+                                tag,
+                                old_delim: old_group.delim(),
+                                new_delim: new_group.delim(),
+                            });
+                        }
+                        let location = format!("{}/{}", location, field.name());
+                        diff_map(&location, (old_dd, old_group), (new_dd, new_group), changes);
+                    }
+                    (None, Some(_)) => changes.push(DictionaryChange::GroupChanged { location: location.into(), tag, group: true }),
+                    (Some(_), None) => changes.push(DictionaryChange::GroupChanged { location: location.into(), tag, group: false }),
+                    (None, None) => {}
+                }
+            }
+            (None, None) => {}
+        }
+    }
+
+    // maps filled with add_field have no declared order to compare
+    if old.layout().is_empty() || new.layout().is_empty() {
+        return;
+    }
+    let old_order = field_order(old_dd, old);
+    let new_order = field_order(new_dd, new);
+    let old_order: Vec<Tag> = old_order.into_iter().filter(|tag| new.is_field(*tag)).collect();
+    let new_order: Vec<Tag> = new_order.into_iter().filter(|tag| old.is_field(*tag)).collect();
+    if old_order != new_order {
+        changes.push(DictionaryChange::FieldOrderChanged { location: location.into(), old: old_order, new: new_order });
+    }
+}
+
+/// The fields of `map` in declaration order, components expanded.
+fn field_order(dd: &DataDictionary, map: &DDMap) -> Vec<Tag> {
+    fn expand(dd: &DataDictionary, layout: &[DDEntry], visited: &mut Vec<Arc<str>>, order: &mut Vec<Tag>) {
+        for entry in layout {
+            match entry {
+                DDEntry::Field(tag) | DDEntry::Group(tag) => {
+                    if !order.contains(tag) {
+                        order.push(*tag);
+                    }
+                }
+                DDEntry::Component { name, .. } => {
+                    if let Some(component) = dd.components().get(name) {
+                        if !visited.contains(name) {
+                            visited.push(name.clone());
+                            expand(dd, component.layout(), visited, order);
+                            visited.pop();
+                        }
+                    }
+                }
+            }
+        }
+    }
+    let mut order = Vec::new();
+    expand(dd, map.layout(), &mut Vec::new(), &mut order);
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::diff;
+    use super::DictionaryChange;
+    use crate::data_dictionary::DataDictionary;
+    use crate::tags;
+
+    const OLD: &str = r#"<fix major="4" minor="4">
+  <header>
+    <field name="MsgType" required="Y"/>
+  </header>
+  <trailer/>
+  <messages>
+    <message name="NewOrderSingle" msgtype="D" msgcat="app">
+      <field name="OrdType" required="Y"/>
+      <field name="Price" required="N"/>
+      <group name="NoPartyIDs" required="N">
+        <field name="PartyID" required="N"/>
+      </group>
+    </message>
+    <message name="OrderCancelRequest" msgtype="F" msgcat="app">
+      <field name="OrdType" required="N"/>
+    </message>
+  </messages>
+  <components/>
+  <fields>
+    <field number="35" name="MsgType" type="STRING"/>
+    <field number="40" name="OrdType" type="CHAR">
+      <value enum="1" description="MARKET"/>
+      <value enum="2" description="LIMIT"/>
+    </field>
+    <field number="44" name="Price" type="PRICE"/>
+    <field number="448" name="PartyID" type="STRING"/>
+    <field number="453" name="NoPartyIDs" type="NUMINGROUP"/>
+  </fields>
+</fix>"#;
+
+    fn new_dictionary() -> String {
+        OLD.replace(r#"<value enum="2" description="LIMIT"/>"#, r#"<value enum="3" description="STOP"/>"#)
+            .replace(r#"<field name="Price" required="N"/>"#, r#"<field name="Price" required="N" required_if="OrdType=3"/>"#)
+            .replace(r#"<field name="PartyID" required="N"/>"#, r#"<field name="PartyID" required="Y"/>"#)
+            .replace(r#"type="PRICE""#, r#"type="STRING""#)
+    }
+
+    #[test]
+    pub fn identical_dictionaries() {
+        let dd = DataDictionary::load_from_string(OLD).unwrap();
+        let report = diff(&dd, &dd);
+        assert!(report.is_empty());
+        assert!(!report.is_breaking());
+    }
+
+    #[test]
+    pub fn reports_changes() {
+        let old = DataDictionary::load_from_string(OLD).unwrap();
+        let new = DataDictionary::load_from_string(&new_dictionary()).unwrap();
+        let report = diff(&old, &new);
+        let changes = report.changes();
+
+        assert!(changes.iter().any(|c| matches!(c, DictionaryChange::FieldRetyped { tag, .. } if *tag == tags::Price)));
+        assert!(changes.iter().any(|c| matches!(c, DictionaryChange::EnumAdded { value, restricted: false, .. } if value.as_ref() == "3")));
+        assert!(changes.iter().any(|c| matches!(c, DictionaryChange::EnumRemoved { value, .. } if value.as_ref() == "2")));
+        assert!(changes.iter().any(|c| matches!(c, DictionaryChange::ConditionChanged { tag, .. } if *tag == tags::Price)));
+        assert!(changes.iter().any(|c| matches!(c,
+            DictionaryChange::RequiredChanged { location, tag, required: true } if location.as_ref() == "D/NoPartyIDs" && *tag == tags::PartyID)));
+        assert!(report.is_breaking());
+
+        // the reverse direction only relaxes the dictionary, apart from the retyped field and removed enum
+        let reverse = diff(&new, &old);
+        assert!(reverse.changes().iter().any(|c| matches!(c, DictionaryChange::RequiredChanged { required: false, .. })));
+        assert!(reverse.non_breaking().any(|c| matches!(c, DictionaryChange::ConditionChanged { new: None, .. })));
+    }
+
+    #[test]
+    pub fn message_added_and_removed() {
+        let old = DataDictionary::load_from_string(OLD).unwrap();
+        let new = DataDictionary::load_from_string(&OLD.replace(r#"msgtype="F""#, r#"msgtype="G""#)).unwrap();
+        let report = diff(&old, &new);
+        assert!(report.non_breaking().any(|c| matches!(c, DictionaryChange::MessageAdded { msg_type, .. } if msg_type.as_ref() == "G")));
+        assert!(report.breaking().any(|c| matches!(c, DictionaryChange::MessageRemoved { msg_type, .. } if msg_type.as_ref() == "F")));
+    }
+
+    #[test]
+    pub fn field_becomes_group() {
+        let old = DataDictionary::load_from_string(OLD).unwrap();
+        let flat = OLD.replace(
+            r#"<group name="NoPartyIDs" required="N">
+        <field name="PartyID" required="N"/>
+      </group>"#,
+            r#"<field name="NoPartyIDs" required="N"/>"#,
+        );
+        let new = DataDictionary::load_from_string(&flat).unwrap();
+
+        let report = diff(&old, &new);
+        assert!(report.breaking().any(|c| matches!(c,
+            DictionaryChange::GroupChanged { location, tag, group: false } if location.as_ref() == "D" && *tag == tags::NoPartyIDs)));
+        let reverse = diff(&new, &old);
+        assert!(reverse.breaking().any(|c| matches!(c,
+            DictionaryChange::GroupChanged { tag, group: true, .. } if *tag == tags::NoPartyIDs)));
+    }
+
+    #[test]
+    pub fn delimiter_changed() {
+        let two_fields = OLD
+            .replace(
+                r#"<field name="PartyID" required="N"/>"#,
+                r#"<field name="PartyID" required="N"/>
+        <field name="PartyIDSource" required="N"/>"#,
+            )
+            .replace(
+                r#"<field number="448" name="PartyID" type="STRING"/>"#,
+                r#"<field number="447" name="PartyIDSource" type="CHAR"/>
+    <field number="448" name="PartyID" type="STRING"/>"#,
+            );
+        let swapped = two_fields.replace(
+            r#"<field name="PartyID" required="N"/>
+        <field name="PartyIDSource" required="N"/>"#,
+            r#"<field name="PartyIDSource" required="N"/>
+        <field name="PartyID" required="N"/>"#,
+        );
+        let old = DataDictionary::load_from_string(&two_fields).unwrap();
+        let new = DataDictionary::load_from_string(&swapped).unwrap();
+
+        let report = diff(&old, &new);
+        assert!(report.breaking().any(|c| matches!(c,
+            DictionaryChange::DelimiterChanged { tag, old_delim, new_delim, .. }
+                if *tag == tags::NoPartyIDs && *old_delim == tags::PartyID && *new_delim == tags::PartyIDSource)));
+        assert!(report.breaking().any(|c| matches!(c,
+            DictionaryChange::FieldOrderChanged { location, .. } if location.as_ref() == "D/NoPartyIDs")));
+    }
+
+    #[test]
+    pub fn field_order_changed() {
+        let old = DataDictionary::load_from_string(OLD).unwrap();
+        let reordered = OLD.replace(
+            r#"<field name="OrdType" required="Y"/>
+      <field name="Price" required="N"/>"#,
+            r#"<field name="Price" required="N"/>
+      <field name="OrdType" required="Y"/>"#,
+        );
+        let new = DataDictionary::load_from_string(&reordered).unwrap();
+
+        let report = diff(&old, &new);
+        assert_eq!(report.changes().len(), 1);
+        assert!(report.breaking().any(|c| matches!(c,
+            DictionaryChange::FieldOrderChanged { location, old, new }
+                if location.as_ref() == "D" && old[..2] == [tags::OrdType, tags::Price] && new[..2] == [tags::Price, tags::OrdType])));
+
+        // a field added in between keeps the order of the others
+        let added = OLD
+            .replace(
+                r#"<field name="OrdType" required="Y"/>
+      <field name="Price" required="N"/>"#,
+                r#"<field name="OrdType" required="Y"/>
+      <field name="StopPx" required="N"/>
+      <field name="Price" required="N"/>"#,
+            )
+            .replace(
+                r#"<field number="448" name="PartyID" type="STRING"/>"#,
+                r#"<field number="99" name="StopPx" type="PRICE"/>
+    <field number="448" name="PartyID" type="STRING"/>"#,
+            );
+        let new = DataDictionary::load_from_string(&added).unwrap();
+        assert!(!diff(&old, &new).is_breaking());
+    }
+
+    #[test]
+    pub fn fix43_to_fix44() {
+        let old = DataDictionary::load_from_string(include_str!("../../../../spec/FIX43.xml")).unwrap();
+        let new = DataDictionary::load_from_string(include_str!("../../../../spec/FIX44.xml")).unwrap();
+        let report = diff(&old, &new);
+        assert!(!report.is_empty());
+        assert!(report.changes().iter().any(|c| matches!(c, DictionaryChange::MessageAdded { .. })));
+    }
+}
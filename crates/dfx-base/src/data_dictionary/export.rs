@@ -0,0 +1,399 @@
+use std::fmt::Write;
+
+use crate::field_map::Tag;
+
+use super::DDEntry;
+use super::DDMap;
+use super::DataDictionary;
+
+/// One line of a message, component or group table, groups are followed by their own rows with `depth + 1`.
+struct Row {
+    depth: usize,
+    tag: Tag,
+    name: String,
+    field_type: String,
+    required: String,
+}
+
+impl DataDictionary {
+    /// Writes the dictionary as QuickFIX XML.
+    ///
+    /// Fields, groups and `<component>` references are written in the order they were declared. Maps
+    /// filled with `add_field` have no layout and are written with their fields ordered by tag, the
+    /// group delimiter first.
+    pub fn to_xml_string(&self) -> String {
+        let mut out = String::new();
+        out.push_str("<fix");
+        if let Some(version) = self.version() {
+            let mut parts = version.splitn(3, '.');
+            let (version_type, major, minor) = (parts.next(), parts.next(), parts.next());
+            if let (Some(version_type), Some(major), Some(minor)) = (version_type, major, minor) {
+                let _ = write!(out, " type=\"{}\" major=\"{}\" minor=\"{}\"", escape_xml(version_type), escape_xml(major), escape_xml(minor));
+            }
+        }
+        out.push_str(">\n");
+
+        self.write_xml_section(&mut out, "header", &self.header);
+
+        out.push_str("  <messages>\n");
+        for (msg_type, message) in self.messages.iter() {
+            let msgcat = if message.admin() { "admin" } else { "app" };
+            let _ = writeln!(out, "    <message name=\"{}\" msgtype=\"{}\" msgcat=\"{}\">", escape_xml(message.name()), escape_xml(msg_type), msgcat);
+            self.write_xml_map(&mut out, message, None, 3);
+            out.push_str("    </message>\n");
+        }
+        out.push_str("  </messages>\n");
+
+        self.write_xml_section(&mut out, "trailer", &self.trailer);
+
+        out.push_str("  <components>\n");
+        for (name, component) in self.components.iter() {
+            let _ = writeln!(out, "    <component name=\"{}\">", escape_xml(name));
+            self.write_xml_map(&mut out, component, None, 3);
+            out.push_str("    </component>\n");
+        }
+        out.push_str("  </components>\n");
+
+        out.push_str("  <fields>\n");
+        for (tag, field) in self.fields_by_tag.iter() {
+            let _ = write!(out, "    <field number=\"{}\" name=\"{}\" type=\"{}\"", tag, escape_xml(field.name()), escape_xml(field.field_type()));
+            if field.has_enums() {
+                out.push_str(">\n");
+                for (value, description) in field.enums() {
+                    let _ = writeln!(out, "      <value enum=\"{}\" description=\"{}\"/>", escape_xml(value), escape_xml(description));
+                }
+                out.push_str("    </field>\n");
+            } else {
+                out.push_str("/>\n");
+            }
+        }
+        out.push_str("  </fields>\n");
+        out.push_str("</fix>\n");
+        out
+    }
+
+    /// Renders the dictionary as a Markdown rules of engagement page.
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# {}\n", self.title());
+
+        out.push_str("## Header\n\n");
+        self.write_markdown_table(&mut out, &self.header);
+
+        out.push_str("## Messages\n\n");
+        for (msg_type, message) in self.messages.iter() {
+            let msgcat = if message.admin() { "admin" } else { "app" };
+            let _ = writeln!(out, "### {} ({})\n\nCategory: {}\n", message.name(), msg_type, msgcat);
+            self.write_markdown_table(&mut out, message);
+        }
+
+        out.push_str("## Trailer\n\n");
+        self.write_markdown_table(&mut out, &self.trailer);
+
+        if !self.components.is_empty() {
+            out.push_str("## Components\n\n");
+            for (name, component) in self.components.iter() {
+                let _ = writeln!(out, "### {}\n", name);
+                self.write_markdown_table(&mut out, component);
+            }
+        }
+
+        out.push_str("## Fields\n\n");
+        out.push_str("| Tag | Field | Type | Values |\n");
+        out.push_str("| --- | --- | --- | --- |\n");
+        for (tag, field) in self.fields_by_tag.iter() {
+            let values = field.enums().iter()
+                .map(|(value, description)| format!("`{}` {}", escape_markdown(value), escape_markdown(description)))
+                .collect::<Vec<_>>()
+                .join("<br>");
+            let _ = writeln!(out, "| {} | {} | {} | {} |", tag, escape_markdown(field.name()), escape_markdown(field.field_type()), values);
+        }
+        out
+    }
+
+    /// Renders the dictionary as a standalone HTML rules of engagement page.
+    pub fn to_html(&self) -> String {
+        let mut out = String::new();
+        let title = escape_xml(&self.title());
+        let _ = writeln!(out, "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>", title);
+        let _ = writeln!(out, "<h1>{}</h1>", title);
+
+        out.push_str("<h2>Header</h2>\n");
+        self.write_html_table(&mut out, &self.header);
+
+        out.push_str("<h2>Messages</h2>\n");
+        for (msg_type, message) in self.messages.iter() {
+            let msgcat = if message.admin() { "admin" } else { "app" };
+            let _ = writeln!(out, "<h3 id=\"msg-{}\">{} ({})</h3>\n<p>Category: {}</p>", escape_xml(msg_type), escape_xml(message.name()), escape_xml(msg_type), msgcat);
+            self.write_html_table(&mut out, message);
+        }
+
+        out.push_str("<h2>Trailer</h2>\n");
+        self.write_html_table(&mut out, &self.trailer);
+
+        if !self.components.is_empty() {
+            out.push_str("<h2>Components</h2>\n");
+            for (name, component) in self.components.iter() {
+                let _ = writeln!(out, "<h3 id=\"component-{}\">{}</h3>", escape_xml(name), escape_xml(name));
+                self.write_html_table(&mut out, component);
+            }
+        }
+
+        out.push_str("<h2>Fields</h2>\n<table>\n<tr><th>Tag</th><th>Field</th><th>Type</th><th>Values</th></tr>\n");
+        for (tag, field) in self.fields_by_tag.iter() {
+            let values = field.enums().iter()
+                .map(|(value, description)| format!("<code>{}</code> {}", escape_xml(value), escape_xml(description)))
+                .collect::<Vec<_>>()
+                .join("<br>");
+            let _ = writeln!(out, "<tr id=\"field-{}\"><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>", tag, tag, escape_xml(field.name()), escape_xml(field.field_type()), values);
+        }
+        out.push_str("</table>\n</body>\n</html>\n");
+        out
+    }
+
+    fn title(&self) -> String {
+        match self.version() {
+            Some(version) => format!("{} Data Dictionary", version),
+            None => "Data Dictionary".into(),
+        }
+    }
+
+    fn field_name(&self, tag: Tag) -> String {
+        match self.fields_by_tag.get(&tag) {
+            Some(field) => field.name().to_string(),
+            None => tag.to_string(),
+        }
+    }
+
+    fn required_text(&self, map: &DDMap, tag: Tag) -> String {
+        if map.required_fields().contains(&tag) {
+            return "Y".into();
+        }
+        match map.conditionally_required_fields().get(&tag) {
+            Some(condition) => format!("if {}={}", self.field_name(condition.tag()), condition.values().join("|")),
+            None => "N".into(),
+        }
+    }
+
+    /// Components are expanded in place, their fields are optional if the component is.
+    fn rows(&self, map: &DDMap, delim: Option<Tag>, depth: usize, optional: bool, rows: &mut Vec<Row>) {
+        for entry in entries(map, delim) {
+            let tag = match entry {
+                DDEntry::Field(tag) | DDEntry::Group(tag) => tag,
+                DDEntry::Component { name, required } => {
+                    if let Some(component) = self.components.get(&name) {
+                        self.rows(component, None, depth, optional || !required, rows);
+                    }
+                    continue;
+                }
+            };
+            let field = &map.fields()[&tag];
+            rows.push(Row {
+                depth,
+                tag,
+                name: field.name().to_string(),
+                field_type: field.field_type().to_string(),
+                required: match optional {
+                    true => "N".into(),
+                    false => self.required_text(map, tag),
+                },
+            });
+            if let Some(group) = map.get_group(tag) {
+                self.rows(group, Some(group.delim()), depth + 1, false, rows);
+            }
+        }
+    }
+
+    fn write_xml_section(&self, out: &mut String, name: &str, map: &DDMap) {
+        if map.fields().is_empty() {
+            let _ = writeln!(out, "  <{}/>", name);
+        } else {
+            let _ = writeln!(out, "  <{}>", name);
+            self.write_xml_map(out, map, None, 2);
+            let _ = writeln!(out, "  </{}>", name);
+        }
+    }
+
+    fn write_xml_map(&self, out: &mut String, map: &DDMap, delim: Option<Tag>, indent: usize) {
+        let pad = "  ".repeat(indent);
+        for entry in entries(map, delim) {
+            let tag = match entry {
+                DDEntry::Field(tag) | DDEntry::Group(tag) => tag,
+                DDEntry::Component { name, required } => {
+                    let required = if required { "Y" } else { "N" };
+                    let _ = writeln!(out, "{}<component name=\"{}\" required=\"{}\"/>", pad, escape_xml(&name), required);
+                    continue;
+                }
+            };
+            let field = &map.fields()[&tag];
+            let required = if map.required_fields().contains(&tag) { "Y" } else { "N" };
+            let condition = match map.conditionally_required_fields().get(&tag) {
+                Some(condition) => format!(
+                    " required_if=\"{}\"",
+                    escape_xml(&format!("{}={}", self.field_name(condition.tag()), condition.values().join("|")))
+                ),
+                None => String::new(),
+            };
+            match map.get_group(tag) {
+                Some(group) => {
+                    let _ = writeln!(out, "{}<group name=\"{}\" required=\"{}\"{}>", pad, escape_xml(field.name()), required, condition);
+                    self.write_xml_map(out, group, Some(group.delim()), indent + 1);
+                    let _ = writeln!(out, "{}</group>", pad);
+                }
+                None => {
+                    let _ = writeln!(out, "{}<field name=\"{}\" required=\"{}\"{}/>", pad, escape_xml(field.name()), required, condition);
+                }
+            }
+        }
+    }
+
+    fn write_markdown_table(&self, out: &mut String, map: &DDMap) {
+        let mut rows = Vec::new();
+        self.rows(map, None, 0, false, &mut rows);
+        if rows.is_empty() {
+            out.push_str("No fields.\n\n");
+            return;
+        }
+        out.push_str("| Tag | Field | Type | Required |\n");
+        out.push_str("| --- | --- | --- | --- |\n");
+        for row in rows {
+            let _ = writeln!(
+                out,
+                "| {} | {}{} | {} | {} |",
+                row.tag,
+                "↳ ".repeat(row.depth),
+                escape_markdown(&row.name),
+                escape_markdown(&row.field_type),
+                escape_markdown(&row.required)
+            );
+        }
+        out.push('\n');
+    }
+
+    fn write_html_table(&self, out: &mut String, map: &DDMap) {
+        let mut rows = Vec::new();
+        self.rows(map, None, 0, false, &mut rows);
+        if rows.is_empty() {
+            out.push_str("<p>No fields.</p>\n");
+            return;
+        }
+        out.push_str("<table>\n<tr><th>Tag</th><th>Field</th><th>Type</th><th>Required</th></tr>\n");
+        for row in rows {
+            let _ = writeln!(
+                out,
+                "<tr><td>{}</td><td style=\"padding-left: {}em\"><a href=\"#field-{}\">{}</a></td><td>{}</td><td>{}</td></tr>",
+                row.tag,
+                row.depth * 2,
+                row.tag,
+                escape_xml(&row.name),
+                escape_xml(&row.field_type),
+                escape_xml(&row.required)
+            );
+        }
+        out.push_str("</table>\n");
+    }
+}
+
+/// The declared layout of `map`, or its fields ordered by tag if it has none.
+fn entries(map: &DDMap, delim: Option<Tag>) -> Vec<DDEntry> {
+    if !map.layout().is_empty() {
+        return map.layout().to_vec();
+    }
+    ordered_tags(map, delim)
+        .into_iter()
+        .map(|tag| match map.get_group(tag) {
+            Some(_) => DDEntry::Group(tag),
+            None => DDEntry::Field(tag),
+        })
+        .collect()
+}
+
+/// Tags of `map` ordered by number, with the group delimiter `delim` first.
+fn ordered_tags(map: &DDMap, delim: Option<Tag>) -> Vec<Tag> {
+    let mut tags: Vec<Tag> = map.fields().keys().copied().collect();
+    if let Some(delim) = delim {
+        if let Some(position) = tags.iter().position(|t| *t == delim) {
+            let delim = tags.remove(position);
+            tags.insert(0, delim);
+        }
+    }
+    tags
+}
+
+fn escape_xml(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn escape_markdown(value: &str) -> String {
+    value.replace('|', "\\|")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::data_dictionary::diff;
+    use crate::data_dictionary::DataDictionary;
+    use crate::tags;
+
+    #[test]
+    pub fn xml_round_trip() {
+        let dd = DataDictionary::load_from_string(include_str!("../../../../spec/FIX44.xml")).unwrap();
+        let xml = dd.to_xml_string();
+        let reloaded = DataDictionary::load_from_string(&xml).unwrap();
+
+        assert_eq!(dd.version(), reloaded.version());
+        assert!(diff(&dd, &reloaded).is_empty(), "{}", diff(&dd, &reloaded));
+        assert_eq!(dd.components().len(), reloaded.components().len());
+        assert!(reloaded.messages()["A"].admin());
+
+        let group = dd.messages()["D"].get_group(tags::NoPartyIDs).unwrap();
+        let reloaded_group = reloaded.messages()["D"].get_group(tags::NoPartyIDs).unwrap();
+        assert_eq!(group.delim(), reloaded_group.delim());
+        assert_eq!(dd.messages()["D"].layout(), reloaded.messages()["D"].layout());
+    }
+
+    #[test]
+    pub fn xml_keeps_declaration_order_and_components() {
+        let dd = DataDictionary::load_from_string(include_str!("../../../../spec/FIX44.xml")).unwrap();
+        let xml = dd.to_xml_string();
+        let start = xml.find("<message name=\"NewOrderSingle\"").unwrap();
+        let message = &xml[start..start + xml[start..].find("</message>").unwrap()];
+        let expected = [
+            "<field name=\"ClOrdID\" required=\"Y\"/>",
+            "<field name=\"SecondaryClOrdID\" required=\"N\"/>",
+            "<field name=\"ClOrdLinkID\" required=\"N\"/>",
+            "<component name=\"Parties\" required=\"N\"/>",
+            "<field name=\"TradeOriginationDate\" required=\"N\"/>",
+            "<field name=\"TradeDate\" required=\"N\"/>",
+        ];
+        let lines: Vec<&str> = message.lines().skip(1).take(expected.len()).map(str::trim).collect();
+        assert_eq!(lines, expected);
+        assert!(!message.contains("<group name=\"NoPartyIDs\""));
+    }
+
+    #[test]
+    pub fn markdown_and_html() {
+        let dd = DataDictionary::load_from_string(include_str!("../../../../spec/FIX44.xml")).unwrap();
+        let markdown = dd.to_markdown();
+        assert!(markdown.starts_with("# FIX.4.4 Data Dictionary"));
+        assert!(markdown.contains("### NewOrderSingle (D)"));
+        assert!(markdown.contains("## Components"));
+        assert!(markdown.contains("| 453 | NoPartyIDs | NUMINGROUP |"));
+        assert!(markdown.contains("| 448 | ↳ PartyID | STRING |"));
+
+        let html = dd.to_html();
+        assert!(html.contains("<h3 id=\"msg-D\">NewOrderSingle (D)</h3>"));
+        assert!(html.contains("<tr id=\"field-40\">"));
+        assert!(html.trim_end().ends_with("</html>"));
+    }
+}
@@ -7,6 +7,13 @@ pub trait DataDictionaryProvider: Send {
     fn get_application_data_dictionary(&self, appl_ver_id: &str) -> &DataDictionary;
     fn add_session_data_dictionary(&mut self, begin_string: &str, dictionary: DataDictionary);
     fn add_application_data_dictionary(&mut self, appl_ver_id: &str, dictionary: DataDictionary);
+    /// Application dictionary registered for `appl_ver_id`, [None] if there is none.
+    ///
+    /// Used on FIXT sessions to pick the dictionary of each message's ApplVerID, messages of an
+    /// ApplVerID without a dictionary are rejected. Unlike
+    /// [`get_application_data_dictionary`](Self::get_application_data_dictionary) this must not
+    /// fall back to a default dictionary.
+    fn find_application_data_dictionary(&self, appl_ver_id: &str) -> Option<&DataDictionary>;
 }
 
 #[derive(Clone, Debug, Default)]
@@ -42,4 +49,8 @@ impl DataDictionaryProvider for DefaultDataDictionaryProvider {
     fn add_application_data_dictionary(&mut self, appl_ver_id: &str, dictionary: DataDictionary) {
         self.app.insert(appl_ver_id.into(), dictionary);
     }
+
+    fn find_application_data_dictionary(&self, appl_ver_id: &str) -> Option<&DataDictionary> {
+        self.app.get(appl_ver_id)
+    }
 }
@@ -77,6 +77,21 @@ impl ApplVerID {
         }
     }
 
+    /// The BeginString of the ApplVerID `appl_ver_id`, `appl_ver_id` itself if it has none.
+    pub fn to_begin_string(appl_ver_id: &str) -> &str {
+        match appl_ver_id {
+            "2" => BeginString::FIX40,
+            "3" => BeginString::FIX41,
+            "4" => BeginString::FIX42,
+            "5" => BeginString::FIX43,
+            "6" => BeginString::FIX44,
+            "7" => BeginString::FIX50,
+            "8" => BeginString::FIX50SP1,
+            "9" => BeginString::FIX50SP2,
+            _ => appl_ver_id,
+        }
+    }
+
     pub fn as_str(&self) -> &str {
         match self {
             ApplVerID::FIX27 => "0",
@@ -140,7 +155,15 @@ pub struct BusinessRejectReason {
 }
 
 impl BusinessRejectReason {
+    pub fn OTHER() -> BusinessRejectReason { BusinessRejectReason { index: 0, reason: "Other".to_string() } }
+    pub fn UNKNOWN_ID() -> BusinessRejectReason { BusinessRejectReason { index: 1, reason: "Unknown ID".to_string() } }
+    pub fn UNKNOWN_SECURITY() -> BusinessRejectReason { BusinessRejectReason { index: 2, reason: "Unknown Security".to_string() } }
     pub fn UNKNOWN_MESSAGE_TYPE() -> BusinessRejectReason { BusinessRejectReason { index: 3, reason: "Unsupported Message Type".to_string() } }
+    pub fn APPLICATION_NOT_AVAILABLE() -> BusinessRejectReason { BusinessRejectReason { index: 4, reason: "Application not available".to_string() } }
+    pub fn CONDITIONALLY_REQUIRED_FIELD_MISSING() -> BusinessRejectReason { BusinessRejectReason { index: 5, reason: "Conditionally Required Field Missing".to_string() } }
+    pub fn NOT_AUTHORIZED() -> BusinessRejectReason { BusinessRejectReason { index: 6, reason: "Not authorized".to_string() } }
+    pub fn DELIVERTO_FIRM_NOT_AVAILABLE_AT_THIS_TIME() -> BusinessRejectReason { BusinessRejectReason { index: 7, reason: "DeliverTo firm not available at this time".to_string() } }
+    pub fn INVALID_PRICE_INCREMENT() -> BusinessRejectReason { BusinessRejectReason { index: 18, reason: "Invalid price increment".to_string() } }
 
     pub fn index(&self) -> usize {
         self.index
@@ -359,7 +359,10 @@ impl Message {
                     },
                     _ => {}
                 }
-            } else if !ignore_body {
+            } else if ignore_body {
+                // the header is complete once the body starts
+                break;
+            } else {
                 if !expecting_body {
                     if self.field_ == 0 {
                         self.field_ = f.tag();
@@ -1,19 +1,25 @@
 use std::{
     fmt::Display,
     net::{SocketAddr, TcpListener},
-    sync::{atomic::AtomicBool, Arc, Mutex},
+    sync::Arc,
     thread::{self, JoinHandle},
     time::Duration,
 };
 use dfx_base::data_dictionary_provider::DataDictionaryProvider;
 use dfx_base::message_factory::MessageFactory;
 use crate::{
-    session::{Application, SessionSetting, SessionSettings},
+    session::{
+        Application, CredentialStore, LogonAuthenticator, ResendPolicy, SessionEvents,
+        SessionSetting, SessionSettings, ValidationRules,
+    },
     message_store::MessageStoreFactory,
     logging::{LogFactory, Logger},
 };
 
-use super::{ConnectionError, SocketReactor, StreamFactory};
+use super::{
+    summarize, ConnectionObserver, SessionHooks, SessionShutdown, ShutdownSignal, SocketReactor,
+    StreamFactory, SHUTDOWN_REASON,
+};
 
 pub(crate) struct SocketAcceptorThread<App, StoreFactory, DataDictionaryProvider, LogFactory, MessageFactory> {
     app: App,
@@ -21,14 +27,14 @@ pub(crate) struct SocketAcceptorThread<App, StoreFactory, DataDictionaryProvider
     data_dictionary_provider: DataDictionaryProvider,
     log_factory: LogFactory,
     message_factory: MessageFactory,
-    addr: SocketAddr,
+    hooks: SessionHooks,
     session_settings: Vec<SessionSetting>,
 }
 
+/// Why an acceptor stopped listening on an endpoint.
 #[derive(Debug)]
-pub(crate) enum AcceptorError {
+pub enum AcceptorError {
     BindError(std::io::Error, SocketAddr),
-    ConnectionError(ConnectionError),
 }
 
 impl Display for AcceptorError {
@@ -38,14 +44,15 @@ impl Display for AcceptorError {
                 "Failed to bind addr: {} error: {}",
                 socket, err
             )),
-            AcceptorError::ConnectionError(err) => fmt.write_fmt(format_args!("{}", err)),
         }
     }
 }
 
-impl From<ConnectionError> for AcceptorError {
-    fn from(e: ConnectionError) -> Self {
-        AcceptorError::ConnectionError(e)
+impl std::error::Error for AcceptorError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AcceptorError::BindError(err, _) => Some(err),
+        }
     }
 }
 
@@ -59,9 +66,10 @@ pub struct SocketAcceptor<App, StoreFactory, DataDictionaryProvider, LogFactory,
     data_dictionary_provider: DataDictionaryProvider,
     log_factory: LogFactory,
     message_factory: MessageFactory,
+    hooks: SessionHooks,
     session_settings: SessionSettings,
     thread: Vec<ThreadState>,
-    running: Arc<AtomicBool>,
+    shutdown: Arc<ShutdownSignal>,
 }
 
 impl<App, SF, DDP, LF, MF, Log> SocketAcceptor<App, SF, DDP, LF, MF>
@@ -79,73 +87,132 @@ where App: Application + Sync + Clone + 'static,
             data_dictionary_provider,
             log_factory,
             message_factory,
+            hooks: SessionHooks::default(),
             session_settings: session_settings.clone(),
             thread: Vec::new(),
-            running: Arc::new(AtomicBool::new(false)),
+            shutdown: Arc::new(ShutdownSignal::default()),
         }
     }
 
+    /// Custom [`ValidationRules`] applied by every session after data dictionary validation.
+    pub fn with_validation_rules(mut self, validation_rules: ValidationRules) -> Self {
+        self.hooks.validation_rules = validation_rules;
+        self
+    }
+
+    /// [`SessionEvents`] subscribers notified of the lifecycle events of every session.
+    pub fn with_session_events(mut self, events: SessionEvents) -> Self {
+        self.hooks.events = events;
+        self
+    }
+
+    /// [`ResendPolicy`] deciding which stored messages are resent and which are gap filled.
+    pub fn with_resend_policy(mut self, resend_policy: ResendPolicy) -> Self {
+        self.hooks.resend_policy = resend_policy;
+        self
+    }
+
+    /// [`ConnectionObserver`] notified when connections are opened, fail or are closed.
+    pub fn with_connection_observer<O: ConnectionObserver + 'static>(mut self, observer: O) -> Self {
+        self.hooks.observer = Some(Arc::new(observer));
+        self
+    }
+
+    /// [`LogonAuthenticator`] deciding whether incoming Logons are accepted.
+    pub fn with_logon_authenticator<A: LogonAuthenticator + 'static>(mut self, authenticator: A) -> Self {
+        self.hooks.authenticator = Some(Arc::new(authenticator));
+        self
+    }
+
+    /// [`CredentialStore`] checking Username/Password on Logon, applying NewPassword and
+    /// answering UserRequest messages.
+    pub fn with_credential_store<C: CredentialStore + 'static>(mut self, credential_store: Arc<C>) -> Self {
+        self.hooks.credential_store = Some(credential_store);
+        self
+    }
+
     /// Starts the engine, creates one thread per socket address.
-    pub fn start(&mut self) -> &mut Self {
-        self.running
-            .store(true, std::sync::atomic::Ordering::SeqCst);
+    ///
+    /// Fails without starting any thread if one of the addresses cannot be bound. Earlier
+    /// versions returned nothing, printed the bind error and kept serving the other addresses:
+    /// callers now handle the [`AcceptorError`], an address that can't be bound on the host has
+    /// to be dropped from the settings.
+    pub fn start(&mut self) -> Result<&mut Self, AcceptorError> {
+        let listeners = self
+            .session_settings
+            .sessions_by_address()
+            .into_iter()
+            .map(|(addr, session_settings)| Ok((bind(addr)?, session_settings)))
+            .collect::<Result<Vec<_>, AcceptorError>>()?;
+        self.shutdown.start();
 
-        //TODO group by port => Vec<SessionSetting>
-        for (addr, session_settings) in self.session_settings.sessions_by_address() {
+        for ((listener, endpoint), session_settings) in listeners {
             let ac = SocketAcceptorThread::new(
                 self.app.clone(),
                 self.store_factory.clone(),
                 self.data_dictionary_provider.clone(),
                 self.log_factory.clone(),
                 self.message_factory.clone(),
-                addr,
+                self.hooks.clone(),
                 session_settings,
             );
-            let thread = ac.start(&self.running);
-            self.thread.push(thread);
+            let thread = ac.start(listener, endpoint, &self.shutdown);
+            self.thread.push(ThreadState { endpoint, thread });
         }
 
-        self
+        Ok(self)
     }
 
     /// Wait for all threads to finish.
     pub fn join(&mut self) {
-        while self.thread.iter().any(|t| !t.thread().is_finished()) {}
+        self.join_threads();
+    }
+
+    fn join_threads(&mut self) -> Vec<SessionShutdown> {
+        self.thread
+            .drain(..)
+            .flat_map(|t| t.thread.join().unwrap_or_default())
+            .collect()
     }
 
     /// List available endpoints, useful for random port allocation.
     pub fn endpoints(&self) -> Vec<SocketAddr>{
         self.thread
             .iter()
-            .filter_map(|t| t.endpoint())
+            .map(|t| t.endpoint)
             .collect()
     }
 
-    /// Stops the engine, and waits for the threads to finish
-    pub fn stop(&mut self) {
-        self.running
-            .store(false, std::sync::atomic::Ordering::Relaxed);
-        self.join()
+    /// Stops the engine, see [`SocketAcceptor::shutdown`].
+    pub fn stop(&mut self) -> Vec<SessionShutdown> {
+        self.shutdown(SHUTDOWN_REASON)
+    }
+
+    /// Stops accepting connections and sends a Logout with `reason` to every logged on session.
+    /// Connections are closed once the Logout is answered or `LogoutTimeout` passed, stores and
+    /// loggers are flushed and all threads joined.
+    pub fn shutdown(&mut self, reason: &str) -> Vec<SessionShutdown> {
+        self.shutdown.trigger(reason);
+        let shutdowns = self.join_threads();
+        summarize(&self.session_settings, shutdowns)
     }
 }
 
 #[derive(Debug)]
 pub(crate) struct ThreadState {
-    endpoint: Arc<Mutex<Option<SocketAddr>>>,
-    thread: JoinHandle<()>
+    endpoint: SocketAddr,
+    thread: JoinHandle<Vec<SessionShutdown>>
 }
 
-impl ThreadState {
-    fn thread(&self) -> &JoinHandle<()> {
-        &self.thread
-    }
-
-    fn endpoint(&self) -> Option<SocketAddr> {
-        match self.endpoint.lock() {
-            Ok(guard) => *guard,
-            Err(_) => todo!(),
-        }
-    }
+/// Binds a non blocking listener, returns it with the bound address.
+fn bind(addr: SocketAddr) -> Result<(TcpListener, SocketAddr), AcceptorError> {
+    let bind = || -> std::io::Result<_> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+        let endpoint = listener.local_addr()?;
+        Ok((listener, endpoint))
+    };
+    bind().map_err(|e| AcceptorError::BindError(e, addr))
 }
 
 impl<App, SF, DDP, LF, MF, Log> SocketAcceptorThread<App, SF, DDP, LF, MF>
@@ -156,92 +223,90 @@ where App: Application + Sync + Clone + 'static,
       MF: MessageFactory + Send + Clone + 'static,
       Log: Logger + Clone + 'static,
 {
-    pub(crate) fn new(app: App, store_factory: SF, data_dictionary_provider: DDP, log_factory: LF, message_factory: MF, addr: SocketAddr, session_settings: Vec<SessionSetting>) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(app: App, store_factory: SF, data_dictionary_provider: DDP, log_factory: LF, message_factory: MF, hooks: SessionHooks, session_settings: Vec<SessionSetting>) -> Self {
         SocketAcceptorThread {
             app,
             store_factory,
             data_dictionary_provider,
             log_factory,
             message_factory,
-            addr,
+            hooks,
             session_settings,
         }
     }
 
-    pub(crate) fn start(self, running: &Arc<AtomicBool>) -> ThreadState {
-        let rt = running.clone();
-        let endpoint = Arc::new(Mutex::new(None));
-        let ref_endpoint = endpoint.clone();
-        let thread = thread::Builder::new()
+    pub(crate) fn start(self, listener: TcpListener, endpoint: SocketAddr, shutdown: &Arc<ShutdownSignal>) -> JoinHandle<Vec<SessionShutdown>> {
+        let shutdown = shutdown.clone();
+        thread::Builder::new()
             .name("socket-acceptor-thread".into())
-            .spawn(move || match self.event_loop(rt, ref_endpoint) {
-                Ok(()) => {}
-                // TODO log error to main logger
-                Err(e) => println!("{e}"),
-            })
-            .expect("socket-acceptor-thread started");
-        ThreadState {
-            endpoint,
-            thread,
-        }
+            .spawn(move || self.event_loop(listener, endpoint, shutdown))
+            .expect("socket-acceptor-thread started")
     }
 
-    fn event_loop(&self, running: Arc<AtomicBool>, endpoint: Arc<Mutex<Option<SocketAddr>>>) -> Result<(), AcceptorError> {
-        let listener = self.bind()?;
-        let addr = listener.local_addr().unwrap();
-        match endpoint.lock() {
-            Ok(mut guard) => { guard.replace(addr); },
-            Err(_) => todo!(),
-        }
+    fn event_loop(&self, listener: TcpListener, endpoint: SocketAddr, shutdown: Arc<ShutdownSignal>) -> Vec<SessionShutdown> {
         let mut threads = Vec::new();
         let mut n = 0;
-        //TODO static listener based on sessions/ports
-        while running.load(std::sync::atomic::Ordering::Relaxed) {
+        while shutdown.is_running() {
+            // sessions of finished connections have nothing left to report
+            threads.retain(|t: &JoinHandle<_>| !t.is_finished());
             match listener.accept() {
-                Ok((stream, _addr)) => {
-                    // TODO replace with connected event.
-                    println!("Connected: {_addr}");
-                    let session_setting = &self.session_settings[0];
-                    let stream = StreamFactory::configure_stream(
-                        stream,
-                        session_setting.socket_settings(),
-                        true,
-                    )?;
-                    let stream = stream;
+                Ok((stream, addr)) => {
+                    // every session on this endpoint shares its socket settings, see SessionSettings
+                    let socket_settings = self.session_settings[0].socket_settings();
                     let session_settings = self.session_settings.clone();
                     let app = self.app.clone();
                     let store_factory = self.store_factory.clone();
                     let data_dictionary_provider = self.data_dictionary_provider.clone();
                     let log_factory = self.log_factory.clone();
                     let message_factory = self.message_factory.clone();
+                    let hooks = self.hooks.clone();
+                    let shutdown = shutdown.clone();
 
                     let t = thread::Builder::new()
                         .name(format!("socket-acceptor-connection-{n}"))
                         .spawn(move || {
-                            let reactor = SocketReactor::new(stream, session_settings, app, store_factory, data_dictionary_provider, log_factory, message_factory);
-                            reactor.start()
-                        })
-                        .unwrap();
-                    threads.push(t);
+                            // TLS handshakes run here so a slow or failing peer doesn't stall the listener
+                            let stream = match StreamFactory::configure_stream(stream, socket_settings, true) {
+                                Ok(stream) => stream,
+                                Err(e) => {
+                                    if let Some(observer) = hooks.observer() {
+                                        observer.on_connect_failed(None, addr, &e);
+                                    }
+                                    return None;
+                                }
+                            };
+                            let reactor = SocketReactor::new(stream, session_settings, app, store_factory, data_dictionary_provider, log_factory, message_factory, hooks, shutdown);
+                            reactor.start().as_ref().and_then(SessionShutdown::of)
+                        });
+                    match t {
+                        Ok(t) => threads.push(t),
+                        Err(e) => {
+                            if let Some(observer) = self.hooks.observer() {
+                                observer.on_connect_failed(None, addr, &e.into());
+                            }
+                        }
+                    }
                     n += 1;
                 }
                 Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
                     thread::sleep(Duration::from_millis(1));
                     continue;
                 }
-                Err(e) => panic!("encountered IO error: {e}"),
+                Err(e) => {
+                    // e.g. out of file descriptors, keep serving the established connections
+                    if let Some(observer) = self.hooks.observer() {
+                        observer.on_accept_failed(endpoint, &e.into());
+                    }
+                    thread::sleep(Duration::from_millis(1));
+                }
             }
         }
 
-        Ok(())
-    }
-
-    fn bind(&self) -> Result<TcpListener, AcceptorError> {
-        let listener =
-            TcpListener::bind(self.addr).map_err(|e| AcceptorError::BindError(e, self.addr))?;
-        listener
-            .set_nonblocking(true)
-            .expect("Cannot set non-blocking");
-        Ok(listener)
+        // the connections log out on their own once the signal is triggered
+        threads
+            .into_iter()
+            .filter_map(|t| t.join().ok().flatten())
+            .collect()
     }
 }
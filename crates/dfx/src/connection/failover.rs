@@ -0,0 +1,195 @@
+use std::{
+    collections::hash_map::RandomState,
+    fmt::Display,
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+use crate::{
+    logging::Logger,
+    session::{Endpoint, FailoverStrategy, ReconnectOptions, SessionSetting},
+};
+
+/// Picks the endpoint of every initiator connection attempt and the delay before it.
+///
+/// Failed attempts move on to the next endpoint and double the delay up to
+/// `ReconnectMaxInterval`, an established connection resets the backoff.
+pub(crate) struct Failover {
+    endpoints: Vec<Endpoint>,
+    options: ReconnectOptions,
+    current: usize,
+    failures: u32,
+}
+
+impl Failover {
+    pub(crate) fn new(settings: &SessionSetting) -> Self {
+        Failover {
+            endpoints: settings.connect_addrs(),
+            options: settings.reconnect_options().cloned().unwrap_or(ReconnectOptions {
+                interval: 30,
+                max_interval: 30,
+                jitter: 0.0,
+                max_attempts: 0,
+                strategy: FailoverStrategy::default(),
+            }),
+            current: 0,
+            failures: 0,
+        }
+    }
+
+    /// The endpoint of the next connection attempt.
+    pub(crate) fn endpoint(&self) -> Endpoint {
+        self.endpoints[self.current].clone()
+    }
+
+    /// Delay before checking again while the session is disabled or outside its schedule.
+    pub(crate) fn interval(&self) -> Duration {
+        Duration::from_millis(self.options.interval as u64)
+    }
+
+    /// Records a failed attempt, `None` once `ReconnectMaxAttempts` attempts failed in a row.
+    pub(crate) fn failed(&mut self) -> Option<Duration> {
+        self.failures = self.failures.saturating_add(1);
+        if self.options.max_attempts > 0 && self.failures >= self.options.max_attempts {
+            return None;
+        }
+        self.current = (self.current + 1) % self.endpoints.len();
+        let backoff = self.options.interval as f64 * 2f64.powi(self.failures as i32 - 1);
+        Some(self.jittered(backoff.min(self.options.max_interval as f64)))
+    }
+
+    /// Records a failed attempt like [`Failover::failed`] and writes it, the next attempt or
+    /// giving up to the session's log.
+    pub(crate) fn log_failed(&mut self, log: &impl Logger, reason: &dyn Display) -> Option<Duration> {
+        log.on_event(&format!("Connection to {} failed: {reason}", self.endpoint()));
+        let timeout = self.failed();
+        match timeout {
+            Some(timeout) => log.on_event(&format!(
+                "Next connection attempt to {} in {timeout:?}",
+                self.endpoint()
+            )),
+            None => log.on_event(&format!("Giving up after {} attempts", self.failures)),
+        }
+        timeout
+    }
+
+    /// Records the end of an established connection.
+    pub(crate) fn disconnected(&mut self) -> Duration {
+        self.failures = 0;
+        self.current = match self.options.strategy {
+            FailoverStrategy::RoundRobin => (self.current + 1) % self.endpoints.len(),
+            FailoverStrategy::Priority => 0,
+        };
+        self.jittered(self.options.interval as f64)
+    }
+
+    fn jittered(&self, delay: f64) -> Duration {
+        let random = RandomState::new().build_hasher().finish() as f64 / u64::MAX as f64;
+        let delay = delay * (1.0 + self.options.jitter * (2.0 * random - 1.0));
+        Duration::from_secs_f64(delay.max(0.0) / 1000.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Mutex, time::Duration};
+
+    use crate::{logging::Logger, session::SessionSettings};
+
+    use super::Failover;
+
+    fn failover(extra: &str) -> Failover {
+        let cfg = format!(
+            "[DEFAULT]
+ConnectionType=initiator
+BeginString=FIX.4.4
+SenderCompID=TW
+TargetCompID=ISLD
+SocketConnectHost=127.0.0.1
+SocketConnectPort=5001
+SocketConnectHost1=127.0.0.1
+SocketConnectPort1=5002
+SocketConnectHost2=127.0.0.1
+SocketConnectPort2=5003
+ReconnectInterval=10
+{extra}
+[SESSION]
+"
+        );
+        let settings = SessionSettings::from_string(&cfg).unwrap();
+        Failover::new(&settings.sessions()[0])
+    }
+
+    fn ports(failover: &Failover) -> u16 {
+        failover.endpoint().port
+    }
+
+    #[test]
+    fn round_robin_cycles_through_endpoints() {
+        let mut failover = failover("");
+        assert_eq!(ports(&failover), 5001);
+        assert_eq!(failover.failed(), Some(Duration::from_millis(10)));
+        assert_eq!(ports(&failover), 5002);
+        assert_eq!(failover.disconnected(), Duration::from_millis(10));
+        assert_eq!(ports(&failover), 5003);
+        failover.failed();
+        assert_eq!(ports(&failover), 5001);
+    }
+
+    #[test]
+    fn priority_returns_to_primary() {
+        let mut failover = failover("FailoverStrategy=Priority");
+        failover.failed();
+        assert_eq!(ports(&failover), 5002);
+        failover.disconnected();
+        assert_eq!(ports(&failover), 5001);
+    }
+
+    #[test]
+    fn backs_off_exponentially_up_to_max_attempts() {
+        let mut failover = failover("ReconnectMaxInterval=35\nReconnectMaxAttempts=5");
+        assert_eq!(failover.failed(), Some(Duration::from_millis(10)));
+        assert_eq!(failover.failed(), Some(Duration::from_millis(20)));
+        assert_eq!(failover.failed(), Some(Duration::from_millis(35)));
+        assert_eq!(failover.failed(), Some(Duration::from_millis(35)));
+        assert_eq!(failover.failed(), None);
+        failover.disconnected();
+        assert_eq!(failover.failures, 0);
+    }
+
+    #[derive(Debug, Default)]
+    struct Events(Mutex<Vec<String>>);
+    impl Logger for Events {
+        fn on_incoming(&self, _incoming: &str) {}
+        fn on_outgoing(&self, _outgoing: &str) {}
+        fn on_event(&self, event: &str) {
+            self.0.lock().unwrap().push(event.into());
+        }
+    }
+
+    #[test]
+    fn logs_failed_attempts() {
+        let mut failover = failover("ReconnectMaxAttempts=2");
+        let log = Events::default();
+        assert_eq!(failover.log_failed(&log, &"refused"), Some(Duration::from_millis(10)));
+        assert_eq!(failover.log_failed(&log, &"refused"), None);
+        assert_eq!(
+            *log.0.lock().unwrap(),
+            [
+                "Connection to 127.0.0.1:5001 failed: refused",
+                "Next connection attempt to 127.0.0.1:5002 in 10ms",
+                "Connection to 127.0.0.1:5002 failed: refused",
+                "Giving up after 2 attempts",
+            ]
+        );
+    }
+
+    #[test]
+    fn jitter_stays_within_bounds() {
+        let mut failover = failover("ReconnectJitter=0.5");
+        for _ in 0..20 {
+            let delay = failover.disconnected();
+            assert!(delay >= Duration::from_millis(5) && delay <= Duration::from_millis(15));
+        }
+    }
+}
@@ -1,21 +1,27 @@
 use std::{
-    sync::{atomic::AtomicBool, Arc},
+    fmt::Display,
+    sync::Arc,
     thread::{self, JoinHandle},
-    time::Duration,
 };
 
 use crate::{
     connection::StreamFactory,
     logging::{LogFactory, Logger},
     message_store::MessageStoreFactory,
-    session::{Application, SessionSetting, SessionSettings},
+    session::{
+        is_session_enabled, Application, Endpoint, ResendPolicy, SessionEvent, SessionEvents,
+        SessionSetting, SessionSettings, ValidationRules,
+    },
 };
 use chrono::Utc;
 use dfx_base::data_dictionary_provider::DataDictionaryProvider;
 use dfx_base::message_factory::MessageFactory;
 use dfx_base::parser::ParserError;
 
-use super::{ConnectionError, SocketReactor};
+use super::{
+    ConnectionError, ConnectionObserver, Failover, LogoutOutcome, SessionHooks, SessionShutdown, ShutdownSignal,
+    SocketReactor, SHUTDOWN_REASON,
+};
 
 /// # Multi-Threaded Socket Initiator
 /// Creates one thread per session
@@ -27,9 +33,10 @@ pub struct SocketInitiator<App, StoreFactory, DataDictionaryProvider, LogFactory
     data_dictionary_provider: DataDictionaryProvider,
     log_factory: LogFactory,
     message_factory: MessageFactory,
+    hooks: SessionHooks,
     session_settings: SessionSettings,
-    thread: Vec<JoinHandle<()>>,
-    running: Arc<AtomicBool>,
+    thread: Vec<JoinHandle<SessionShutdown>>,
+    shutdown: Arc<ShutdownSignal>,
 }
 
 impl<App, SF, DDP, LF, MF, Log> SocketInitiator<App, SF, DDP, LF, MF>
@@ -56,15 +63,39 @@ where
             data_dictionary_provider,
             log_factory,
             message_factory,
+            hooks: SessionHooks::default(),
             session_settings,
             thread: Vec::new(),
-            running: Arc::new(AtomicBool::new(false)),
+            shutdown: Arc::new(ShutdownSignal::default()),
         }
     }
 
+    /// Custom [`ValidationRules`] applied by every session after data dictionary validation.
+    pub fn with_validation_rules(mut self, validation_rules: ValidationRules) -> Self {
+        self.hooks.validation_rules = validation_rules;
+        self
+    }
+
+    /// [`SessionEvents`] subscribers notified of the lifecycle events of every session.
+    pub fn with_session_events(mut self, events: SessionEvents) -> Self {
+        self.hooks.events = events;
+        self
+    }
+
+    /// [`ResendPolicy`] deciding which stored messages are resent and which are gap filled.
+    pub fn with_resend_policy(mut self, resend_policy: ResendPolicy) -> Self {
+        self.hooks.resend_policy = resend_policy;
+        self
+    }
+
+    /// [`ConnectionObserver`] notified when connections are opened, fail or are closed.
+    pub fn with_connection_observer<O: ConnectionObserver + 'static>(mut self, observer: O) -> Self {
+        self.hooks.observer = Some(Arc::new(observer));
+        self
+    }
+
     pub fn start(&mut self) {
-        self.running
-            .store(true, std::sync::atomic::Ordering::SeqCst);
+        self.shutdown.start();
         for session_settings in self.session_settings.sessions() {
             let ac = SocketInitiatorThread::new(
                 self.app.clone(),
@@ -72,19 +103,35 @@ where
                 self.data_dictionary_provider.clone(),
                 self.log_factory.clone(),
                 self.message_factory.clone(),
+                self.hooks.clone(),
                 session_settings.clone(),
             );
-            let thread = ac.start(&self.running);
+            let thread = ac.start(&self.shutdown);
             self.thread.push(thread);
         }
     }
     pub fn join(&mut self) {
-        while self.thread.iter().any(|t| !t.is_finished()) {}
+        self.join_threads();
+    }
+
+    fn join_threads(&mut self) -> Vec<SessionShutdown> {
+        self.thread
+            .drain(..)
+            .filter_map(|t| t.join().ok())
+            .collect()
     }
-    pub fn stop(mut self) {
-        self.running
-            .store(false, std::sync::atomic::Ordering::Relaxed);
-        self.join()
+
+    /// Stops the engine, see [`SocketInitiator::shutdown`].
+    pub fn stop(mut self) -> Vec<SessionShutdown> {
+        self.shutdown(SHUTDOWN_REASON)
+    }
+
+    /// Stops reconnecting and sends a Logout with `reason` to every logged on session.
+    /// Connections are closed once the Logout is answered or `LogoutTimeout` passed, stores and
+    /// loggers are flushed and all threads joined.
+    pub fn shutdown(&mut self, reason: &str) -> Vec<SessionShutdown> {
+        self.shutdown.trigger(reason);
+        self.join_threads()
     }
 }
 
@@ -100,16 +147,38 @@ pub(crate) struct SocketInitiatorThread<
     data_dictionary_provider: DataDictionaryProvider,
     log_factory: LogFactory,
     message_factory: MessageFactory,
+    hooks: SessionHooks,
     session_settings: SessionSetting,
 }
 
+/// Why an initiator's connection attempt failed.
 #[derive(Debug)]
-pub(crate) enum InitiatorError {
+pub enum InitiatorError {
     ConnectionError(ConnectionError),
     ParserError(ParserError),
     IoError(std::io::Error),
 }
 
+impl Display for InitiatorError {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InitiatorError::ConnectionError(err) => fmt.write_fmt(format_args!("{}", err)),
+            InitiatorError::ParserError(err) => match *err {},
+            InitiatorError::IoError(err) => fmt.write_fmt(format_args!("IO error: {}", err)),
+        }
+    }
+}
+
+impl std::error::Error for InitiatorError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            InitiatorError::ConnectionError(err) => Some(err),
+            InitiatorError::ParserError(err) => match *err {},
+            InitiatorError::IoError(err) => Some(err),
+        }
+    }
+}
+
 impl From<ConnectionError> for InitiatorError {
     fn from(e: ConnectionError) -> InitiatorError {
         InitiatorError::ConnectionError(e)
@@ -141,6 +210,7 @@ where
         data_dictionary_provider: DDP,
         log_factory: LF,
         message_factory: MF,
+        hooks: SessionHooks,
         session_settings: SessionSetting,
     ) -> Self {
         SocketInitiatorThread {
@@ -149,41 +219,73 @@ where
             data_dictionary_provider,
             log_factory,
             message_factory,
+            hooks,
             session_settings,
         }
     }
 
-    pub(crate) fn start(mut self, running: &Arc<AtomicBool>) -> JoinHandle<()> {
-        let running = running.clone();
+    pub(crate) fn start(mut self, shutdown: &Arc<ShutdownSignal>) -> JoinHandle<SessionShutdown> {
+        let shutdown = shutdown.clone();
         thread::Builder::new()
             .name("socket-initiator-thread".into())
             .spawn(move || {
-                let timeout = self.session_settings.reconnect_interval().unwrap_or(30) as u64;
+                let mut failover = Failover::new(&self.session_settings);
+                let session_id = self.session_settings.session_id().clone();
+                let log = self.log_factory.create(&session_id);
                 // loop here for session reconnect!
-                while running.load(std::sync::atomic::Ordering::Relaxed) {
-                    if self
-                        .session_settings
-                        .schedule()
-                        .is_session_time(&Utc::now())
+                while shutdown.is_running() {
+                    let timeout = if is_session_enabled(self.session_settings.session_id())
+                        && self
+                            .session_settings
+                            .schedule()
+                            .is_session_time(&Utc::now())
                     {
-                        if let Err(e) = self.event_loop() {
-                            match e {
-                                e => println!(
-                                    "SocketInitiator Connect error to {:?}: {:?}",
-                                    self.session_settings.socket_settings(),
-                                    e
-                                ),
-                            }
+                        match self.event_loop(failover.endpoint(), &shutdown) {
+                            Ok(Some(summary)) => return summary,
+                            Ok(None) => failover.disconnected(),
+                            Err(e) => match failover.log_failed(&log, &e) {
+                                Some(timeout) => timeout,
+                                None => break,
+                            },
                         }
-                    }
-                    thread::sleep(Duration::from_millis(timeout))
+                    } else {
+                        failover.interval()
+                    };
+                    thread::sleep(timeout)
                 }
+                SessionShutdown::new(session_id, LogoutOutcome::Disconnected)
             })
             .expect("socket-acceptor-thread started")
     }
 
-    fn event_loop(&mut self) -> Result<(), InitiatorError> {
-        let stream = StreamFactory::create_client_stream(self.session_settings.socket_settings())?;
+    /// Runs one connection, returns the session's outcome if it ended by the shutdown.
+    fn event_loop(
+        &mut self,
+        endpoint: Endpoint,
+        shutdown: &Arc<ShutdownSignal>,
+    ) -> Result<Option<SessionShutdown>, InitiatorError> {
+        let address = endpoint.addr;
+        let socket_settings = self.session_settings.socket_settings().with_endpoint(endpoint);
+        let session_id = self.session_settings.session_id();
+        self.hooks
+            .events
+            .publish(session_id, SessionEvent::Connecting { address });
+        if let Some(observer) = self.hooks.observer() {
+            observer.on_connect_attempt(session_id, address);
+        }
+        let stream = match StreamFactory::create_client_stream(socket_settings) {
+            Ok(stream) => stream,
+            Err(e) => {
+                if let Some(observer) = self.hooks.observer() {
+                    observer.on_connect_failed(Some(session_id), address, &e);
+                }
+                let reason = format!("{e}");
+                self.hooks
+                    .events
+                    .publish(session_id, SessionEvent::ConnectFailed { address, reason });
+                return Err(e.into());
+            }
+        };
         let app = self.app.clone();
         let store_factory = self.store_factory.clone();
         let data_dictionary_provider = self.data_dictionary_provider.clone();
@@ -197,9 +299,9 @@ where
             data_dictionary_provider,
             log_factory,
             message_factory,
+            self.hooks.clone(),
+            shutdown.clone(),
         );
-        reactor.start();
-
-        Ok(())
+        Ok(reactor.start().as_ref().and_then(SessionShutdown::of))
     }
 }
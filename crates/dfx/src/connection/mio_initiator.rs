@@ -0,0 +1,146 @@
+use std::{
+    sync::Arc,
+    thread::{self, JoinHandle},
+};
+
+use dfx_base::data_dictionary_provider::DataDictionaryProvider;
+use dfx_base::message_factory::MessageFactory;
+use mio::Waker;
+
+use crate::{
+    logging::{LogFactory, Logger},
+    message_store::MessageStoreFactory,
+    session::{Application, ResendPolicy, SessionEvents, SessionSettings, ValidationRules},
+};
+
+use super::{
+    summarize, ConnectionObserver, MioReactor, SessionFactory, SessionHooks, SessionShutdown, ShutdownSignal,
+    SHUTDOWN_REASON,
+};
+
+/// # Single-Threaded Socket Initiator
+/// Connects every session from one thread multiplexed with mio,
+/// available with the `mio` feature. Application callbacks must not block, see
+/// [`MioSocketAcceptor`](super::MioSocketAcceptor).
+/// ## Usage
+/// Created and started like [`SocketInitiator`](super::SocketInitiator).
+pub struct MioSocketInitiator<App, StoreFactory, DataDictionaryProvider, LogFactory, MessageFactory> {
+    app: App,
+    store_factory: StoreFactory,
+    data_dictionary_provider: DataDictionaryProvider,
+    log_factory: LogFactory,
+    message_factory: MessageFactory,
+    hooks: SessionHooks,
+    session_settings: SessionSettings,
+    thread: Option<JoinHandle<Vec<SessionShutdown>>>,
+    waker: Option<Arc<Waker>>,
+    shutdown: Arc<ShutdownSignal>,
+}
+
+impl<App, SF, DDP, LF, MF, Log> MioSocketInitiator<App, SF, DDP, LF, MF>
+where
+    App: Application + Clone + 'static,
+    SF: MessageStoreFactory + Send + Clone + 'static,
+    DDP: DataDictionaryProvider + Send + Clone + 'static,
+    LF: LogFactory<Log = Log> + Send + Clone + 'static,
+    MF: MessageFactory + Send + Clone + 'static,
+    Log: Logger + Clone + 'static,
+{
+    pub fn new(
+        session_settings: SessionSettings,
+        app: App,
+        store_factory: SF,
+        data_dictionary_provider: DDP,
+        log_factory: LF,
+        message_factory: MF,
+    ) -> Self {
+        MioSocketInitiator {
+            app,
+            store_factory,
+            data_dictionary_provider,
+            log_factory,
+            message_factory,
+            hooks: SessionHooks::default(),
+            session_settings,
+            thread: None,
+            waker: None,
+            shutdown: Arc::new(ShutdownSignal::default()),
+        }
+    }
+
+    /// Custom [`ValidationRules`] applied by every session after data dictionary validation.
+    pub fn with_validation_rules(mut self, validation_rules: ValidationRules) -> Self {
+        self.hooks.validation_rules = validation_rules;
+        self
+    }
+
+    /// [`SessionEvents`] subscribers notified of the lifecycle events of every session.
+    pub fn with_session_events(mut self, events: SessionEvents) -> Self {
+        self.hooks.events = events;
+        self
+    }
+
+    /// [`ResendPolicy`] deciding which stored messages are resent and which are gap filled.
+    pub fn with_resend_policy(mut self, resend_policy: ResendPolicy) -> Self {
+        self.hooks.resend_policy = resend_policy;
+        self
+    }
+
+    /// [`ConnectionObserver`] notified when connections are opened, fail or are closed.
+    pub fn with_connection_observer<O: ConnectionObserver + 'static>(mut self, observer: O) -> Self {
+        self.hooks.observer = Some(Arc::new(observer));
+        self
+    }
+
+    /// Starts the reactor thread, which connects every session.
+    pub fn start(&mut self) -> std::io::Result<()> {
+        self.shutdown.start();
+        let factory = SessionFactory {
+            app: self.app.clone(),
+            store_factory: self.store_factory.clone(),
+            data_dictionary_provider: self.data_dictionary_provider.clone(),
+            log_factory: self.log_factory.clone(),
+            message_factory: self.message_factory.clone(),
+            hooks: self.hooks.clone(),
+        };
+        let mut reactor = MioReactor::new(factory, self.shutdown.clone())?;
+        for session_settings in self.session_settings.sessions() {
+            reactor.initiate(session_settings.clone());
+        }
+        self.waker = Some(reactor.waker());
+        let thread = thread::Builder::new()
+            .name("mio-initiator-reactor".into())
+            .spawn(move || reactor.run())?;
+        self.thread = Some(thread);
+        Ok(())
+    }
+
+    /// Wait for the reactor thread to finish.
+    pub fn join(&mut self) {
+        self.join_thread();
+    }
+
+    fn join_thread(&mut self) -> Vec<SessionShutdown> {
+        match self.thread.take() {
+            Some(thread) => thread.join().unwrap_or_default(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Stops the reactor, see [`MioSocketInitiator::shutdown`].
+    pub fn stop(&mut self) -> Vec<SessionShutdown> {
+        self.shutdown(SHUTDOWN_REASON)
+    }
+
+    /// Stops reconnecting and sends a Logout with `reason` to every logged on session.
+    /// Connections are closed once the Logout is answered or `LogoutTimeout` passed, stores and
+    /// loggers are flushed and the reactor thread joined.
+    pub fn shutdown(&mut self, reason: &str) -> Vec<SessionShutdown> {
+        self.shutdown.trigger(reason);
+        if let Some(waker) = self.waker.take() {
+            let _ = waker.wake();
+        }
+        let shutdowns = self.join_thread();
+        summarize(&self.session_settings, shutdowns)
+    }
+}
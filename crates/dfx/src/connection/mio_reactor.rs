@@ -0,0 +1,872 @@
+use std::{
+    collections::HashMap,
+    io::{ErrorKind, Read, Write},
+    net::{Shutdown, SocketAddr},
+    sync::{
+        mpsc::{channel, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use chrono::Utc;
+use dfx_base::data_dictionary_provider::DataDictionaryProvider;
+use dfx_base::message::Message;
+use dfx_base::message_factory::MessageFactory;
+use dfx_base::session_id::SessionId;
+use mio::{
+    net::{TcpListener, TcpStream},
+    Events, Interest, Poll, Token, Waker,
+};
+
+use crate::{
+    logging::{LogFactory, Logger},
+    message_store::MessageStoreFactory,
+    parser::Parser,
+    session::{
+        is_session_enabled, Application, ISession, PeerIdentity,
+        QueuedResponder, ReactorThread, ResponderEvent, SessionEvent, SessionSetting, SslOptions, Wakeup,
+    },
+};
+
+use super::{
+    best_session_setting, certificate_mismatch, proxy, tls, ConnectionError, Failover, ReactorError, SessionHooks, SessionShutdown, ShutdownSignal,
+    SocketSettings, TimerWheel, BUF_SIZE, TICK_INTERVAL,
+};
+
+const WAKER: Token = Token(0);
+const TIMER_RESOLUTION: Duration = Duration::from_millis(5);
+const TIMER_SLOTS: usize = 512;
+
+/// The user supplied factories and hooks every session is created from.
+pub(crate) struct SessionFactory<App, StoreFactory, DataDictionaryProvider, LogFactory, MessageFactory> {
+    pub(crate) app: App,
+    pub(crate) store_factory: StoreFactory,
+    pub(crate) data_dictionary_provider: DataDictionaryProvider,
+    pub(crate) log_factory: LogFactory,
+    pub(crate) message_factory: MessageFactory,
+    pub(crate) hooks: SessionHooks,
+}
+
+impl<App, SF, DDP, LF, MF, Log> SessionFactory<App, SF, DDP, LF, MF>
+where App: Application + Clone + 'static,
+      SF: MessageStoreFactory + Send + Clone + 'static,
+      DDP: DataDictionaryProvider + Send + Clone + 'static,
+      LF: LogFactory<Log = Log> + Send + Clone + 'static,
+      MF: MessageFactory + Send + Clone + 'static,
+      Log: Logger + Clone + 'static
+{
+    fn create(&self, session_id: SessionId, settings: &SessionSetting, peer: PeerIdentity) -> ISession<App, DDP, Log, MF> {
+        let log = self.log_factory.create(&session_id);
+        let rejection = certificate_mismatch(settings, &session_id, &peer);
+        let mut session = ISession::from_settings(
+            session_id,
+            self.app.clone(),
+            Box::new(self.store_factory.clone()),
+            self.data_dictionary_provider.clone(),
+            log,
+            self.message_factory.clone(),
+            settings.clone()
+        );
+        self.hooks.apply(&mut session);
+        session.set_peer(peer);
+        if let Some(reason) = rejection {
+            session.reject_logon(reason);
+        }
+        session
+    }
+}
+
+enum Timer {
+    /// Run `next()` for the session on this connection.
+    Tick(Token),
+    /// (Re)connect the initiator session at this index.
+    Connect(usize),
+}
+
+/// An initiator connection tunneled through its proxy on a helper thread.
+struct Tunneled {
+    index: usize,
+    address: SocketAddr,
+    socket_settings: SocketSettings,
+    stream: std::io::Result<std::net::TcpStream>,
+}
+
+enum Transport {
+    Connecting(TcpStream),
+    Handshake(Box<tls::MidHandshake<TcpStream>>),
+    Tcp(TcpStream),
+    Ssl(Box<tls::TlsStream<TcpStream>>),
+}
+
+impl Transport {
+    fn secure(stream: TcpStream, ssl_options: &Option<SslOptions>) -> Result<Transport, ReactorError> {
+        let handshake = match ssl_options {
+            Some(SslOptions::Acceptor { acceptor }) => tls::accept(acceptor, stream),
+            Some(SslOptions::Initiator { initiator, domain }) => tls::connect(initiator, domain, stream),
+            None => return Ok(Transport::Tcp(stream)),
+        };
+        Transport::handshake(handshake)
+    }
+
+    fn handshake(
+        handshake: Result<tls::Handshake<TcpStream>, ConnectionError>,
+    ) -> Result<Transport, ReactorError> {
+        match handshake? {
+            tls::Handshake::Established(stream) => Ok(Transport::Ssl(Box::new(stream))),
+            tls::Handshake::Pending(stream) => Ok(Transport::Handshake(Box::new(stream))),
+        }
+    }
+
+    fn stream(&self) -> &TcpStream {
+        match self {
+            Transport::Connecting(stream) | Transport::Tcp(stream) => stream,
+            Transport::Handshake(stream) => stream.get_ref(),
+            Transport::Ssl(stream) => stream.get_ref(),
+        }
+    }
+
+    fn stream_mut(&mut self) -> &mut TcpStream {
+        match self {
+            Transport::Connecting(stream) | Transport::Tcp(stream) => stream,
+            Transport::Handshake(stream) => stream.get_mut(),
+            Transport::Ssl(stream) => stream.get_mut(),
+        }
+    }
+
+    fn is_established(&self) -> bool {
+        matches!(self, Transport::Tcp(_) | Transport::Ssl(_))
+    }
+
+    fn peer_identity(&self) -> PeerIdentity {
+        let address = self.stream().peer_addr().ok();
+        let certificate = match self {
+            Transport::Ssl(stream) => tls::peer_certificate(stream),
+            _ => None,
+        };
+        PeerIdentity::new(address, certificate)
+    }
+
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            Transport::Tcp(stream) => stream.read(buf),
+            Transport::Ssl(stream) => stream.read(buf),
+            _ => Err(ErrorKind::WouldBlock.into()),
+        }
+    }
+
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Transport::Tcp(stream) => stream.write(buf),
+            Transport::Ssl(stream) => stream.write(buf),
+            _ => Err(ErrorKind::WouldBlock.into()),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Transport::Tcp(stream) => stream.flush(),
+            Transport::Ssl(stream) => stream.flush(),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether the socket must become writable before anything else can happen.
+    fn wants_write(&self) -> bool {
+        match self {
+            Transport::Tcp(_) => false,
+            Transport::Ssl(stream) => tls::wants_write(stream),
+            _ => true,
+        }
+    }
+
+    fn shutdown(&mut self) {
+        if let Transport::Ssl(stream) = self {
+            let _ = stream.shutdown();
+        }
+        let _ = self.stream().shutdown(Shutdown::Both);
+    }
+}
+
+struct Connection<App, DataDictionaryProvider, Log, MessageFactory> {
+    transport: Option<Transport>,
+    socket_settings: SocketSettings,
+    settings: Vec<SessionSetting>,
+    parser: Parser,
+    session: Option<ISession<App, DataDictionaryProvider, Log, MessageFactory>>,
+    events: Option<Receiver<ResponderEvent>>,
+    outgoing: Vec<u8>,
+    interest: Interest,
+    wakeup: Wakeup,
+    started: bool,
+    /// Index of the initiator session to reconnect once this connection closes.
+    initiator: Option<usize>,
+}
+
+impl<App, DDP, Log, MF> Connection<App, DDP, Log, MF>
+where App: Application + Clone + 'static,
+      DDP: DataDictionaryProvider + Send + Clone + 'static,
+      MF: MessageFactory + Send + Clone + 'static,
+      Log: Logger + Clone + 'static
+{
+    fn interest(&self) -> Interest {
+        match self.transport.as_ref() {
+            Some(transport) if !transport.wants_write() && self.outgoing.is_empty() => Interest::READABLE,
+            _ => Interest::READABLE | Interest::WRITABLE,
+        }
+    }
+
+    fn ready<SF, LF>(&mut self, factory: &SessionFactory<App, SF, DDP, LF, MF>) -> Result<(), ReactorError>
+    where SF: MessageStoreFactory + Send + Clone + 'static,
+          LF: LogFactory<Log = Log> + Send + Clone + 'static,
+    {
+        self.progress()?;
+        if !self.transport.as_ref().is_some_and(Transport::is_established) {
+            return Ok(());
+        }
+        if !self.started {
+            self.start(factory)?;
+        }
+        self.read(factory)?;
+        self.flush()
+    }
+
+    /// Moves a connecting or handshaking transport along as far as it goes without blocking.
+    fn progress(&mut self) -> Result<(), ReactorError> {
+        loop {
+            match self.transport.take() {
+                Some(Transport::Connecting(stream)) => {
+                    if let Some(e) = stream.take_error()? {
+                        return Err(e.into());
+                    }
+                    match stream.peer_addr() {
+                        Ok(_) => {}
+                        Err(e) if e.kind() == ErrorKind::NotConnected || e.kind() == ErrorKind::WouldBlock => {
+                            self.transport = Some(Transport::Connecting(stream));
+                            return Ok(());
+                        }
+                        Err(e) => return Err(e.into()),
+                    }
+                    stream.set_nodelay(self.socket_settings.no_delay())?;
+                    self.transport = Some(Transport::secure(stream, self.socket_settings.ssl_options())?);
+                }
+                Some(Transport::Handshake(stream)) => {
+                    self.transport = Some(Transport::handshake(stream.handshake())?);
+                    return Ok(());
+                }
+                transport => {
+                    self.transport = transport;
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    fn start<SF, LF>(&mut self, factory: &SessionFactory<App, SF, DDP, LF, MF>) -> Result<(), ReactorError>
+    where SF: MessageStoreFactory + Send + Clone + 'static,
+          LF: LogFactory<Log = Log> + Send + Clone + 'static,
+    {
+        self.started = true;
+        if self.settings.len() == 1 {
+            let session_setting = &self.settings[0];
+            if session_setting.connection().is_initiator()
+                || (session_setting.connection().is_acceptor() && !session_setting.is_dynamic())
+            {
+                let session_id = session_setting.session_id().clone();
+                let settings = session_setting.clone();
+                self.create_session(factory, session_id, &settings)?;
+            }
+        }
+        if let Some(observer) = factory.hooks.observer() {
+            let peer = self
+                .transport
+                .as_ref()
+                .map(Transport::peer_identity)
+                .unwrap_or_default();
+            observer.on_connected(self.session.as_ref().map(|s| s.session_id()), &peer);
+        }
+        self.next();
+        Ok(())
+    }
+
+    fn create_session<SF, LF>(&mut self, factory: &SessionFactory<App, SF, DDP, LF, MF>, session_id: SessionId, settings: &SessionSetting) -> Result<(), ReactorError>
+    where SF: MessageStoreFactory + Send + Clone + 'static,
+          LF: LogFactory<Log = Log> + Send + Clone + 'static,
+    {
+        let peer = self
+            .transport
+            .as_ref()
+            .map(Transport::peer_identity)
+            .unwrap_or_default();
+        let mut session = factory.create(session_id.clone(), settings, peer);
+        let (responder, events) = QueuedResponder::new();
+        session.set_responder(Box::new(responder));
+        session.set_wakeup(self.wakeup.clone());
+        if session.set_connected(&session_id).is_err() {
+            session.log().on_event("Disconnecting, the session is already connected");
+            return Err(ReactorError::Disconnect);
+        }
+        session.log_connected();
+        self.events = Some(events);
+        self.session = Some(session);
+        Ok(())
+    }
+
+    fn next(&mut self) {
+        if let Some(session) = self.session.as_mut() {
+            session.next();
+        }
+    }
+
+    fn read<SF, LF>(&mut self, factory: &SessionFactory<App, SF, DDP, LF, MF>) -> Result<(), ReactorError>
+    where SF: MessageStoreFactory + Send + Clone + 'static,
+          LF: LogFactory<Log = Log> + Send + Clone + 'static,
+    {
+        let mut buffer = [0; BUF_SIZE];
+        loop {
+            let transport = match self.transport.as_mut() {
+                Some(transport) => transport,
+                None => return Err(ReactorError::Disconnect),
+            };
+            match transport.read(&mut buffer) {
+                Ok(0) => return Err(ReactorError::Disconnect),
+                Ok(read) => {
+                    self.parser.add_to_stream(&buffer[..read]);
+                    self.process_stream(factory)?;
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return Ok(()),
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    fn process_stream<SF, LF>(&mut self, factory: &SessionFactory<App, SF, DDP, LF, MF>) -> Result<(), ReactorError>
+    where SF: MessageStoreFactory + Send + Clone + 'static,
+          LF: LogFactory<Log = Log> + Send + Clone + 'static,
+    {
+        while let Some(msg) = self.parser.read_fix_message()? {
+            if let Some(session) = self.session.as_mut() {
+                session.next_msg(msg);
+                continue;
+            }
+            let message = Message::new(&msg[..])?;
+            let session_id = message.extract_contra_session_id();
+            let settings = match best_session_setting(&self.settings, &session_id) {
+                Some(settings) if settings.accepts(&session_id) => settings.clone(),
+                // TODO this.Log("ERROR: Disconnecting; received message for unknown session: " + msg);
+                _ => return Err(ReactorError::Disconnect),
+            };
+            self.create_session(factory, session_id, &settings)?;
+            if let Some(session) = self.session.as_mut() {
+                session.next_msg(msg);
+            }
+        }
+        Ok(())
+    }
+
+    /// Queues what the session sent and writes as much as the socket takes.
+    fn flush(&mut self) -> Result<(), ReactorError> {
+        let mut disconnect = false;
+        if let Some(events) = self.events.as_ref() {
+            for event in events.try_iter() {
+                match event {
+                    ResponderEvent::Send(message) => self.outgoing.extend_from_slice(message.as_bytes()),
+                    ResponderEvent::Disconnect => {
+                        disconnect = true;
+                        break;
+                    }
+                }
+            }
+        }
+        let written = self.write();
+        if disconnect {
+            return Err(ReactorError::Disconnect);
+        }
+        written
+    }
+
+    fn write(&mut self) -> Result<(), ReactorError> {
+        let transport = match self.transport.as_mut() {
+            Some(transport) if transport.is_established() => transport,
+            _ => return Ok(()),
+        };
+        let mut written = 0;
+        while written < self.outgoing.len() {
+            match transport.write(&self.outgoing[written..]) {
+                Ok(0) => return Err(ReactorError::Disconnect),
+                Ok(n) => written += n,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        self.outgoing.drain(..written);
+        if self.outgoing.is_empty() {
+            let _ = transport.flush();
+        }
+        Ok(())
+    }
+}
+
+/// Hosts many connections and their sessions on one thread.
+///
+/// Sockets are multiplexed with mio, `next()` is run from a [`TimerWheel`] every
+/// [`TICK_INTERVAL`] and whenever messages or commands are queued for a session.
+pub(crate) struct MioReactor<App, StoreFactory, DataDictionaryProvider, LogFactory, MessageFactory, Log> {
+    poll: Poll,
+    waker: Arc<Waker>,
+    woken: Arc<Mutex<Vec<Token>>>,
+    tunneled: (Sender<Tunneled>, Receiver<Tunneled>),
+    factory: SessionFactory<App, StoreFactory, DataDictionaryProvider, LogFactory, MessageFactory>,
+    listeners: HashMap<Token, (TcpListener, Vec<SessionSetting>)>,
+    initiators: Vec<(SessionSetting, Failover, Log)>,
+    connections: HashMap<Token, Connection<App, DataDictionaryProvider, Log, MessageFactory>>,
+    timers: TimerWheel<Timer>,
+    next_token: usize,
+    shutdown: Arc<ShutdownSignal>,
+    shutdowns: Vec<SessionShutdown>,
+}
+
+impl<App, SF, DDP, LF, MF, Log> MioReactor<App, SF, DDP, LF, MF, Log>
+where App: Application + Clone + 'static,
+      SF: MessageStoreFactory + Send + Clone + 'static,
+      DDP: DataDictionaryProvider + Send + Clone + 'static,
+      LF: LogFactory<Log = Log> + Send + Clone + 'static,
+      MF: MessageFactory + Send + Clone + 'static,
+      Log: Logger + Clone + 'static
+{
+    pub(crate) fn new(factory: SessionFactory<App, SF, DDP, LF, MF>, shutdown: Arc<ShutdownSignal>) -> std::io::Result<Self> {
+        let poll = Poll::new()?;
+        let waker = Arc::new(Waker::new(poll.registry(), WAKER)?);
+        Ok(MioReactor {
+            poll,
+            waker,
+            woken: Arc::new(Mutex::new(Vec::new())),
+            tunneled: channel(),
+            factory,
+            listeners: HashMap::new(),
+            initiators: Vec::new(),
+            connections: HashMap::new(),
+            timers: TimerWheel::new(TIMER_RESOLUTION, TIMER_SLOTS),
+            next_token: WAKER.0 + 1,
+            shutdown,
+            shutdowns: Vec::new(),
+        })
+    }
+
+    /// Wakes up the reactor, e.g. to notice the shutdown.
+    pub(crate) fn waker(&self) -> Arc<Waker> {
+        self.waker.clone()
+    }
+
+    fn token(&mut self) -> Token {
+        let token = Token(self.next_token);
+        self.next_token += 1;
+        token
+    }
+
+    /// Binds `addr` for the acceptor sessions in `settings`, returns the bound address.
+    pub(crate) fn listen(&mut self, addr: SocketAddr, settings: Vec<SessionSetting>) -> std::io::Result<SocketAddr> {
+        let mut listener = TcpListener::bind(addr)?;
+        let local_addr = listener.local_addr()?;
+        let token = self.token();
+        self.poll
+            .registry()
+            .register(&mut listener, token, Interest::READABLE)?;
+        self.listeners.insert(token, (listener, settings));
+        Ok(local_addr)
+    }
+
+    /// Adds an initiator session, connected as soon as the reactor runs.
+    pub(crate) fn initiate(&mut self, settings: SessionSetting) {
+        self.timers
+            .schedule(Instant::now(), Timer::Connect(self.initiators.len()));
+        let failover = Failover::new(&settings);
+        let log = self.factory.log_factory.create(settings.session_id());
+        self.initiators.push((settings, failover, log));
+    }
+
+    /// Runs until shut down and every connection closed, returns the outcome of the sessions
+    /// that were connected by then.
+    pub(crate) fn run(mut self) -> Vec<SessionShutdown> {
+        // every session of the reactor runs on this thread
+        let _reactor = ReactorThread::enter();
+        let mut events = Events::with_capacity(256);
+        let mut shutting_down = false;
+        loop {
+            if !shutting_down && !self.shutdown.is_running() {
+                shutting_down = true;
+                self.begin_shutdown();
+            }
+            if shutting_down && self.connections.is_empty() {
+                break;
+            }
+            let timeout = self
+                .timers
+                .next_deadline()
+                .map(|deadline| deadline.saturating_duration_since(Instant::now()));
+            if let Err(e) = self.poll.poll(&mut events, timeout) {
+                if e.kind() == ErrorKind::Interrupted {
+                    continue;
+                }
+                // the reactor can't go on, every connection is closed with the cause
+                let tokens: Vec<Token> = self.connections.keys().copied().collect();
+                for token in tokens {
+                    let error = std::io::Error::new(e.kind(), e.to_string());
+                    self.close(token, Some(error.into()));
+                }
+                break;
+            }
+            for event in events.iter() {
+                match event.token() {
+                    WAKER => self.woken(),
+                    token if self.listeners.contains_key(&token) => self.accept(token),
+                    token => self.ready(token),
+                }
+            }
+            for timer in self.timers.expire(Instant::now()) {
+                match timer {
+                    Timer::Tick(token) => self.tick(token),
+                    Timer::Connect(index) => self.connect(index),
+                }
+            }
+        }
+        let tokens: Vec<Token> = self.connections.keys().copied().collect();
+        for token in tokens {
+            self.close(token, None);
+        }
+        self.shutdowns
+    }
+
+    /// Stops accepting and connecting and logs out every session, each connection closes once
+    /// its Logout is answered or timed out.
+    fn begin_shutdown(&mut self) {
+        for (_, (mut listener, _)) in self.listeners.drain() {
+            let _ = self.poll.registry().deregister(&mut listener);
+        }
+        let reason = self.shutdown.reason();
+        let tokens: Vec<Token> = self.connections.keys().copied().collect();
+        for token in tokens {
+            let logging_out = match self.connections.get_mut(&token) {
+                Some(Connection { session: Some(session), .. }) => {
+                    session.shutdown(&reason);
+                    true
+                }
+                _ => false,
+            };
+            if logging_out {
+                self.next(token);
+            } else {
+                self.close(token, None);
+            }
+        }
+    }
+
+    fn woken(&mut self) {
+        while let Ok(tunneled) = self.tunneled.1.try_recv() {
+            self.tunneled(tunneled);
+        }
+        let mut tokens = match self.woken.lock() {
+            Ok(mut woken) => std::mem::take(&mut *woken),
+            Err(_) => return,
+        };
+        tokens.sort_unstable();
+        tokens.dedup();
+        for token in tokens {
+            self.next(token);
+        }
+    }
+
+    fn tick(&mut self, token: Token) {
+        if self.connections.contains_key(&token) {
+            self.next(token);
+            self.timers
+                .schedule(Instant::now() + TICK_INTERVAL, Timer::Tick(token));
+        }
+    }
+
+    fn next(&mut self, token: Token) {
+        let result = match self.connections.get_mut(&token) {
+            Some(connection) => {
+                connection.next();
+                connection.flush()
+            }
+            None => return,
+        };
+        self.update(token, result);
+    }
+
+    fn ready(&mut self, token: Token) {
+        let result = match self.connections.get_mut(&token) {
+            Some(connection) => connection.ready(&self.factory),
+            None => return,
+        };
+        self.update(token, result);
+    }
+
+    /// Closes the connection on errors, otherwise asks for the readiness it waits for.
+    fn update(&mut self, token: Token, result: Result<(), ReactorError>) {
+        if let Err(e) = result {
+            self.close(token, Some(e));
+            return;
+        }
+        let registry = self.poll.registry();
+        let reregistered = match self.connections.get_mut(&token) {
+            Some(connection) => {
+                let interest = connection.interest();
+                if interest == connection.interest {
+                    return;
+                }
+                connection.interest = interest;
+                match connection.transport.as_mut() {
+                    Some(transport) => registry.reregister(transport.stream_mut(), token, interest),
+                    None => return,
+                }
+            }
+            None => return,
+        };
+        if let Err(e) = reregistered {
+            self.close(token, Some(e.into()));
+        }
+    }
+
+    fn accept(&mut self, token: Token) {
+        loop {
+            let (stream, addr, settings) = match self.listeners.get(&token) {
+                Some((listener, settings)) => match listener.accept() {
+                    Ok((stream, addr)) => (stream, addr, settings.clone()),
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => return,
+                    Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                    Err(e) => {
+                        if let (Some(observer), Ok(endpoint)) = (self.factory.hooks.observer(), listener.local_addr()) {
+                            observer.on_accept_failed(endpoint, &e.into());
+                        }
+                        return;
+                    }
+                },
+                None => return,
+            };
+            let socket_settings = settings[0].socket_settings();
+            let transport = stream
+                .set_nodelay(socket_settings.no_delay())
+                .map_err(ReactorError::from)
+                .and_then(|_| Transport::secure(stream, socket_settings.ssl_options()));
+            match transport {
+                Ok(transport) => {
+                    let token = self.register(transport, socket_settings, settings, None);
+                    self.ready(token);
+                }
+                Err(e) => {
+                    if let (Some(observer), Some(cause)) = (self.factory.hooks.observer(), e.into_cause()) {
+                        observer.on_connect_failed(None, addr, &cause);
+                    }
+                }
+            }
+        }
+    }
+
+    fn connect(&mut self, index: usize) {
+        if !self.shutdown.is_running() {
+            return;
+        }
+        let (settings, failover, _) = &self.initiators[index];
+        let settings = settings.clone();
+        let session_id = settings.session_id();
+        if !is_session_enabled(session_id) || !settings.schedule().is_session_time(&Utc::now()) {
+            let timeout = failover.interval();
+            self.reconnect(index, Some(timeout));
+            return;
+        }
+        let endpoint = failover.endpoint();
+        let address = endpoint.addr;
+        let socket_settings = settings.socket_settings().with_endpoint(endpoint);
+        self.factory
+            .hooks
+            .events
+            .publish(session_id, SessionEvent::Connecting { address });
+        if let Some(observer) = self.factory.hooks.observer() {
+            observer.on_connect_attempt(session_id, address);
+        }
+        if socket_settings.proxy().is_some() {
+            self.tunnel(index, address, socket_settings);
+            return;
+        }
+        match TcpStream::connect(address) {
+            Ok(stream) => {
+                self.register(Transport::Connecting(stream), socket_settings, vec![settings], Some(index));
+            }
+            Err(e) => self.connect_failed(index, address, e.into()),
+        }
+    }
+
+    /// Connects through the proxy on a helper thread, the proxy handshake would block the reactor.
+    /// The stream joins the poll in [`MioReactor::tunneled`] once tunneled.
+    fn tunnel(&mut self, index: usize, address: SocketAddr, socket_settings: SocketSettings) {
+        let sender = self.tunneled.0.clone();
+        let waker = self.waker.clone();
+        let spawned = thread::Builder::new()
+            .name("proxy-connect".into())
+            .spawn(move || {
+                let stream = match (socket_settings.proxy(), socket_settings.endpoint()) {
+                    (Some(proxy), Some(target)) => proxy::connect(proxy, target),
+                    _ => Err(ErrorKind::InvalidInput.into()),
+                };
+                let _ = sender.send(Tunneled { index, address, socket_settings, stream });
+                let _ = waker.wake();
+            });
+        if let Err(e) = spawned {
+            self.connect_failed(index, address, e.into());
+        }
+    }
+
+    fn tunneled(&mut self, tunneled: Tunneled) {
+        let Tunneled { index, address, socket_settings, stream } = tunneled;
+        let stream = stream.and_then(|stream| {
+            stream.set_nonblocking(true)?;
+            Ok(TcpStream::from_std(stream))
+        });
+        match stream {
+            Ok(stream) if self.shutdown.is_running() => {
+                let settings = self.initiators[index].0.clone();
+                self.register(Transport::Connecting(stream), socket_settings, vec![settings], Some(index));
+            }
+            Ok(_) => {}
+            Err(e) => self.connect_failed(index, address, e.into()),
+        }
+    }
+
+    fn connect_failed(&mut self, index: usize, address: SocketAddr, e: ConnectionError) {
+        let session_id = self.initiators[index].0.session_id().clone();
+        if let Some(observer) = self.factory.hooks.observer() {
+            observer.on_connect_failed(Some(&session_id), address, &e);
+        }
+        let reason = format!("{e}");
+        self.factory
+            .hooks
+            .events
+            .publish(&session_id, SessionEvent::ConnectFailed { address, reason });
+        let (_, failover, log) = &mut self.initiators[index];
+        let timeout = failover.log_failed(log, &e);
+        self.reconnect(index, timeout);
+    }
+
+    /// Schedules the next attempt of the initiator at `index`, `None` gives up on it.
+    fn reconnect(&mut self, index: usize, timeout: Option<Duration>) {
+        if !self.shutdown.is_running() {
+            return;
+        }
+        if let Some(timeout) = timeout {
+            self.timers
+                .schedule(Instant::now() + timeout, Timer::Connect(index));
+        }
+    }
+
+    fn register(
+        &mut self,
+        mut transport: Transport,
+        socket_settings: SocketSettings,
+        settings: Vec<SessionSetting>,
+        initiator: Option<usize>,
+    ) -> Token {
+        let token = self.token();
+        let interest = Interest::READABLE | Interest::WRITABLE;
+        let registered = self
+            .poll
+            .registry()
+            .register(transport.stream_mut(), token, interest);
+        let woken = self.woken.clone();
+        let waker = self.waker.clone();
+        let wakeup: Wakeup = Arc::new(move || {
+            if let Ok(mut woken) = woken.lock() {
+                woken.push(token);
+            }
+            let _ = waker.wake();
+        });
+        self.connections.insert(
+            token,
+            Connection {
+                transport: Some(transport),
+                socket_settings,
+                settings,
+                parser: Parser::default(),
+                session: None,
+                events: None,
+                outgoing: Vec::new(),
+                interest,
+                wakeup,
+                started: false,
+                initiator,
+            },
+        );
+        self.timers
+            .schedule(Instant::now() + TICK_INTERVAL, Timer::Tick(token));
+        if let Err(e) = registered {
+            self.close(token, Some(e.into()));
+        }
+        token
+    }
+
+    fn close(&mut self, token: Token, error: Option<ReactorError>) {
+        let mut connection = match self.connections.remove(&token) {
+            Some(connection) => connection,
+            None => return,
+        };
+        let cause = error.and_then(ReactorError::into_cause);
+        // without a session the cause only reaches the observer below
+        if let (Some(cause), Some(session)) = (&cause, connection.session.as_mut()) {
+            session.log().on_event(&format!("Connection error: {cause}"));
+        }
+        if let Some(session) = connection.session.as_mut() {
+            let session_id = session.session_id().clone();
+            session.set_disconnected(&session_id);
+            session.flush();
+            self.shutdowns.extend(SessionShutdown::of(session));
+        }
+        let mut peer = PeerIdentity::default();
+        if let Some(mut transport) = connection.transport.take() {
+            peer = transport.peer_identity();
+            let _ = self.poll.registry().deregister(transport.stream_mut());
+            transport.shutdown();
+        }
+        if let Some(observer) = self.factory.hooks.observer() {
+            let session_id = connection.session.as_ref().map(|s| s.session_id());
+            if connection.started {
+                observer.on_disconnected(session_id, &peer, cause.as_ref());
+            } else if let Some(cause) = &cause {
+                // the connect or the TLS handshake failed
+                let (session_id, address) = match connection.initiator {
+                    Some(_) => (
+                        connection.settings.first().map(SessionSetting::session_id),
+                        connection.socket_settings.get_endpoint().ok(),
+                    ),
+                    None => (None, peer.address()),
+                };
+                if let Some(address) = address {
+                    observer.on_connect_failed(session_id, address, cause);
+                }
+            }
+        }
+        if let Some(index) = connection.initiator {
+            let (settings, failover, log) = &mut self.initiators[index];
+            let timeout = if connection.started {
+                Some(failover.disconnected())
+            } else {
+                let reason = match cause {
+                    Some(e) => e.to_string(),
+                    None => "Reactor stopped".into(),
+                };
+                if let Ok(address) = connection.socket_settings.get_endpoint() {
+                    let reason = reason.clone();
+                    self.factory
+                        .hooks
+                        .events
+                        .publish(settings.session_id(), SessionEvent::ConnectFailed { address, reason });
+                }
+                failover.log_failed(log, &reason)
+            };
+            self.reconnect(index, timeout);
+        }
+    }
+}
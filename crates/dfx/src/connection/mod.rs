@@ -3,22 +3,108 @@ mod initiator;
 use std::{
     fmt::Display,
     net::AddrParseError,
+    sync::Arc,
+};
+
+use dfx_base::{
+    data_dictionary_provider::DataDictionaryProvider, message::MessageParseError,
+    message_factory::MessageFactory,
+};
+
+use crate::{
+    logging::Logger,
+    session::{
+        Application, CredentialStore, ISession, LogonAuthenticator, ResendPolicy, SessionEvents,
+        ValidationRules,
+    },
 };
 
 pub use initiator::*;
 mod acceptor;
 pub use acceptor::*;
+mod failover;
+pub(crate) use failover::*;
+mod observer;
+pub use observer::*;
+mod proxy;
 mod reactor;
 pub(crate) use reactor::*;
+pub use reactor::ReactorError;
+mod shutdown;
+pub use shutdown::*;
 mod socket_settings;
 pub(crate) use socket_settings::*;
 mod stream_factory;
 pub(crate) use stream_factory::*;
+pub(crate) mod tls;
+#[cfg(feature = "tokio")]
+mod tokio_reactor;
+#[cfg(feature = "tokio")]
+pub(crate) use tokio_reactor::*;
+#[cfg(feature = "tokio")]
+mod tokio_acceptor;
+#[cfg(feature = "tokio")]
+pub use tokio_acceptor::*;
+#[cfg(feature = "tokio")]
+mod tokio_initiator;
+#[cfg(feature = "tokio")]
+pub use tokio_initiator::*;
+#[cfg(feature = "mio")]
+mod timer_wheel;
+#[cfg(feature = "mio")]
+pub(crate) use timer_wheel::*;
+#[cfg(feature = "mio")]
+mod mio_reactor;
+#[cfg(feature = "mio")]
+pub(crate) use mio_reactor::*;
+#[cfg(feature = "mio")]
+mod mio_acceptor;
+#[cfg(feature = "mio")]
+pub use mio_acceptor::*;
+#[cfg(feature = "mio")]
+mod mio_initiator;
+#[cfg(feature = "mio")]
+pub use mio_initiator::*;
+
+/// User supplied extensions handed to every session created by an acceptor or initiator.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SessionHooks {
+    pub(crate) validation_rules: ValidationRules,
+    pub(crate) events: SessionEvents,
+    pub(crate) authenticator: Option<Arc<dyn LogonAuthenticator>>,
+    pub(crate) credential_store: Option<Arc<dyn CredentialStore>>,
+    pub(crate) resend_policy: ResendPolicy,
+    pub(crate) observer: Option<Arc<dyn ConnectionObserver>>,
+}
+
+impl SessionHooks {
+    pub(crate) fn apply<App, DDP, Log, MF>(&self, session: &mut ISession<App, DDP, Log, MF>)
+    where App: Application + Clone + 'static,
+          DDP: DataDictionaryProvider + Send + Clone + 'static,
+          Log: Logger + Clone,
+          MF: MessageFactory + Send + Clone + 'static,
+    {
+        session.set_validation_rules(self.validation_rules.clone());
+        session.set_events(self.events.clone());
+        session.set_authenticator(self.authenticator.clone());
+        session.set_credential_store(self.credential_store.clone());
+        session.set_resend_policy(self.resend_policy.clone());
+    }
+
+    pub(crate) fn observer(&self) -> Option<&dyn ConnectionObserver> {
+        self.observer.as_deref()
+    }
+}
 
+/// Why a connection could not be established or was dropped.
 #[derive(Debug)]
-pub(crate) enum ConnectionError {
+pub enum ConnectionError {
     IOError(std::io::Error),
     AddrParseError(AddrParseError),
+    /// The TLS handshake with the peer failed.
+    HandshakeError(String),
+    /// The peer sent bytes that are not a FIX message.
+    ParseError(MessageParseError),
 }
 impl From<std::io::Error> for ConnectionError {
     fn from(e: std::io::Error) -> ConnectionError {
@@ -40,6 +126,22 @@ impl Display for ConnectionError {
             ConnectionError::AddrParseError(err) => {
                 fmt.write_fmt(format_args!("Failed to parse address: {}", err))
             }
+            ConnectionError::HandshakeError(err) => {
+                fmt.write_fmt(format_args!("TLS handshake failed: {}", err))
+            }
+            ConnectionError::ParseError(err) => {
+                fmt.write_fmt(format_args!("Failed to parse message: {:?}", err))
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConnectionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConnectionError::IOError(err) => Some(err),
+            ConnectionError::AddrParseError(err) => Some(err),
+            _ => None,
         }
     }
 }
@@ -0,0 +1,52 @@
+use std::net::SocketAddr;
+
+use dfx_base::session_id::SessionId;
+
+use crate::session::PeerIdentity;
+
+use super::ConnectionError;
+
+/// Notified when the connections of an acceptor or initiator are opened and closed, e.g. to
+/// alert when a counterparty is unreachable.
+///
+/// Called on the connection's thread or task, implementations should hand the notification off
+/// rather than block. `session_id` is `None` for accepted connections whose Logon has not
+/// identified the session yet.
+#[allow(unused_variables)]
+pub trait ConnectionObserver: Send + Sync {
+    /// An initiator is opening a connection to `address`.
+    fn on_connect_attempt(&self, session_id: &SessionId, address: SocketAddr) {}
+
+    /// The connection is established, TLS already negotiated.
+    fn on_connected(&self, session_id: Option<&SessionId>, peer: &PeerIdentity) {}
+
+    /// An initiator could not connect to `address`, or the TLS handshake with an initiator or
+    /// an accepted peer failed.
+    fn on_connect_failed(
+        &self,
+        session_id: Option<&SessionId>,
+        address: SocketAddr,
+        error: &ConnectionError,
+    ) {
+    }
+
+    /// The acceptor listening on `endpoint` could not accept a connection, e.g. because the
+    /// process ran out of file descriptors. The acceptor keeps listening.
+    fn on_accept_failed(&self, endpoint: SocketAddr, error: &ConnectionError) {}
+
+    /// An established connection was closed. `cause` is `None` when the connection ended
+    /// regularly, after a Logout, on shutdown or because the peer closed it.
+    fn on_disconnected(
+        &self,
+        session_id: Option<&SessionId>,
+        peer: &PeerIdentity,
+        cause: Option<&ConnectionError>,
+    ) {
+    }
+}
+
+impl std::fmt::Debug for dyn ConnectionObserver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ConnectionObserver")
+    }
+}
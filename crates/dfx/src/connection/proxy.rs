@@ -0,0 +1,248 @@
+use std::{
+    io::{self, Read, Write},
+    net::{IpAddr, TcpStream},
+    time::Duration,
+};
+
+use crate::session::{Endpoint, ProxyCredentials, ProxyOptions, ProxyType};
+
+/// How long the proxy gets to answer each step of the handshake.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Connects to `target` through `proxy`.
+///
+/// `target` is passed on as configured, the proxy resolves host names. The returned stream is
+/// tunneled to `target`, TLS is negotiated on top of it as usual.
+pub(crate) fn connect(proxy: &ProxyOptions, target: &Endpoint) -> io::Result<TcpStream> {
+    let mut stream = TcpStream::connect_timeout(&proxy.addr, HANDSHAKE_TIMEOUT)?;
+    stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT))?;
+    match proxy.proxy_type {
+        ProxyType::Socks5 => socks5(&mut stream, target, proxy.credentials.as_ref())?,
+        ProxyType::Http => http_connect(&mut stream, target, proxy.credentials.as_ref())?,
+    }
+    stream.set_read_timeout(None)?;
+    Ok(stream)
+}
+
+fn proxy_error(reason: String) -> io::Error {
+    io::Error::new(io::ErrorKind::ConnectionRefused, reason)
+}
+
+/// RFC 1928 CONNECT, with RFC 1929 username/password authentication when configured.
+fn socks5<S: Read + Write>(
+    stream: &mut S,
+    target: &Endpoint,
+    credentials: Option<&ProxyCredentials>,
+) -> io::Result<()> {
+    const NO_AUTHENTICATION: u8 = 0x00;
+    const USERNAME_PASSWORD: u8 = 0x02;
+
+    match credentials {
+        Some(_) => stream.write_all(&[5, 2, NO_AUTHENTICATION, USERNAME_PASSWORD])?,
+        None => stream.write_all(&[5, 1, NO_AUTHENTICATION])?,
+    }
+    let mut reply = [0; 2];
+    stream.read_exact(&mut reply)?;
+    match (reply, credentials) {
+        ([5, NO_AUTHENTICATION], _) => {}
+        ([5, USERNAME_PASSWORD], Some(credentials)) => {
+            let user = credentials.user.as_bytes();
+            let password = credentials.password.as_bytes();
+            if user.len() > 255 || password.len() > 255 {
+                return Err(proxy_error("SOCKS5 credentials longer than 255 bytes".into()));
+            }
+            let mut request = vec![1, user.len() as u8];
+            request.extend_from_slice(user);
+            request.push(password.len() as u8);
+            request.extend_from_slice(password);
+            stream.write_all(&request)?;
+            stream.read_exact(&mut reply)?;
+            if reply[1] != 0 {
+                return Err(proxy_error("SOCKS5 proxy rejected the credentials".into()));
+            }
+        }
+        (reply, _) => {
+            return Err(proxy_error(format!(
+                "SOCKS5 proxy offered no acceptable authentication: {reply:?}"
+            )))
+        }
+    }
+
+    let mut request = vec![5, 1, 0];
+    match target.host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => {
+            request.push(1);
+            request.extend_from_slice(&ip.octets());
+        }
+        Ok(IpAddr::V6(ip)) => {
+            request.push(4);
+            request.extend_from_slice(&ip.octets());
+        }
+        Err(_) => {
+            let host = target.host.as_bytes();
+            if host.len() > 255 {
+                return Err(proxy_error("SOCKS5 host name longer than 255 bytes".into()));
+            }
+            request.push(3);
+            request.push(host.len() as u8);
+            request.extend_from_slice(host);
+        }
+    }
+    request.extend_from_slice(&target.port.to_be_bytes());
+    stream.write_all(&request)?;
+
+    let mut reply = [0; 4];
+    stream.read_exact(&mut reply)?;
+    if reply[1] != 0 {
+        return Err(proxy_error(format!(
+            "SOCKS5 proxy failed to connect to {target}: reply {}",
+            reply[1]
+        )));
+    }
+    // skip the bound address and port
+    let address_len = match reply[3] {
+        1 => 4,
+        4 => 16,
+        3 => {
+            let mut len = [0; 1];
+            stream.read_exact(&mut len)?;
+            len[0] as usize
+        }
+        atyp => return Err(proxy_error(format!("SOCKS5 proxy sent address type {atyp}"))),
+    };
+    let mut bound = vec![0; address_len + 2];
+    stream.read_exact(&mut bound)?;
+    Ok(())
+}
+
+/// HTTP CONNECT, with basic authentication when configured.
+fn http_connect<S: Read + Write>(
+    stream: &mut S,
+    target: &Endpoint,
+    credentials: Option<&ProxyCredentials>,
+) -> io::Result<()> {
+    let mut request = format!("CONNECT {target} HTTP/1.1\r\nHost: {target}\r\n");
+    if let Some(credentials) = credentials {
+        let token = base64(format!("{}:{}", credentials.user, credentials.password).as_bytes());
+        request.push_str(&format!("Proxy-Authorization: Basic {token}\r\n"));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes())?;
+
+    // read byte by byte, anything after the headers already belongs to the tunnel
+    let mut response = Vec::new();
+    let mut byte = [0; 1];
+    while !response.ends_with(b"\r\n\r\n") {
+        if response.len() > 8192 {
+            return Err(proxy_error("HTTP proxy response headers too long".into()));
+        }
+        stream.read_exact(&mut byte)?;
+        response.push(byte[0]);
+    }
+    let response = String::from_utf8_lossy(&response);
+    let status_line = response.lines().next().unwrap_or_default();
+    match status_line.split_whitespace().nth(1) {
+        Some("200") => Ok(()),
+        _ => Err(proxy_error(format!(
+            "HTTP proxy failed to connect to {target}: {status_line}"
+        ))),
+    }
+}
+
+fn base64(input: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut output = String::with_capacity(input.len().div_ceil(3) * 4);
+    for chunk in input.chunks(3) {
+        let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                output.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                output.push('=');
+            }
+        }
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Cursor, Read, Write};
+
+    use crate::session::Endpoint;
+
+    use super::{base64, http_connect, socks5};
+
+    /// Replays `input` as the proxy's answers and records what was sent.
+    struct Proxy {
+        input: Cursor<Vec<u8>>,
+        output: Vec<u8>,
+    }
+
+    impl Read for Proxy {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.input.read(buf)
+        }
+    }
+
+    impl Write for Proxy {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.output.write(buf)
+        }
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn endpoint(host: &str) -> Endpoint {
+        Endpoint {
+            host: host.into(),
+            port: 5001,
+            addr: "127.0.0.1:1080".parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn socks5_sends_host_names_unresolved() {
+        let mut proxy = Proxy {
+            input: Cursor::new(vec![5, 0, 5, 0, 0, 1, 0, 0, 0, 0, 0, 0]),
+            output: Vec::new(),
+        };
+        socks5(&mut proxy, &endpoint("fix.example.com"), None).unwrap();
+        let mut expected = vec![5, 1, 0, 5, 1, 0, 3, 15];
+        expected.extend_from_slice(b"fix.example.com");
+        expected.extend_from_slice(&5001u16.to_be_bytes());
+        assert_eq!(proxy.output, expected);
+    }
+
+    #[test]
+    fn socks5_sends_ip_addresses() {
+        let mut proxy = Proxy {
+            input: Cursor::new(vec![5, 0, 5, 0, 0, 1, 0, 0, 0, 0, 0, 0]),
+            output: Vec::new(),
+        };
+        socks5(&mut proxy, &endpoint("10.0.0.1"), None).unwrap();
+        assert_eq!(proxy.output[3..], [5, 1, 0, 1, 10, 0, 0, 1, 0x13, 0x89]);
+    }
+
+    #[test]
+    fn http_connect_sends_host_names_unresolved() {
+        let mut proxy = Proxy {
+            input: Cursor::new(b"HTTP/1.1 200 Connection established\r\n\r\n".to_vec()),
+            output: Vec::new(),
+        };
+        http_connect(&mut proxy, &endpoint("fix.example.com"), None).unwrap();
+        assert_eq!(
+            String::from_utf8(proxy.output).unwrap(),
+            "CONNECT fix.example.com:5001 HTTP/1.1\r\nHost: fix.example.com:5001\r\n\r\n"
+        );
+    }
+
+    #[test]
+    fn encodes_base64_with_padding() {
+        assert_eq!(base64(b"user:pass"), "dXNlcjpwYXNz");
+        assert_eq!(base64(b"user:passw"), "dXNlcjpwYXNzdw==");
+        assert_eq!(base64(b"user:passwo"), "dXNlcjpwYXNzd28=");
+    }
+}
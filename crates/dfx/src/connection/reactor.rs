@@ -1,7 +1,11 @@
 use std::{
+    fmt::Display,
     io::{Read, Write},
-    sync::mpsc::{Receiver, Sender},
-    time::Duration,
+    sync::{
+        mpsc::{Receiver, Sender},
+        Arc,
+    },
+    time::{Duration, Instant},
 };
 
 use dfx_base::message::{Message, MessageParseError};
@@ -13,13 +17,16 @@ use crate::{
     parser::{Parser, ParserError},
     session::{
         Application, ChannelResponder, ResponderEvent, ResponderResponse, ISession,
-        SessionSetting,
+        PeerIdentity, SessionSetting, SessionThread,
     }, message_store::MessageStoreFactory, logging::{LogFactory, Logger},
 };
 
-use super::{ConnectionError, Stream, StreamError};
+use super::{ConnectionError, SessionHooks, ShutdownSignal, Stream, StreamError};
 
 pub(crate) const BUF_SIZE: usize = 512;
+/// The session timer: heartbeats, test requests and timeouts are checked at least this often,
+/// also while the socket keeps delivering data.
+pub(crate) const TICK_INTERVAL: Duration = Duration::from_millis(50);
 pub(crate) struct SocketReactor<App: Application, StoreFactory, DataDictionaryProvider, LogFactory, MessageFactory, Log> {
     session: Option<ISession<App, DataDictionaryProvider, Log, MessageFactory>>,
     parser: Parser,
@@ -33,18 +40,63 @@ pub(crate) struct SocketReactor<App: Application, StoreFactory, DataDictionaryPr
     data_dictionary_provider: DataDictionaryProvider,
     log_factory: LogFactory,
     message_factory: MessageFactory,
+    hooks: SessionHooks,
+    shutdown: Arc<ShutdownSignal>,
+    next_tick: Instant,
+    // set on the reactor's thread once it knows its session
+    session_thread: Option<SessionThread>,
 }
 
+/// Why a session's connection stopped being served.
 #[derive(Debug)]
-pub(crate) enum ReactorError {
+pub enum ReactorError {
     ConnectionError(ConnectionError),
     ParserError(ParserError),
+    /// The peer sent bytes that are not a FIX message.
     MessageParseError(MessageParseError),
     IoError(std::io::Error),
-    StreamError(StreamError),
+    /// The session or the peer closed the connection.
     Disconnect,
 }
 
+impl Display for ReactorError {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReactorError::ConnectionError(err) => fmt.write_fmt(format_args!("{}", err)),
+            ReactorError::ParserError(err) => match *err {},
+            ReactorError::MessageParseError(err) => {
+                fmt.write_fmt(format_args!("Failed to parse message: {:?}", err))
+            }
+            ReactorError::IoError(err) => fmt.write_fmt(format_args!("IO error: {}", err)),
+            ReactorError::Disconnect => fmt.write_str("Disconnected"),
+        }
+    }
+}
+
+impl std::error::Error for ReactorError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ReactorError::ConnectionError(err) => Some(err),
+            ReactorError::ParserError(err) => match *err {},
+            ReactorError::IoError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl ReactorError {
+    /// What made the connection fail, `None` for a regular disconnect.
+    pub(crate) fn into_cause(self) -> Option<ConnectionError> {
+        match self {
+            ReactorError::ConnectionError(e) => Some(e),
+            ReactorError::ParserError(e) => match e {},
+            ReactorError::MessageParseError(e) => Some(ConnectionError::ParseError(e)),
+            ReactorError::IoError(e) => Some(ConnectionError::IOError(e)),
+            ReactorError::Disconnect => None,
+        }
+    }
+}
+
 impl From<ConnectionError> for ReactorError {
     fn from(e: ConnectionError) -> ReactorError {
         ReactorError::ConnectionError(e)
@@ -67,7 +119,9 @@ impl From<std::io::Error> for ReactorError {
 }
 impl From<StreamError> for ReactorError {
     fn from(e: StreamError) -> ReactorError {
-        ReactorError::StreamError(e)
+        match e {
+            StreamError::IO(e) => ReactorError::IoError(e),
+        }
     }
 }
 
@@ -79,11 +133,14 @@ where App: Application + Clone + 'static,
       MF: MessageFactory + Send + Clone + 'static,
       Log: Logger + Clone + 'static
 {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         connection: Stream,
         settings: Vec<SessionSetting>,
         app: App,
-        store_factory: SF, data_dictionary_provider: DDP, log_factory: LF, message_factory: MF
+        store_factory: SF, data_dictionary_provider: DDP, log_factory: LF, message_factory: MF,
+        hooks: SessionHooks,
+        shutdown: Arc<ShutdownSignal>,
     ) -> Self {
         let mut reactor = SocketReactor {
             session: None,
@@ -99,6 +156,10 @@ where App: Application + Clone + 'static,
             data_dictionary_provider,
             log_factory,
             message_factory,
+            hooks,
+            shutdown,
+            next_tick: Instant::now() + TICK_INTERVAL,
+            session_thread: None,
         };
         if reactor.settings.len() == 1 {
             let session_setting = &reactor.settings[0];
@@ -128,83 +189,82 @@ where App: Application + Clone + 'static,
     }
 
     pub(crate) fn start(mut self) -> Option<ISession<App, DDP, Log, MF>> {
+        let peer = self
+            .stream
+            .as_ref()
+            .map(Stream::peer_identity)
+            .unwrap_or_default();
+        self.session_thread = self.session.as_ref().map(|s| SessionThread::enter(s.session_id()));
+        if let Some(observer) = self.hooks.observer() {
+            let session_id = self.session.as_ref().map(|s| s.session_id());
+            observer.on_connected(session_id, &peer);
+        }
         // TODO while within session time
-        if let Err(e) = self.event_loop() {
-            match e {
-                ReactorError::Disconnect => {
-                    if let Some(session) = self.session.as_ref() {
-                        let session_id = session.session_id().clone();
-                        self.set_disconnected(session_id);
-                    } else {
-                        // TODO
-                    }
-                }
-                e => todo!("SocketReactor::start: Error {:?}", e),
-            }
+        let result = self.event_loop();
+        if let Some(session) = self.session.as_mut() {
+            let session_id = session.session_id().clone();
+            session.set_disconnected(&session_id);
+        }
+        let cause = result.err().and_then(ReactorError::into_cause);
+        // without a session the cause only reaches the observer below
+        if let (Some(cause), Some(session)) = (&cause, self.session.as_mut()) {
+            session.log().on_event(&format!("Connection error: {cause}"));
+        }
+        if let Some(stream) = self.stream.as_mut() {
+            let _ = stream.shutdown(std::net::Shutdown::Both);
+        }
+        if let Some(session) = self.session.as_mut() {
+            session.flush();
+        }
+        if let Some(observer) = self.hooks.observer() {
+            let session_id = self.session.as_ref().map(|s| s.session_id());
+            observer.on_disconnected(session_id, &peer, cause.as_ref());
         }
         self.session
     }
 
+    /// Runs until the session disconnects, `Err(ReactorError::Disconnect)` on a regular disconnect.
     fn event_loop(&mut self) -> Result<(), ReactorError> {
-        while let None = self.session {
+        while self.session.is_none() {
+            if !self.shutdown.is_running() {
+                return Err(ReactorError::Disconnect);
+            }
             self.read()?;
         }
 
-        //TODO empty session
-        let session_id = self
-            .session
-            .as_ref()
-            .expect("Session not found!")
-            .session_id()
-            .clone();
-        self.set_connected(session_id.clone())?;
-
-        let session = self.session.as_mut().expect("Session not found!");
+        let session = self.session.as_mut().ok_or(ReactorError::Disconnect)?;
+        let session_id = session.session_id().clone();
         session
-            .log()
-            .on_event(format!("Connection succeeded {}", &session_id).as_str());
+            .set_connected(&session_id)
+            .map_err(|_e| ReactorError::Disconnect)?;
+        session.log_connected();
         session.next();
-        while let Ok(()) = self.read() {}
-        let session_id = self
-            .session
-            .as_ref()
-            .expect("Session not found!")
-            .session_id()
-            .clone();
-        self.set_disconnected(session_id);
-        let remote = self.stream.as_ref().unwrap().peer_addr()?;
-        self.stream
-            .as_mut()
-            .unwrap()
-            .shutdown(std::net::Shutdown::Both)?;
-        println!("Disconnected: {remote:?}");
-        Ok(())
-    }
-
-    // TODO move this to a concurrent map > SessionState > Sender<Message>
-    fn set_connected(&mut self, session_id: SessionId) -> Result<(), ReactorError> {
-        self.session
-            .as_mut()
-            .unwrap()
-            .set_connected(&session_id).map_err(|_e| ReactorError::Disconnect)?;
-        Ok(())
-    }
-
-    // TODO move this to a concurrent map > SessionState > Sender<Message>
-    fn set_disconnected(&mut self, session_id: SessionId) {
-        self.session.as_mut().unwrap().set_disconnected(&session_id);
+        loop {
+            self.read()?;
+            if !self.shutdown.is_running() {
+                let reason = self.shutdown.reason();
+                if let Some(session) = self.session.as_mut() {
+                    // logs out once, then keeps reading until the Logout is answered or times out
+                    session.shutdown(&reason);
+                }
+            }
+        }
     }
 
     fn read(&mut self) -> Result<(), ReactorError> {
         let read = self.read_some()?;
         if read > 0 {
             self.parser.add_to_stream(&self.buffer[..read]);
-        } else if let Some(session) = self.get_session_mut() {
-            session.next();
-            // } else {
-            //     return Err(ReactorError::Timeout(
-            //         "Reactor timed out while reading socket".into(),
-            //     ));
+        }
+        if read == 0 || Instant::now() >= self.next_tick {
+            if let Some(session) = self.get_session_mut() {
+                session.next();
+                self.next_tick = Instant::now() + TICK_INTERVAL;
+                // } else {
+                //     return Err(ReactorError::Timeout(
+                //         "Reactor timed out while reading socket".into(),
+                //     ));
+            }
         }
 
         self.process_responder()?;
@@ -219,14 +279,14 @@ where App: Application + Clone + 'static,
         if let Some(stream) = self.stream.as_mut() {
             match stream.read(&mut self.buffer) {
                 Ok(read) => Ok(read),
-                Err(ref e) if e.as_io_error().is_some() && e.as_io_error().unwrap().kind() == std::io::ErrorKind::WouldBlock => {
+                Err(ref e) if e.as_io_error().is_some_and(|e| e.kind() == std::io::ErrorKind::WouldBlock) => {
                     // println!("Would block {e:?}");
                     Ok(0)
                 },
                 Err(e) => Err(e.into()),
             }
         } else {
-            panic!("reactor::read_some")
+            Err(ReactorError::Disconnect)
         }
     }
 
@@ -235,18 +295,20 @@ where App: Application + Clone + 'static,
             if let Some(session) = self.session.as_mut() {
                 session.next_msg(msg);
             } else {
-                let message = Message::new(&msg[..]).map_err(|_e| ReactorError::Disconnect)?;
+                let message = Message::new(&msg[..])?;
                 let session_id = message.extract_contra_session_id();
-                eprintln!("Extracted session id {session_id}");
                 let session_settings = self.for_session_id(&session_id);
                 match session_settings {
                     Some(settings) => {
                         if settings.accepts(&session_id) {
                             let session = self.create_session(session_id.clone(), settings);
+                            self.session_thread = Some(SessionThread::enter(&session_id));
                             self.session = Some(session);
                             self.create_responder();
                             // queue instead?
-                            self.session.as_mut().unwrap().next_msg(msg);
+                            if let Some(session) = self.session.as_mut() {
+                                session.next_msg(msg);
+                            }
                         } else {
                             return Err(ReactorError::Disconnect);
                         }
@@ -263,7 +325,9 @@ where App: Application + Clone + 'static,
 
     fn create_session(&self, session_id: SessionId, settings: &SessionSetting) -> ISession<App, DDP, Log, MF> {
         let log = self.log_factory.create(&session_id);
-        ISession::from_settings(
+        let peer = self.stream.as_ref().map(Stream::peer_identity).unwrap_or_default();
+        let rejection = certificate_mismatch(settings, &session_id, &peer);
+        let mut session = ISession::from_settings(
             session_id,
             self.app.clone(),
             Box::new(self.store_factory.clone()),
@@ -271,7 +335,13 @@ where App: Application + Clone + 'static,
             log,
             self.message_factory.clone(),
             settings.clone()
-        )
+        );
+        self.hooks.apply(&mut session);
+        session.set_peer(peer);
+        if let Some(reason) = rejection {
+            session.reject_logon(reason);
+        }
+        session
     }
 
     fn process_responder(&mut self) -> Result<(), ReactorError> {
@@ -279,17 +349,14 @@ where App: Application + Clone + 'static,
             (Some(tx), Some(rx)) => match rx.recv_timeout(Duration::from_millis(1)) {
                 Ok(event) => match event {
                     ResponderEvent::Send(message) => {
-                        let stream = &mut self.stream.as_mut().unwrap();
+                        let stream = self.stream.as_mut().ok_or(ReactorError::Disconnect)?;
                         match stream.write_all(message.as_bytes()) {
                             Ok(_) => tx.send(ResponderResponse::Sent(true)).unwrap_or(()),
                             Err(_) => tx.send(ResponderResponse::Sent(false)).unwrap_or(()),
                         };
                         Ok(stream.flush()?)
                     }
-                    ResponderEvent::Disconnect => {
-                        println!("Reactor: Disconnect");
-                        Err(ReactorError::Disconnect)
-                    }
+                    ResponderEvent::Disconnect => Err(ReactorError::Disconnect),
                 },
                 Err(_) => Ok(()),
             },
@@ -298,13 +365,29 @@ where App: Application + Clone + 'static,
     }
 
     fn for_session_id(&self, session_id: &SessionId) -> Option<&SessionSetting> {
-        let best_match = &self
-            .settings
-            .iter()
-            .map(|s| (s.score(session_id), s))
-            .filter(|(score, _)| score > &0)
-            .max_by(|(k1, _), (k2, _)| k1.cmp(k2))
-            .map(|(_, v)| v);
-        *best_match
+        best_session_setting(&self.settings, session_id)
     }
 }
+
+/// Why the counterparty may not log on as `session_id`, its client certificate isn't bound to the
+/// CompID by `SSLCertificateCompIDs`.
+pub(crate) fn certificate_mismatch(
+    settings: &SessionSetting,
+    session_id: &SessionId,
+    peer: &PeerIdentity,
+) -> Option<String> {
+    settings.certificate_comp_ids()?.check(session_id, peer.certificate())
+}
+
+/// The setting that matches `session_id` best, used for sessions created from their first message.
+pub(crate) fn best_session_setting<'a>(
+    settings: &'a [SessionSetting],
+    session_id: &SessionId,
+) -> Option<&'a SessionSetting> {
+    settings
+        .iter()
+        .map(|s| (s.score(session_id), s))
+        .filter(|(score, _)| score > &0)
+        .max_by(|(k1, _), (k2, _)| k1.cmp(k2))
+        .map(|(_, v)| v)
+}
@@ -0,0 +1,115 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+};
+
+use dfx_base::{
+    data_dictionary_provider::DataDictionaryProvider, message_factory::MessageFactory,
+    session_id::SessionId,
+};
+
+use crate::{
+    logging::Logger,
+    session::{Application, ISession, SessionSettings},
+};
+
+/// Logout reason used by `stop()`.
+pub(crate) const SHUTDOWN_REASON: &str = "Shutting down";
+
+/// How a session ended when its acceptor or initiator shut down.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogoutOutcome {
+    /// Logout sent and answered by the counterparty.
+    LoggedOut,
+    /// Logout sent but not answered within `LogoutTimeout`.
+    TimedOut,
+    /// Connected but not logged on, closed without a Logout.
+    NotLoggedOn,
+    /// No connection at the time of the shutdown.
+    Disconnected,
+}
+
+/// Returned per session by `shutdown()` and `stop()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionShutdown {
+    session_id: SessionId,
+    outcome: LogoutOutcome,
+}
+
+impl SessionShutdown {
+    pub(crate) fn new(session_id: SessionId, outcome: LogoutOutcome) -> Self {
+        SessionShutdown { session_id, outcome }
+    }
+
+    /// The outcome of a session that was connected when the shutdown started.
+    pub(crate) fn of<App, DDP, Log, MF>(session: &ISession<App, DDP, Log, MF>) -> Option<Self>
+    where App: Application + Clone + 'static,
+          DDP: DataDictionaryProvider + Send + Clone + 'static,
+          Log: Logger + Clone,
+          MF: MessageFactory + Send + Clone + 'static,
+    {
+        session
+            .shutdown_outcome()
+            .map(|outcome| SessionShutdown::new(session.session_id().clone(), outcome))
+    }
+
+    pub fn session_id(&self) -> &SessionId {
+        &self.session_id
+    }
+
+    pub fn outcome(&self) -> LogoutOutcome {
+        self.outcome
+    }
+}
+
+/// Shared by an acceptor or initiator and its reactors, which log out their sessions once it
+/// is triggered.
+#[derive(Debug, Default)]
+pub(crate) struct ShutdownSignal {
+    running: AtomicBool,
+    reason: Mutex<Option<String>>,
+}
+
+impl ShutdownSignal {
+    pub(crate) fn start(&self) {
+        self.running.store(true, Ordering::SeqCst);
+    }
+
+    pub(crate) fn trigger(&self, reason: &str) {
+        if let Ok(mut guard) = self.reason.lock() {
+            guard.replace(reason.into());
+        }
+        self.running.store(false, Ordering::SeqCst);
+    }
+
+    pub(crate) fn is_running(&self) -> bool {
+        self.running.load(Ordering::Relaxed)
+    }
+
+    /// The Logout reason passed to [`ShutdownSignal::trigger`].
+    pub(crate) fn reason(&self) -> String {
+        self.reason
+            .lock()
+            .ok()
+            .and_then(|guard| guard.clone())
+            .unwrap_or_else(|| SHUTDOWN_REASON.into())
+    }
+}
+
+/// Completes the shutdowns reported by the reactors with every configured session that
+/// had no connection.
+pub(crate) fn summarize(
+    settings: &SessionSettings,
+    mut shutdowns: Vec<SessionShutdown>,
+) -> Vec<SessionShutdown> {
+    for setting in settings.sessions() {
+        let session_id = setting.session_id();
+        if !setting.is_dynamic() && !shutdowns.iter().any(|s| s.session_id() == session_id) {
+            shutdowns.push(SessionShutdown::new(
+                session_id.clone(),
+                LogoutOutcome::Disconnected,
+            ));
+        }
+    }
+    shutdowns
+}
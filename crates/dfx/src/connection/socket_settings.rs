@@ -1,6 +1,6 @@
 use std::net::SocketAddr;
 
-use crate::session::{SocketOptions, SslOptions};
+use crate::session::{Endpoint, ProxyOptions, SocketOptions, SslOptions};
 
 use super::ConnectionError;
 
@@ -13,6 +13,8 @@ pub(crate) struct SocketSettings {
     send_timeout: u64,
     receive_timeout: u64,
     ssl_options: Option<SslOptions>,
+    proxy: Option<ProxyOptions>,
+    endpoint: Option<Endpoint>,
 }
 
 impl SocketSettings {
@@ -25,7 +27,9 @@ impl SocketSettings {
             // receive_buffer_size: socket_options.receive_buffer_size(),
             send_timeout: socket_options.send_timeout(),
             receive_timeout: socket_options.receive_timeout(),
-            ssl_options
+            ssl_options,
+            proxy: None,
+            endpoint: None,
         }
     }
 
@@ -33,6 +37,24 @@ impl SocketSettings {
         Ok(self.addr)
     }
 
+    /// Tunnel connections through `proxy`, initiators only.
+    pub(crate) fn with_proxy(mut self, proxy: Option<ProxyOptions>) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
+    /// The same settings for another endpoint, used by initiators failing over.
+    pub(crate) fn with_endpoint(mut self, endpoint: Endpoint) -> Self {
+        self.addr = endpoint.addr;
+        self.endpoint = Some(endpoint);
+        self
+    }
+
+    /// The endpoint of an initiator as configured, the proxy connects to it.
+    pub(crate) fn endpoint(&self) -> Option<&Endpoint> {
+        self.endpoint.as_ref()
+    }
+
 
     pub(crate) fn no_delay(&self) -> bool {
         self.no_delay
@@ -57,4 +79,8 @@ impl SocketSettings {
     pub(crate) fn ssl_options(&self) -> &Option<SslOptions> {
         &self.ssl_options
     }
+
+    pub(crate) fn proxy(&self) -> Option<&ProxyOptions> {
+        self.proxy.as_ref()
+    }
 }
@@ -1,5 +1,8 @@
+use crate::connection::proxy;
+use crate::connection::tls::{self, TlsStream};
 use crate::connection::ConnectionError;
 use crate::connection::SocketSettings;
+use crate::session::PeerIdentity;
 use crate::session::SslOptions;
 use std::io::Read;
 use std::io::Write;
@@ -9,7 +12,7 @@ use std::time::Duration;
 
 pub(crate) enum Stream {
     Tcp(TcpStream),
-    Ssl(native_tls::TlsStream<TcpStream>),
+    Ssl(Box<TlsStream<TcpStream>>),
 }
 
 #[derive(Debug)]
@@ -38,6 +41,14 @@ impl Stream {
             Stream::Ssl(ssl) => ssl.get_ref().peer_addr().map(|addr| Some(addr)),
         }
     }
+    pub(crate) fn peer_identity(&self) -> PeerIdentity {
+        let address = self.peer_addr().ok().flatten();
+        let certificate = match self {
+            Stream::Tcp(_) => None,
+            Stream::Ssl(ssl) => tls::peer_certificate(ssl),
+        };
+        PeerIdentity::new(address, certificate)
+    }
     pub(crate) fn shutdown(&mut self, how: std::net::Shutdown) -> std::io::Result<()> {
         match self {
             Stream::Tcp(tcp) => tcp.shutdown(how),
@@ -88,7 +99,10 @@ impl StreamFactory {
         settings: SocketSettings,
     ) -> Result<Stream, ConnectionError> {
         let endpoint: SocketAddr = settings.get_endpoint()?;
-        let stream = TcpStream::connect(endpoint)?;
+        let stream = match (settings.proxy(), settings.endpoint()) {
+            (Some(proxy), Some(target)) => proxy::connect(proxy, target)?,
+            _ => TcpStream::connect(endpoint)?,
+        };
         let stream =  StreamFactory::configure_stream(stream, settings, false)?;
         Ok(stream)
     }
@@ -99,14 +113,14 @@ impl StreamFactory {
     ) -> Result<Stream, ConnectionError> {
         match settings.ssl_options() {
             Some(SslOptions::Acceptor { acceptor }) => {
-                let mut stream = acceptor.accept(stream).unwrap();
+                let mut stream = tls::establish(tls::accept(acceptor, stream)?)?;
                 StreamFactory::configure_stream_mut(stream.get_mut(), &settings)?;
-                Ok(Stream::Ssl(stream))
+                Ok(Stream::Ssl(Box::new(stream)))
             },
             Some(SslOptions::Initiator { initiator, domain }) => {
-                let mut stream = initiator.connect(domain, stream).unwrap();
+                let mut stream = tls::establish(tls::connect(initiator, domain, stream)?)?;
                 StreamFactory::configure_stream_mut(stream.get_mut(), &settings)?;
-                Ok(Stream::Ssl(stream))
+                Ok(Stream::Ssl(Box::new(stream)))
             },
             None => {
                 StreamFactory::configure_stream_mut(&mut stream, &settings)?;
@@ -0,0 +1,115 @@
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    time::{Duration, Instant},
+};
+
+/// Hashed timer wheel, timers fire on the first tick of `resolution` at or after their deadline.
+///
+/// Expiring is O(1) per timer, a timer further away than one rotation stays in its slot until
+/// the wheel has come around often enough. The due ticks are also kept in a heap so the next
+/// deadline is known without scanning the slots.
+pub(crate) struct TimerWheel<T> {
+    slots: Vec<Vec<(u64, T)>>,
+    deadlines: BinaryHeap<Reverse<u64>>,
+    resolution: Duration,
+    start: Instant,
+    current: u64,
+}
+
+impl<T> TimerWheel<T> {
+    pub(crate) fn new(resolution: Duration, slots: usize) -> Self {
+        assert!(!resolution.is_zero() && slots > 0, "TimerWheel needs a resolution and slots");
+        TimerWheel {
+            slots: (0..slots).map(|_| Vec::new()).collect(),
+            deadlines: BinaryHeap::new(),
+            resolution,
+            start: Instant::now(),
+            current: 0,
+        }
+    }
+
+    fn tick_of(&self, instant: Instant) -> u64 {
+        let elapsed = instant.saturating_duration_since(self.start);
+        (elapsed.as_nanos() / self.resolution.as_nanos()) as u64
+    }
+
+    pub(crate) fn schedule(&mut self, deadline: Instant, timer: T) {
+        let elapsed = deadline.saturating_duration_since(self.start).as_nanos();
+        let resolution = self.resolution.as_nanos();
+        let tick = elapsed.div_ceil(resolution) as u64;
+        let tick = tick.max(self.current);
+        let slot = (tick % self.slots.len() as u64) as usize;
+        self.slots[slot].push((tick, timer));
+        self.deadlines.push(Reverse(tick));
+    }
+
+    /// Removes and returns every timer due at `now`.
+    pub(crate) fn expire(&mut self, now: Instant) -> Vec<T> {
+        let target = self.tick_of(now);
+        if target < self.current {
+            return Vec::new();
+        }
+        let mut expired = Vec::new();
+        let visits = (target - self.current + 1).min(self.slots.len() as u64);
+        for tick in self.current..self.current + visits {
+            let slot = (tick % self.slots.len() as u64) as usize;
+            let mut pending = Vec::new();
+            for (due, timer) in self.slots[slot].drain(..) {
+                if due <= target {
+                    expired.push(timer);
+                } else {
+                    pending.push((due, timer));
+                }
+            }
+            self.slots[slot] = pending;
+        }
+        // every timer due by `target` was in one of the visited slots
+        while self.deadlines.peek().is_some_and(|Reverse(due)| *due <= target) {
+            self.deadlines.pop();
+        }
+        self.current = target + 1;
+        expired
+    }
+
+    /// The tick at which the next timer fires, `None` while the wheel is empty.
+    pub(crate) fn next_deadline(&self) -> Option<Instant> {
+        let Reverse(due) = self.deadlines.peek()?;
+        let nanos = self.resolution.as_nanos().saturating_mul(u128::from(*due));
+        Some(self.start + Duration::from_nanos(u64::try_from(nanos).unwrap_or(u64::MAX)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::TimerWheel;
+
+    #[test]
+    fn expires_timers_in_order_across_rotations() {
+        let resolution = Duration::from_millis(10);
+        let mut wheel = TimerWheel::new(resolution, 4);
+        let start = Instant::now();
+        wheel.schedule(start + Duration::from_millis(15), "soon");
+        wheel.schedule(start + Duration::from_millis(95), "later");
+        wheel.schedule(start, "now");
+
+        assert_eq!(wheel.expire(start + Duration::from_millis(11)), vec!["now"]);
+        assert_eq!(wheel.expire(start + Duration::from_millis(19)), Vec::<&str>::new());
+        assert_eq!(wheel.expire(start + Duration::from_millis(25)), vec!["soon"]);
+        let next = wheel.next_deadline().unwrap();
+        assert!(next >= start + Duration::from_millis(95));
+        assert_eq!(wheel.expire(start + Duration::from_millis(60)), Vec::<&str>::new());
+        assert_eq!(wheel.expire(start + Duration::from_millis(150)), vec!["later"]);
+        assert_eq!(wheel.next_deadline(), None);
+    }
+
+    #[test]
+    fn next_deadline_beyond_u32_ticks() {
+        let mut wheel = TimerWheel::new(Duration::from_nanos(1), 8);
+        let deadline = wheel.start + Duration::from_secs(5);
+        wheel.schedule(deadline, ());
+        assert_eq!(wheel.next_deadline(), Some(deadline));
+    }
+}
@@ -0,0 +1,74 @@
+//! TLS backends, `native-tls` or `rustls` depending on the enabled feature.
+//!
+//! Both backends expose the same items: [`TlsAcceptor`] and [`TlsConnector`] built from a
+//! [`TlsConfig`], a [`TlsStream`] and the [`MidHandshake`] of a non-blocking socket. Without
+//! either feature only plain TCP connections are available.
+#![cfg_attr(not(any(feature = "native-tls", feature = "rustls")), allow(dead_code))]
+#[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+mod none;
+#[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+pub(crate) use none::*;
+
+#[cfg(all(feature = "native-tls", not(feature = "rustls")))]
+mod native;
+#[cfg(all(feature = "native-tls", not(feature = "rustls")))]
+pub(crate) use native::*;
+#[cfg(feature = "rustls")]
+mod rustls;
+#[cfg(feature = "rustls")]
+pub(crate) use self::rustls::*;
+#[cfg(feature = "tokio")]
+mod tokio_stream;
+#[cfg(feature = "tokio")]
+pub(crate) use tokio_stream::{accept_async, connect_async};
+
+use std::{fs::File, io::Read};
+
+use super::ConnectionError;
+
+/// The `SSL*` settings a [`TlsAcceptor`] or [`TlsConnector`] is built from.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TlsConfig {
+    /// `SSLCertificate`, the certificate chain and private key of this side.
+    pub(crate) certificate: Option<String>,
+    pub(crate) certificate_password: Option<String>,
+    /// `SSLCACertificate`, trusted to sign the certificate of the counterparty. Ignored by
+    /// native-tls acceptors, which don't verify client certificates.
+    pub(crate) ca_certificate: Option<String>,
+    /// `SSLRequireClientCertificate`, rustls only: native-tls acceptors ignore it with a warning.
+    pub(crate) require_client_certificate: bool,
+    pub(crate) min_protocol: Option<String>,
+    pub(crate) max_protocol: Option<String>,
+    pub(crate) use_sni: bool,
+    pub(crate) accept_invalid_certs: bool,
+    pub(crate) accept_invalid_hostnames: bool,
+    pub(crate) disable_built_in_roots: bool,
+}
+
+/// A TLS handshake in progress, see [`MidHandshake::handshake`].
+pub(crate) enum Handshake<S> {
+    Established(TlsStream<S>),
+    Pending(MidHandshake<S>),
+}
+
+/// Runs the handshake of a blocking socket to the end.
+pub(crate) fn establish<S>(
+    mut handshake: Handshake<S>,
+) -> Result<TlsStream<S>, ConnectionError>
+where S: std::io::Read + std::io::Write,
+{
+    loop {
+        match handshake {
+            Handshake::Established(stream) => return Ok(stream),
+            Handshake::Pending(stream) => handshake = stream.handshake()?,
+        }
+    }
+}
+
+fn read_file(path: &str) -> Result<Vec<u8>, String> {
+    let mut buf = vec![];
+    File::open(path)
+        .and_then(|mut file| file.read_to_end(&mut buf))
+        .map_err(|e| format!("Failed to read {path}: {e}"))?;
+    Ok(buf)
+}
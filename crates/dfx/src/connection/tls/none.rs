@@ -0,0 +1,94 @@
+//! Neither TLS backend is enabled: connections are plain TCP and sessions with `SSLEnable=Y`
+//! fail to load. The types can't be constructed.
+use std::convert::Infallible;
+use std::io::{self, Read, Write};
+use std::marker::PhantomData;
+
+use crate::connection::ConnectionError;
+use crate::session::PeerCertificate;
+
+use super::{Handshake, TlsConfig};
+
+const NO_BACKEND: &str = "SSLEnable requires the native-tls or the rustls feature";
+
+#[derive(Clone, Debug)]
+pub(crate) enum TlsAcceptor {}
+
+#[derive(Clone, Debug)]
+pub(crate) enum TlsConnector {}
+
+pub(crate) struct TlsStream<S>(Infallible, PhantomData<S>);
+
+impl<S> TlsStream<S> {
+    pub(crate) fn get_ref(&self) -> &S {
+        match self.0 {}
+    }
+    pub(crate) fn get_mut(&mut self) -> &mut S {
+        match self.0 {}
+    }
+    pub(crate) fn shutdown(&mut self) -> io::Result<()> {
+        match self.0 {}
+    }
+}
+
+impl<S> Read for TlsStream<S> {
+    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+        match self.0 {}
+    }
+}
+
+impl<S> Write for TlsStream<S> {
+    fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+        match self.0 {}
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.0 {}
+    }
+}
+
+pub(crate) struct MidHandshake<S>(Infallible, PhantomData<S>);
+
+impl<S> MidHandshake<S> {
+    #[cfg(any(feature = "mio", feature = "tokio"))]
+    pub(crate) fn get_ref(&self) -> &S {
+        match self.0 {}
+    }
+    #[cfg(any(feature = "mio", feature = "tokio"))]
+    pub(crate) fn get_mut(&mut self) -> &mut S {
+        match self.0 {}
+    }
+    pub(crate) fn handshake(self) -> Result<Handshake<S>, ConnectionError> {
+        match self.0 {}
+    }
+}
+
+pub(crate) fn acceptor(_config: &TlsConfig) -> Result<TlsAcceptor, String> {
+    Err(NO_BACKEND.into())
+}
+
+pub(crate) fn connector(_config: &TlsConfig) -> Result<TlsConnector, String> {
+    Err(NO_BACKEND.into())
+}
+
+pub(crate) fn accept<S>(acceptor: &TlsAcceptor, _stream: S) -> Result<Handshake<S>, ConnectionError> {
+    match *acceptor {}
+}
+
+pub(crate) fn connect<S>(
+    connector: &TlsConnector,
+    _domain: &str,
+    _stream: S,
+) -> Result<Handshake<S>, ConnectionError> {
+    match *connector {}
+}
+
+pub(crate) fn peer_certificate<S>(stream: &TlsStream<S>) -> Option<PeerCertificate> {
+    match stream.0 {}
+}
+
+/// Whether TLS records are waiting for the socket to become writable.
+#[cfg(any(feature = "mio", feature = "tokio"))]
+pub(crate) fn wants_write<S>(stream: &TlsStream<S>) -> bool {
+    match stream.0 {}
+}
@@ -0,0 +1,353 @@
+use std::io::{self, ErrorKind, Read, Write};
+use std::sync::Arc;
+
+use ::rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use ::rustls::client::WebPkiServerVerifier;
+use ::rustls::crypto::{verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+use ::rustls::pki_types::pem::PemObject;
+use ::rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use ::rustls::server::WebPkiClientVerifier;
+use ::rustls::version::{TLS12, TLS13};
+use ::rustls::{
+    CertificateError, ClientConfig, ClientConnection, Connection, DigitallySignedStruct, Error,
+    RootCertStore, ServerConfig, ServerConnection, SignatureScheme, SupportedProtocolVersion,
+};
+
+use crate::connection::ConnectionError;
+use crate::session::PeerCertificate;
+
+use super::{read_file, Handshake, TlsConfig};
+
+pub(crate) type TlsAcceptor = Arc<ServerConfig>;
+pub(crate) type TlsConnector = Arc<ClientConfig>;
+
+/// A rustls connection over a blocking or non-blocking socket.
+pub(crate) struct TlsStream<S> {
+    connection: Connection,
+    stream: S,
+}
+
+impl<S: Read + Write> TlsStream<S> {
+    pub(crate) fn get_ref(&self) -> &S {
+        &self.stream
+    }
+    pub(crate) fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+    pub(crate) fn shutdown(&mut self) -> io::Result<()> {
+        self.connection.send_close_notify();
+        self.write_tls()
+    }
+
+    /// Writes the pending TLS records, those the socket doesn't take stay queued.
+    fn write_tls(&mut self) -> io::Result<()> {
+        while self.connection.wants_write() {
+            match self.connection.write_tls(&mut self.stream) {
+                Ok(_) => {}
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<S: Read + Write> Read for TlsStream<S> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            match self.connection.reader().read(buf) {
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                read => return read,
+            }
+            let read = self.connection.read_tls(&mut self.stream)?;
+            self.connection
+                .process_new_packets()
+                .map_err(|e| io::Error::new(ErrorKind::InvalidData, e))?;
+            // e.g. key updates answered by the peer
+            self.write_tls()?;
+            if read == 0 {
+                // Ok(0) after a close_notify, UnexpectedEof otherwise
+                return self.connection.reader().read(buf);
+            }
+        }
+    }
+}
+
+impl<S: Read + Write> Write for TlsStream<S> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.connection.writer().write(buf)?;
+        self.write_tls()?;
+        if written == 0 && !buf.is_empty() {
+            // the buffer is full until the socket takes the pending records
+            return Err(ErrorKind::WouldBlock.into());
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_tls()?;
+        self.stream.flush()
+    }
+}
+
+pub(crate) struct MidHandshake<S>(TlsStream<S>);
+
+impl<S: Read + Write> MidHandshake<S> {
+    #[cfg(any(feature = "mio", feature = "tokio"))]
+    pub(crate) fn get_ref(&self) -> &S {
+        self.0.get_ref()
+    }
+    #[cfg(any(feature = "mio", feature = "tokio"))]
+    pub(crate) fn get_mut(&mut self) -> &mut S {
+        self.0.get_mut()
+    }
+    pub(crate) fn handshake(mut self) -> Result<Handshake<S>, ConnectionError> {
+        let stream = &mut self.0;
+        while stream.connection.is_handshaking() {
+            match stream.connection.complete_io(&mut stream.stream) {
+                Ok(_) => {}
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return Ok(Handshake::Pending(self)),
+                Err(e) => return Err(ConnectionError::HandshakeError(e.to_string())),
+            }
+        }
+        stream.write_tls()?;
+        Ok(Handshake::Established(self.0))
+    }
+}
+
+pub(crate) fn acceptor(config: &TlsConfig) -> Result<TlsAcceptor, String> {
+    let path = config
+        .certificate
+        .as_deref()
+        .ok_or("SSLCertificate must be set for acceptors")?;
+    let (certificates, key) = identity(path, config.certificate_password.as_deref())?;
+    let provider = provider();
+    let builder = ServerConfig::builder_with_provider(provider.clone())
+        .with_protocol_versions(&protocol_versions(config)?)
+        .map_err(|e| e.to_string())?;
+    let builder = match config.ca_certificate.as_deref() {
+        Some(ca_certificate) => {
+            let roots = Arc::new(root_store(ca_certificate)?);
+            let verifier = WebPkiClientVerifier::builder_with_provider(roots, provider);
+            let verifier = match config.require_client_certificate {
+                true => verifier,
+                false => verifier.allow_unauthenticated(),
+            };
+            builder.with_client_cert_verifier(verifier.build().map_err(|e| e.to_string())?)
+        }
+        None if config.require_client_certificate => {
+            return Err("SSLCACertificate must be set when SSLRequireClientCertificate=Y".into())
+        }
+        None => builder.with_no_client_auth(),
+    };
+    let server_config = builder
+        .with_single_cert(certificates, key)
+        .map_err(|e| format!("Invalid SSLCertificate {path}: {e}"))?;
+    Ok(Arc::new(server_config))
+}
+
+pub(crate) fn connector(config: &TlsConfig) -> Result<TlsConnector, String> {
+    let provider = provider();
+    let builder = ClientConfig::builder_with_provider(provider.clone())
+        .with_protocol_versions(&protocol_versions(config)?)
+        .map_err(|e| e.to_string())?;
+    let mut roots = RootCertStore::empty();
+    if !config.disable_built_in_roots {
+        roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+    }
+    if let Some(ca_certificate) = config.ca_certificate.as_deref() {
+        roots.roots.extend(root_store(ca_certificate)?.roots);
+    }
+    let builder = if config.accept_invalid_certs || config.accept_invalid_hostnames {
+        let webpki = match config.accept_invalid_certs {
+            true => None,
+            false => Some(
+                WebPkiServerVerifier::builder_with_provider(Arc::new(roots), provider.clone())
+                    .build()
+                    .map_err(|e| e.to_string())?,
+            ),
+        };
+        builder
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(DangerousVerifier { webpki, provider }))
+    } else {
+        builder.with_root_certificates(roots)
+    };
+    let mut client_config = match config.certificate.as_deref() {
+        Some(path) => {
+            let (certificates, key) = identity(path, config.certificate_password.as_deref())?;
+            builder
+                .with_client_auth_cert(certificates, key)
+                .map_err(|e| format!("Invalid SSLCertificate {path}: {e}"))?
+        }
+        None => builder.with_no_client_auth(),
+    };
+    client_config.enable_sni = config.use_sni;
+    Ok(Arc::new(client_config))
+}
+
+fn provider() -> Arc<CryptoProvider> {
+    Arc::new(::rustls::crypto::ring::default_provider())
+}
+
+/// The certificate chain and unencrypted private key of a PEM file.
+fn identity(
+    path: &str,
+    password: Option<&str>,
+) -> Result<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>), String> {
+    if password.is_some() {
+        return Err("SSLCertificatePassword is only supported with the native-tls feature".into());
+    }
+    let buf = read_file(path)?;
+    let certificates = CertificateDer::pem_slice_iter(&buf)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Invalid SSLCertificate {path}: {e}"))?;
+    if certificates.is_empty() {
+        return Err(format!("No certificate in SSLCertificate {path}"));
+    }
+    let key = PrivateKeyDer::from_pem_slice(&buf)
+        .map_err(|e| format!("No private key in SSLCertificate {path}: {e}"))?;
+    Ok((certificates, key))
+}
+
+fn root_store(path: &str) -> Result<RootCertStore, String> {
+    let buf = read_file(path)?;
+    let mut roots = RootCertStore::empty();
+    for certificate in CertificateDer::pem_slice_iter(&buf) {
+        let certificate = certificate.map_err(|e| format!("Invalid SSLCACertificate {path}: {e}"))?;
+        roots
+            .add(certificate)
+            .map_err(|e| format!("Invalid SSLCACertificate {path}: {e}"))?;
+    }
+    Ok(roots)
+}
+
+/// rustls speaks TLSv1.2 and TLSv1.3 only.
+fn protocol_versions(config: &TlsConfig) -> Result<Vec<&'static SupportedProtocolVersion>, String> {
+    let min = config.min_protocol.as_deref().map(protocol).transpose()?.unwrap_or(0);
+    let max = config.max_protocol.as_deref().map(protocol).transpose()?.unwrap_or(u8::MAX);
+    let versions: Vec<_> = [(3, &TLS12), (4, &TLS13)]
+        .into_iter()
+        .filter(|(version, _)| (min..=max).contains(version))
+        .map(|(_, version)| version)
+        .collect();
+    if versions.is_empty() {
+        return Err("SSLMinProtocol and SSLMaxProtocol exclude both TLSv1.2 and TLSv1.3".into());
+    }
+    Ok(versions)
+}
+
+fn protocol(protocol: &str) -> Result<u8, String> {
+    match protocol {
+        "SSLv3" => Ok(0),
+        "TLSv1.0" => Ok(1),
+        "TLSv1.1" => Ok(2),
+        "TLSv1.2" => Ok(3),
+        "TLSv1.3" => Ok(4),
+        _ => Err(format!("Unknown SSL protocol {protocol}")),
+    }
+}
+
+/// Verifies the server certificate as far as `SSLAcceptInvalidCerts` and
+/// `SSLAcceptInvalidHostnames` ask for, `webpki` is `None` when no certificate is checked.
+#[derive(Debug)]
+struct DangerousVerifier {
+    webpki: Option<Arc<WebPkiServerVerifier>>,
+    provider: Arc<CryptoProvider>,
+}
+
+impl ServerCertVerifier for DangerousVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, Error> {
+        let webpki = match self.webpki.as_ref() {
+            Some(webpki) => webpki,
+            None => return Ok(ServerCertVerified::assertion()),
+        };
+        match webpki.verify_server_cert(end_entity, intermediates, server_name, ocsp_response, now) {
+            Err(Error::InvalidCertificate(
+                CertificateError::NotValidForName | CertificateError::NotValidForNameContext { .. },
+            )) => Ok(ServerCertVerified::assertion()),
+            verified => verified,
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, Error> {
+        verify_tls12_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, Error> {
+        verify_tls13_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+pub(crate) fn accept<S: Read + Write>(
+    acceptor: &TlsAcceptor,
+    stream: S,
+) -> Result<Handshake<S>, ConnectionError> {
+    let connection = ServerConnection::new(acceptor.clone()).map_err(handshake_error)?;
+    MidHandshake(TlsStream {
+        connection: connection.into(),
+        stream,
+    })
+    .handshake()
+}
+
+pub(crate) fn connect<S: Read + Write>(
+    connector: &TlsConnector,
+    domain: &str,
+    stream: S,
+) -> Result<Handshake<S>, ConnectionError> {
+    let connection =
+        ClientConnection::new(connector.clone(), server_name(domain)?).map_err(handshake_error)?;
+    MidHandshake(TlsStream {
+        connection: connection.into(),
+        stream,
+    })
+    .handshake()
+}
+
+fn server_name(domain: &str) -> Result<ServerName<'static>, ConnectionError> {
+    ServerName::try_from(domain.to_owned()).map_err(|e| {
+        ConnectionError::HandshakeError(format!("Invalid server name {domain}: {e}"))
+    })
+}
+
+fn handshake_error(e: Error) -> ConnectionError {
+    ConnectionError::HandshakeError(e.to_string())
+}
+
+fn end_entity(certificates: Option<&[CertificateDer<'static>]>) -> Option<PeerCertificate> {
+    certificates
+        .and_then(|certificates| certificates.first())
+        .map(|certificate| PeerCertificate::from_der(certificate.to_vec()))
+}
+
+pub(crate) fn peer_certificate<S>(stream: &TlsStream<S>) -> Option<PeerCertificate> {
+    end_entity(stream.connection.peer_certificates())
+}
+
+/// Whether TLS records are waiting for the socket to become writable.
+#[cfg(any(feature = "mio", feature = "tokio"))]
+pub(crate) fn wants_write<S>(stream: &TlsStream<S>) -> bool {
+    stream.connection.wants_write()
+}
@@ -0,0 +1,202 @@
+//! TLS on the tokio transport.
+//!
+//! The backend's stream runs over the non-blocking tokio socket like on the mio transport: a
+//! TLS call that would block waits for the socket to become ready in the direction it blocked.
+use std::io::{self, ErrorKind, Read, Write};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use ::tokio::io::{AsyncRead, AsyncWrite, Interest, ReadBuf};
+use ::tokio::net::TcpStream;
+
+use crate::connection::{AsyncStream, ConnectionError};
+use crate::session::PeerCertificate;
+
+use super::{Handshake, TlsAcceptor, TlsConnector, TlsStream};
+
+/// A tokio socket read and written without blocking, remembering which direction blocked.
+pub(crate) struct TokioSocket {
+    stream: TcpStream,
+    read_blocked: bool,
+    write_blocked: bool,
+}
+
+impl TokioSocket {
+    fn new(stream: TcpStream) -> Self {
+        TokioSocket {
+            stream,
+            read_blocked: false,
+            write_blocked: false,
+        }
+    }
+
+    fn clear(&mut self) {
+        self.read_blocked = false;
+        self.write_blocked = false;
+    }
+
+    /// What the TLS call that last failed with `WouldBlock` waits for, `None` if the socket
+    /// didn't block.
+    fn interest(&self) -> Option<Interest> {
+        match (self.read_blocked, self.write_blocked) {
+            (true, true) => Some(Interest::READABLE | Interest::WRITABLE),
+            (true, false) => Some(Interest::READABLE),
+            (false, true) => Some(Interest::WRITABLE),
+            (false, false) => None,
+        }
+    }
+
+    /// Registers `cx` for the directions that blocked, `Ready` once one of them is ready again.
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let mut ready = false;
+        if self.read_blocked {
+            if let Poll::Ready(result) = self.stream.poll_read_ready(cx) {
+                result?;
+                ready = true;
+            }
+        }
+        if self.write_blocked {
+            if let Poll::Ready(result) = self.stream.poll_write_ready(cx) {
+                result?;
+                ready = true;
+            }
+        }
+        match ready {
+            true => Poll::Ready(Ok(())),
+            false => Poll::Pending,
+        }
+    }
+}
+
+impl Read for TokioSocket {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.stream.try_read(buf);
+        self.read_blocked |= matches!(&read, Err(e) if e.kind() == ErrorKind::WouldBlock);
+        read
+    }
+}
+
+impl Write for TokioSocket {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.stream.try_write(buf);
+        self.write_blocked |= matches!(&written, Err(e) if e.kind() == ErrorKind::WouldBlock);
+        written
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The established TLS stream of a tokio connection.
+struct AsyncTlsStream(TlsStream<TokioSocket>);
+
+impl AsyncTlsStream {
+    /// Runs `op` until it no longer fails with `WouldBlock`, `Pending` while the socket isn't
+    /// ready for it.
+    fn poll_tls<T>(
+        &mut self,
+        cx: &mut Context<'_>,
+        mut op: impl FnMut(&mut TlsStream<TokioSocket>) -> io::Result<T>,
+    ) -> Poll<io::Result<T>> {
+        loop {
+            self.0.get_mut().clear();
+            match op(&mut self.0) {
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                    let socket = self.0.get_ref();
+                    if socket.interest().is_none() {
+                        return Poll::Ready(Err(e));
+                    }
+                    match socket.poll_ready(cx) {
+                        Poll::Ready(Ok(())) => {}
+                        Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                        Poll::Pending => return Poll::Pending,
+                    }
+                }
+                result => return Poll::Ready(result),
+            }
+        }
+    }
+}
+
+impl AsyncRead for AsyncTlsStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let read = match self.get_mut().poll_tls(cx, |stream| stream.read(buf.initialize_unfilled())) {
+            Poll::Ready(read) => read?,
+            Poll::Pending => return Poll::Pending,
+        };
+        buf.advance(read);
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for AsyncTlsStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.get_mut().poll_tls(cx, |stream| stream.write(buf))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().poll_tls(cx, |stream| {
+            stream.flush()?;
+            match super::wants_write(stream) {
+                // records the socket didn't take yet
+                true => Err(ErrorKind::WouldBlock.into()),
+                false => Ok(()),
+            }
+        })
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        match this.poll_tls(cx, |stream| stream.shutdown()) {
+            Poll::Ready(result) => result?,
+            Poll::Pending => return Poll::Pending,
+        }
+        Pin::new(&mut this.0.get_mut().stream).poll_shutdown(cx)
+    }
+}
+
+/// Runs the handshake on the tokio socket, waiting for it whenever the handshake would block.
+async fn establish(mut handshake: Handshake<TokioSocket>) -> Result<TlsStream<TokioSocket>, ConnectionError> {
+    loop {
+        match handshake {
+            Handshake::Established(stream) => return Ok(stream),
+            Handshake::Pending(mut stream) => {
+                let socket = stream.get_ref();
+                let interest = socket.interest().ok_or_else(|| {
+                    ConnectionError::HandshakeError("TLS handshake blocked without waiting for the socket".into())
+                })?;
+                socket.stream.ready(interest).await?;
+                stream.get_mut().clear();
+                handshake = stream.handshake()?;
+            }
+        }
+    }
+}
+
+pub(crate) async fn accept_async(
+    acceptor: &TlsAcceptor,
+    stream: TcpStream,
+) -> Result<(Box<dyn AsyncStream>, Option<PeerCertificate>), ConnectionError> {
+    let stream = establish(super::accept(acceptor, TokioSocket::new(stream))?).await?;
+    let certificate = super::peer_certificate(&stream);
+    Ok((Box::new(AsyncTlsStream(stream)), certificate))
+}
+
+pub(crate) async fn connect_async(
+    connector: &TlsConnector,
+    domain: &str,
+    stream: TcpStream,
+) -> Result<(Box<dyn AsyncStream>, Option<PeerCertificate>), ConnectionError> {
+    let stream = establish(super::connect(connector, domain, TokioSocket::new(stream))?).await?;
+    let certificate = super::peer_certificate(&stream);
+    Ok((Box::new(AsyncTlsStream(stream)), certificate))
+}
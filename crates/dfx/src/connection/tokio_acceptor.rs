@@ -0,0 +1,251 @@
+use std::{net::SocketAddr, sync::Arc};
+
+use dfx_base::data_dictionary_provider::DataDictionaryProvider;
+use dfx_base::message_factory::MessageFactory;
+use tokio::{
+    net::TcpListener,
+    sync::watch,
+    task::{JoinHandle, JoinSet},
+};
+
+use crate::{
+    logging::{LogFactory, Logger},
+    message_store::MessageStoreFactory,
+    session::{
+        Application, CredentialStore, LogonAuthenticator, ResendPolicy, SessionEvents,
+        SessionSetting, SessionSettings, ValidationRules,
+    },
+};
+
+use super::{
+    summarize, AsyncConnection, ConnectionObserver, SessionHooks, SessionShutdown, TokioReactor, SHUTDOWN_REASON,
+};
+
+/// # Async Socket Acceptor
+/// Listens and runs every connection as a task on the current tokio runtime,
+/// available with the `tokio` feature.
+///
+/// Sessions are driven by socket readiness, the session timer and queued messages,
+/// no thread is created or blocked per connection. The application callbacks run on the
+/// runtime's worker threads and must not block: calls waiting for a session, like
+/// [`Session::send_to_session_sync`](crate::session::Session::send_to_session_sync), fail
+/// there with [`SessionError::WouldBlock`](crate::session::SessionError::WouldBlock).
+/// ## Example
+/// ```no_run
+/// # use dfx::{connection::TokioSocketAcceptor, session::SessionSettings};
+/// # use dfx::{data_dictionary_provider::DefaultDataDictionaryProvider, logging::PrintlnLogFactory};
+/// # use dfx::{message::DefaultMessageFactory, message_store::MemoryStoreFactory};
+/// # async fn run<App>(session_settings: SessionSettings, app: App) -> std::io::Result<()>
+/// # where App: dfx::session::Application + Sync + Clone + 'static {
+/// let mut acceptor = TokioSocketAcceptor::new(
+///     &session_settings,
+///     app,
+///     MemoryStoreFactory::new(),
+///     DefaultDataDictionaryProvider::new(),
+///     PrintlnLogFactory::new(),
+///     DefaultMessageFactory::new(),
+/// );
+/// acceptor.start().await?;
+/// // ...
+/// acceptor.stop().await;
+/// # Ok(())
+/// # }
+/// ```
+pub struct TokioSocketAcceptor<App, StoreFactory, DataDictionaryProvider, LogFactory, MessageFactory> {
+    app: App,
+    store_factory: StoreFactory,
+    data_dictionary_provider: DataDictionaryProvider,
+    log_factory: LogFactory,
+    message_factory: MessageFactory,
+    hooks: SessionHooks,
+    session_settings: SessionSettings,
+    endpoints: Vec<SocketAddr>,
+    tasks: Vec<JoinHandle<Vec<SessionShutdown>>>,
+    shutdown: watch::Sender<Option<String>>,
+}
+
+impl<App, SF, DDP, LF, MF, Log> TokioSocketAcceptor<App, SF, DDP, LF, MF>
+where App: Application + Sync + Clone + 'static,
+      SF: MessageStoreFactory + Send + Clone + 'static,
+      DDP: DataDictionaryProvider + Send + Clone + 'static,
+      LF: LogFactory<Log = Log> + Send + Clone + 'static,
+      MF: MessageFactory + Send + Clone + 'static,
+      Log: Logger + Clone + 'static,
+{
+    pub fn new(session_settings: &SessionSettings, app: App, store_factory: SF, data_dictionary_provider: DDP, log_factory: LF, message_factory: MF) -> Self {
+        TokioSocketAcceptor {
+            app,
+            store_factory,
+            data_dictionary_provider,
+            log_factory,
+            message_factory,
+            hooks: SessionHooks::default(),
+            session_settings: session_settings.clone(),
+            endpoints: Vec::new(),
+            tasks: Vec::new(),
+            shutdown: watch::channel(None).0,
+        }
+    }
+
+    /// Custom [`ValidationRules`] applied by every session after data dictionary validation.
+    pub fn with_validation_rules(mut self, validation_rules: ValidationRules) -> Self {
+        self.hooks.validation_rules = validation_rules;
+        self
+    }
+
+    /// [`SessionEvents`] subscribers notified of the lifecycle events of every session.
+    pub fn with_session_events(mut self, events: SessionEvents) -> Self {
+        self.hooks.events = events;
+        self
+    }
+
+    /// [`ResendPolicy`] deciding which stored messages are resent and which are gap filled.
+    pub fn with_resend_policy(mut self, resend_policy: ResendPolicy) -> Self {
+        self.hooks.resend_policy = resend_policy;
+        self
+    }
+
+    /// [`ConnectionObserver`] notified when connections are opened, fail or are closed.
+    pub fn with_connection_observer<O: ConnectionObserver + 'static>(mut self, observer: O) -> Self {
+        self.hooks.observer = Some(Arc::new(observer));
+        self
+    }
+
+    /// [`LogonAuthenticator`] deciding whether incoming Logons are accepted.
+    pub fn with_logon_authenticator<A: LogonAuthenticator + 'static>(mut self, authenticator: A) -> Self {
+        self.hooks.authenticator = Some(Arc::new(authenticator));
+        self
+    }
+
+    /// [`CredentialStore`] checking Username/Password on Logon, applying NewPassword and
+    /// answering UserRequest messages.
+    pub fn with_credential_store<C: CredentialStore + 'static>(mut self, credential_store: Arc<C>) -> Self {
+        self.hooks.credential_store = Some(credential_store);
+        self
+    }
+
+    /// Binds every socket address and starts accepting connections on the current runtime.
+    pub async fn start(&mut self) -> std::io::Result<&mut Self> {
+        self.shutdown.send_replace(None);
+        for (addr, session_settings) in self.session_settings.sessions_by_address() {
+            let listener = TcpListener::bind(addr).await?;
+            self.endpoints.push(listener.local_addr()?);
+            let listen = Listener {
+                app: self.app.clone(),
+                store_factory: self.store_factory.clone(),
+                data_dictionary_provider: self.data_dictionary_provider.clone(),
+                log_factory: self.log_factory.clone(),
+                message_factory: self.message_factory.clone(),
+                hooks: self.hooks.clone(),
+                session_settings,
+            };
+            let task = tokio::spawn(listen.accept_loop(listener, self.shutdown.subscribe()));
+            self.tasks.push(task);
+        }
+        Ok(self)
+    }
+
+    /// Waits for all listeners and their connections to finish.
+    pub async fn join(&mut self) {
+        self.join_tasks().await;
+    }
+
+    async fn join_tasks(&mut self) -> Vec<SessionShutdown> {
+        let mut shutdowns = Vec::new();
+        for task in self.tasks.drain(..) {
+            shutdowns.extend(task.await.unwrap_or_default());
+        }
+        shutdowns
+    }
+
+    /// List bound endpoints, useful for random port allocation.
+    pub fn endpoints(&self) -> Vec<SocketAddr> {
+        self.endpoints.clone()
+    }
+
+    /// Stops listening, see [`TokioSocketAcceptor::shutdown`].
+    pub async fn stop(&mut self) -> Vec<SessionShutdown> {
+        self.shutdown(SHUTDOWN_REASON).await
+    }
+
+    /// Stops listening and sends a Logout with `reason` to every logged on session.
+    /// Connections are closed once the Logout is answered or `LogoutTimeout` passed, stores and
+    /// loggers are flushed and all tasks awaited.
+    pub async fn shutdown(&mut self, reason: &str) -> Vec<SessionShutdown> {
+        self.shutdown.send_replace(Some(reason.into()));
+        let shutdowns = self.join_tasks().await;
+        self.endpoints.clear();
+        summarize(&self.session_settings, shutdowns)
+    }
+}
+
+struct Listener<App, StoreFactory, DataDictionaryProvider, LogFactory, MessageFactory> {
+    app: App,
+    store_factory: StoreFactory,
+    data_dictionary_provider: DataDictionaryProvider,
+    log_factory: LogFactory,
+    message_factory: MessageFactory,
+    hooks: SessionHooks,
+    session_settings: Vec<SessionSetting>,
+}
+
+impl<App, SF, DDP, LF, MF, Log> Listener<App, SF, DDP, LF, MF>
+where App: Application + Sync + Clone + 'static,
+      SF: MessageStoreFactory + Send + Clone + 'static,
+      DDP: DataDictionaryProvider + Send + Clone + 'static,
+      LF: LogFactory<Log = Log> + Send + Clone + 'static,
+      MF: MessageFactory + Send + Clone + 'static,
+      Log: Logger + Clone + 'static,
+{
+    async fn accept_loop(
+        self,
+        listener: TcpListener,
+        mut shutdown: watch::Receiver<Option<String>>,
+    ) -> Vec<SessionShutdown> {
+        let mut connections = JoinSet::new();
+        loop {
+            tokio::select! {
+                accepted = listener.accept() => match accepted {
+                    Ok((stream, addr)) => {
+                        let socket_settings = self.session_settings[0].socket_settings();
+                        let reactor = TokioReactor::new(
+                            self.session_settings.clone(),
+                            self.app.clone(),
+                            self.store_factory.clone(),
+                            self.data_dictionary_provider.clone(),
+                            self.log_factory.clone(),
+                            self.message_factory.clone(),
+                            self.hooks.clone(),
+                        );
+                        let shutdown = shutdown.clone();
+                        let hooks = self.hooks.clone();
+                        connections.spawn(async move {
+                            match AsyncConnection::configure(stream, &socket_settings).await {
+                                Ok(connection) => reactor.run(connection, shutdown).await,
+                                Err(e) => {
+                                    if let Some(observer) = hooks.observer() {
+                                        observer.on_connect_failed(None, addr, &e);
+                                    }
+                                    None
+                                }
+                            }
+                        });
+                    }
+                    Err(e) => {
+                        if let (Some(observer), Ok(endpoint)) = (self.hooks.observer(), listener.local_addr()) {
+                            observer.on_accept_failed(endpoint, &e.into());
+                        }
+                    }
+                },
+                Some(_) = connections.join_next(), if !connections.is_empty() => {}
+                _ = shutdown.changed() => break,
+            }
+        }
+        // the connections log out on their own once the shutdown reason is set
+        let mut shutdowns = Vec::new();
+        while let Some(joined) = connections.join_next().await {
+            shutdowns.extend(joined.ok().flatten());
+        }
+        shutdowns
+    }
+}
@@ -0,0 +1,226 @@
+use std::sync::Arc;
+
+use chrono::Utc;
+use dfx_base::data_dictionary_provider::DataDictionaryProvider;
+use dfx_base::message_factory::MessageFactory;
+use tokio::{sync::watch, task::JoinHandle};
+
+use crate::{
+    logging::{LogFactory, Logger},
+    message_store::MessageStoreFactory,
+    session::{
+        is_session_enabled, Application, Endpoint, ResendPolicy, SessionEvent, SessionEvents,
+        SessionSetting, SessionSettings, ValidationRules,
+    },
+};
+
+use super::{
+    AsyncConnection, ConnectionObserver, Failover, InitiatorError, LogoutOutcome, SessionHooks, SessionShutdown,
+    TokioReactor, SHUTDOWN_REASON,
+};
+
+/// # Async Socket Initiator
+/// Runs every session as a task on the current tokio runtime,
+/// available with the `tokio` feature. Application callbacks must not block, see
+/// [`TokioSocketAcceptor`](super::TokioSocketAcceptor).
+/// ## Usage
+/// See [`TokioSocketAcceptor`](super::TokioSocketAcceptor).
+pub struct TokioSocketInitiator<App, StoreFactory, DataDictionaryProvider, LogFactory, MessageFactory> {
+    app: App,
+    store_factory: StoreFactory,
+    data_dictionary_provider: DataDictionaryProvider,
+    log_factory: LogFactory,
+    message_factory: MessageFactory,
+    hooks: SessionHooks,
+    session_settings: SessionSettings,
+    tasks: Vec<JoinHandle<SessionShutdown>>,
+    shutdown: watch::Sender<Option<String>>,
+}
+
+impl<App, SF, DDP, LF, MF, Log> TokioSocketInitiator<App, SF, DDP, LF, MF>
+where
+    App: Application + Clone + 'static,
+    SF: MessageStoreFactory + Send + Clone + 'static,
+    DDP: DataDictionaryProvider + Send + Clone + 'static,
+    LF: LogFactory<Log = Log> + Send + Clone + 'static,
+    MF: MessageFactory + Send + Clone + 'static,
+    Log: Logger + Clone + 'static,
+{
+    pub fn new(
+        session_settings: SessionSettings,
+        app: App,
+        store_factory: SF,
+        data_dictionary_provider: DDP,
+        log_factory: LF,
+        message_factory: MF,
+    ) -> Self {
+        TokioSocketInitiator {
+            app,
+            store_factory,
+            data_dictionary_provider,
+            log_factory,
+            message_factory,
+            hooks: SessionHooks::default(),
+            session_settings,
+            tasks: Vec::new(),
+            shutdown: watch::channel(None).0,
+        }
+    }
+
+    /// Custom [`ValidationRules`] applied by every session after data dictionary validation.
+    pub fn with_validation_rules(mut self, validation_rules: ValidationRules) -> Self {
+        self.hooks.validation_rules = validation_rules;
+        self
+    }
+
+    /// [`SessionEvents`] subscribers notified of the lifecycle events of every session.
+    pub fn with_session_events(mut self, events: SessionEvents) -> Self {
+        self.hooks.events = events;
+        self
+    }
+
+    /// [`ResendPolicy`] deciding which stored messages are resent and which are gap filled.
+    pub fn with_resend_policy(mut self, resend_policy: ResendPolicy) -> Self {
+        self.hooks.resend_policy = resend_policy;
+        self
+    }
+
+    /// [`ConnectionObserver`] notified when connections are opened, fail or are closed.
+    pub fn with_connection_observer<O: ConnectionObserver + 'static>(mut self, observer: O) -> Self {
+        self.hooks.observer = Some(Arc::new(observer));
+        self
+    }
+
+    /// Spawns one task per session, must be called from within a tokio runtime.
+    pub fn start(&mut self) {
+        self.shutdown.send_replace(None);
+        for session_settings in self.session_settings.sessions() {
+            let session = InitiatorSession {
+                app: self.app.clone(),
+                store_factory: self.store_factory.clone(),
+                data_dictionary_provider: self.data_dictionary_provider.clone(),
+                log_factory: self.log_factory.clone(),
+                message_factory: self.message_factory.clone(),
+                hooks: self.hooks.clone(),
+                session_settings: session_settings.clone(),
+            };
+            let task = tokio::spawn(session.connect_loop(self.shutdown.subscribe()));
+            self.tasks.push(task);
+        }
+    }
+
+    /// Waits for all session tasks to finish.
+    pub async fn join(&mut self) {
+        self.join_tasks().await;
+    }
+
+    async fn join_tasks(&mut self) -> Vec<SessionShutdown> {
+        let mut shutdowns = Vec::new();
+        for task in self.tasks.drain(..) {
+            shutdowns.extend(task.await.ok());
+        }
+        shutdowns
+    }
+
+    /// Disconnects every session, see [`TokioSocketInitiator::shutdown`].
+    pub async fn stop(&mut self) -> Vec<SessionShutdown> {
+        self.shutdown(SHUTDOWN_REASON).await
+    }
+
+    /// Stops reconnecting and sends a Logout with `reason` to every logged on session.
+    /// Connections are closed once the Logout is answered or `LogoutTimeout` passed, stores and
+    /// loggers are flushed and all tasks awaited.
+    pub async fn shutdown(&mut self, reason: &str) -> Vec<SessionShutdown> {
+        self.shutdown.send_replace(Some(reason.into()));
+        self.join_tasks().await
+    }
+}
+
+struct InitiatorSession<App, StoreFactory, DataDictionaryProvider, LogFactory, MessageFactory> {
+    app: App,
+    store_factory: StoreFactory,
+    data_dictionary_provider: DataDictionaryProvider,
+    log_factory: LogFactory,
+    message_factory: MessageFactory,
+    hooks: SessionHooks,
+    session_settings: SessionSetting,
+}
+
+impl<App, SF, DDP, LF, MF, Log> InitiatorSession<App, SF, DDP, LF, MF>
+where
+    App: Application + Clone + 'static,
+    SF: MessageStoreFactory + Send + Clone + 'static,
+    DDP: DataDictionaryProvider + Send + Clone + 'static,
+    LF: LogFactory<Log = Log> + Send + Clone + 'static,
+    MF: MessageFactory + Send + Clone + 'static,
+    Log: Logger + Clone + 'static,
+{
+    async fn connect_loop(mut self, mut shutdown: watch::Receiver<Option<String>>) -> SessionShutdown {
+        let mut failover = Failover::new(&self.session_settings);
+        let session_id = self.session_settings.session_id().clone();
+        let log = self.log_factory.create(&session_id);
+        while shutdown.borrow().is_none() {
+            let timeout = if is_session_enabled(self.session_settings.session_id())
+                && self
+                    .session_settings
+                    .schedule()
+                    .is_session_time(&Utc::now())
+            {
+                match self.connect(failover.endpoint(), shutdown.clone()).await {
+                    Ok(Some(summary)) => return summary,
+                    Ok(None) => failover.disconnected(),
+                    Err(e) => match failover.log_failed(&log, &e) {
+                        Some(timeout) => timeout,
+                        None => break,
+                    },
+                }
+            } else {
+                failover.interval()
+            };
+            tokio::select! {
+                _ = tokio::time::sleep(timeout) => {}
+                _ = shutdown.changed() => break,
+            }
+        }
+        SessionShutdown::new(session_id, LogoutOutcome::Disconnected)
+    }
+
+    async fn connect(
+        &mut self,
+        endpoint: Endpoint,
+        shutdown: watch::Receiver<Option<String>>,
+    ) -> Result<Option<SessionShutdown>, InitiatorError> {
+        let address = endpoint.addr;
+        let socket_settings = self.session_settings.socket_settings().with_endpoint(endpoint);
+        let session_id = self.session_settings.session_id();
+        self.hooks
+            .events
+            .publish(session_id, SessionEvent::Connecting { address });
+        if let Some(observer) = self.hooks.observer() {
+            observer.on_connect_attempt(session_id, address);
+        }
+        let connection = match AsyncConnection::connect(&socket_settings).await {
+            Ok(connection) => connection,
+            Err(e) => {
+                if let Some(observer) = self.hooks.observer() {
+                    observer.on_connect_failed(Some(session_id), address, &e);
+                }
+                let reason = format!("{e}");
+                self.hooks
+                    .events
+                    .publish(session_id, SessionEvent::ConnectFailed { address, reason });
+                return Err(e.into());
+            }
+        };
+        let reactor = TokioReactor::new(
+            vec![self.session_settings.clone()],
+            self.app.clone(),
+            self.store_factory.clone(),
+            self.data_dictionary_provider.clone(),
+            self.log_factory.clone(),
+            self.message_factory.clone(),
+            self.hooks.clone(),
+        );
+        Ok(reactor.run(connection, shutdown).await)
+    }
+}
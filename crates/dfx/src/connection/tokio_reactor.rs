@@ -0,0 +1,303 @@
+use std::{
+    net::SocketAddr,
+    sync::{mpsc::Receiver, Arc},
+};
+
+use dfx_base::data_dictionary_provider::DataDictionaryProvider;
+use dfx_base::message::Message;
+use dfx_base::message_factory::MessageFactory;
+use dfx_base::session_id::SessionId;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt},
+    net::TcpStream,
+    sync::{watch, Notify},
+    time::{interval, MissedTickBehavior},
+};
+
+use crate::{
+    logging::{LogFactory, Logger},
+    message_store::MessageStoreFactory,
+    parser::Parser,
+    session::{
+        Application, ISession, PeerIdentity, QueuedResponder, ReactorThread, ResponderEvent,
+        SessionSetting, SslOptions,
+    },
+};
+
+use super::{
+    best_session_setting, certificate_mismatch, proxy, tls, ConnectionError, ReactorError, SessionHooks, SessionShutdown,
+    SocketSettings, BUF_SIZE, TICK_INTERVAL,
+};
+
+pub(crate) trait AsyncStream: AsyncRead + AsyncWrite + Unpin + Send {}
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> AsyncStream for T {}
+
+/// A connected socket, TLS already negotiated.
+pub(crate) struct AsyncConnection {
+    stream: Box<dyn AsyncStream>,
+    peer: PeerIdentity,
+}
+
+impl AsyncConnection {
+    pub(crate) async fn connect(settings: &SocketSettings) -> Result<Self, ConnectionError> {
+        let endpoint: SocketAddr = settings.get_endpoint()?;
+        let stream = match (settings.proxy().cloned(), settings.endpoint().cloned()) {
+            (Some(proxy), Some(target)) => {
+                let stream = tokio::task::spawn_blocking(move || proxy::connect(&proxy, &target))
+                    .await
+                    .map_err(std::io::Error::other)??;
+                stream.set_nonblocking(true)?;
+                TcpStream::from_std(stream)?
+            }
+            _ => TcpStream::connect(endpoint).await?,
+        };
+        AsyncConnection::configure(stream, settings).await
+    }
+
+    pub(crate) async fn configure(
+        stream: TcpStream,
+        settings: &SocketSettings,
+    ) -> Result<Self, ConnectionError> {
+        stream.set_nodelay(settings.no_delay())?;
+        let address = stream.peer_addr().ok();
+        let (stream, certificate) = match settings.ssl_options() {
+            Some(SslOptions::Acceptor { acceptor }) => tls::accept_async(acceptor, stream).await?,
+            Some(SslOptions::Initiator { initiator, domain }) => {
+                tls::connect_async(initiator, domain, stream).await?
+            }
+            None => (Box::new(stream) as Box<dyn AsyncStream>, None),
+        };
+        Ok(AsyncConnection {
+            stream,
+            peer: PeerIdentity::new(address, certificate),
+        })
+    }
+}
+
+/// Async counterpart of [`SocketReactor`](super::SocketReactor), drives one session per connection.
+///
+/// The session is run when bytes arrive, on every [`TICK_INTERVAL`] and when messages or
+/// commands are queued for it, instead of polling the socket. It runs on the runtime's worker
+/// thread, inside a [`ReactorThread`] so application callbacks can't block on a session.
+pub(crate) struct TokioReactor<App, StoreFactory, DataDictionaryProvider, LogFactory, MessageFactory, Log> {
+    session: Option<ISession<App, DataDictionaryProvider, Log, MessageFactory>>,
+    parser: Parser,
+    events: Option<Receiver<ResponderEvent>>,
+    settings: Vec<SessionSetting>,
+    app: App,
+    store_factory: StoreFactory,
+    data_dictionary_provider: DataDictionaryProvider,
+    log_factory: LogFactory,
+    message_factory: MessageFactory,
+    hooks: SessionHooks,
+    peer: PeerIdentity,
+    wakeup: Arc<Notify>,
+}
+
+impl<App, SF, DDP, LF, MF, Log> TokioReactor<App, SF, DDP, LF, MF, Log>
+where App: Application + Clone + 'static,
+      SF: MessageStoreFactory + Send + Clone + 'static,
+      DDP: DataDictionaryProvider + Send + Clone + 'static,
+      LF: LogFactory<Log = Log> + Send + Clone + 'static,
+      MF: MessageFactory + Send + Clone + 'static,
+      Log: Logger + Clone + 'static
+{
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        settings: Vec<SessionSetting>,
+        app: App,
+        store_factory: SF, data_dictionary_provider: DDP, log_factory: LF, message_factory: MF,
+        hooks: SessionHooks,
+    ) -> Self {
+        TokioReactor {
+            session: None,
+            parser: Parser::default(),
+            events: None,
+            settings,
+            app,
+            store_factory,
+            data_dictionary_provider,
+            log_factory,
+            message_factory,
+            hooks,
+            peer: PeerIdentity::default(),
+            wakeup: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Runs the connection until either side disconnects, once `shutdown` carries a reason the
+    /// session is logged out first.
+    pub(crate) async fn run(
+        mut self,
+        connection: AsyncConnection,
+        mut shutdown: watch::Receiver<Option<String>>,
+    ) -> Option<SessionShutdown> {
+        let AsyncConnection { mut stream, peer } = connection;
+        self.peer = peer;
+        if self.settings.len() == 1 {
+            let session_setting = &self.settings[0];
+            if session_setting.connection().is_initiator()
+                || (session_setting.connection().is_acceptor() && !session_setting.is_dynamic())
+            {
+                let session_id = session_setting.session_id().clone();
+                let settings = session_setting.clone();
+                if self.create_session(session_id, &settings).is_err() {
+                    return None;
+                }
+            }
+        }
+
+        if let Some(observer) = self.hooks.observer() {
+            let session_id = self.session.as_ref().map(|s| s.session_id());
+            observer.on_connected(session_id, &self.peer);
+        }
+        let cause = self
+            .event_loop(&mut stream, &mut shutdown)
+            .await
+            .err()
+            .and_then(ReactorError::into_cause);
+        if let Some(session) = self.session.as_mut() {
+            let _reactor = ReactorThread::enter();
+            // without a session the cause only reaches the observer below
+            if let Some(cause) = &cause {
+                session.log().on_event(&format!("Connection error: {cause}"));
+            }
+            let session_id = session.session_id().clone();
+            session.set_disconnected(&session_id);
+            session.flush();
+        }
+        let _ = stream.shutdown().await;
+        if let Some(observer) = self.hooks.observer() {
+            let session_id = self.session.as_ref().map(|s| s.session_id());
+            observer.on_disconnected(session_id, &self.peer, cause.as_ref());
+        }
+        self.session.as_ref().and_then(SessionShutdown::of)
+    }
+
+    async fn event_loop(
+        &mut self,
+        stream: &mut Box<dyn AsyncStream>,
+        shutdown: &mut watch::Receiver<Option<String>>,
+    ) -> Result<(), ReactorError> {
+        let wakeup = self.wakeup.clone();
+        let mut buffer = [0; BUF_SIZE];
+        let mut tick = interval(TICK_INTERVAL);
+        tick.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+        self.next();
+        self.write_pending(stream).await?;
+        let mut shutting_down = false;
+        loop {
+            if !shutting_down {
+                let reason = shutdown.borrow().clone();
+                if let Some(reason) = reason {
+                    match self.session.as_mut() {
+                        // keeps running until the Logout is answered or times out
+                        Some(session) => {
+                            let _reactor = ReactorThread::enter();
+                            session.shutdown(&reason);
+                        }
+                        None => return Ok(()),
+                    }
+                    shutting_down = true;
+                    self.write_pending(stream).await?;
+                }
+            }
+            tokio::select! {
+                read = stream.read(&mut buffer) => match read? {
+                    0 => return Err(ReactorError::Disconnect),
+                    read => {
+                        self.parser.add_to_stream(&buffer[..read]);
+                        self.process_stream()?;
+                    }
+                },
+                _ = tick.tick() => self.next(),
+                _ = wakeup.notified() => self.next(),
+                changed = shutdown.changed(), if !shutting_down => if changed.is_err() {
+                    return Ok(());
+                },
+            }
+            self.write_pending(stream).await?;
+        }
+    }
+
+    fn next(&mut self) {
+        if let Some(session) = self.session.as_mut() {
+            let _reactor = ReactorThread::enter();
+            session.next();
+        }
+    }
+
+    async fn write_pending(&mut self, stream: &mut Box<dyn AsyncStream>) -> Result<(), ReactorError> {
+        let events: Vec<ResponderEvent> = match self.events.as_ref() {
+            Some(events) => events.try_iter().collect(),
+            None => return Ok(()),
+        };
+        if events.is_empty() {
+            return Ok(());
+        }
+        for event in events {
+            match event {
+                ResponderEvent::Send(message) => stream.write_all(message.as_bytes()).await?,
+                ResponderEvent::Disconnect => {
+                    stream.flush().await?;
+                    return Err(ReactorError::Disconnect);
+                }
+            }
+        }
+        Ok(stream.flush().await?)
+    }
+
+    fn process_stream(&mut self) -> Result<(), ReactorError> {
+        let _reactor = ReactorThread::enter();
+        while let Some(msg) = self.parser.read_fix_message()? {
+            if let Some(session) = self.session.as_mut() {
+                session.next_msg(msg);
+                continue;
+            }
+            let message = Message::new(&msg[..])?;
+            let session_id = message.extract_contra_session_id();
+            let settings = match best_session_setting(&self.settings, &session_id) {
+                Some(settings) if settings.accepts(&session_id) => settings.clone(),
+                // TODO this.Log("ERROR: Disconnecting; received message for unknown session: " + msg);
+                _ => return Err(ReactorError::Disconnect),
+            };
+            self.create_session(session_id, &settings)?;
+            if let Some(session) = self.session.as_mut() {
+                session.next_msg(msg);
+            }
+        }
+        Ok(())
+    }
+
+    fn create_session(&mut self, session_id: SessionId, settings: &SessionSetting) -> Result<(), ReactorError> {
+        let _reactor = ReactorThread::enter();
+        let log = self.log_factory.create(&session_id);
+        let mut session = ISession::from_settings(
+            session_id.clone(),
+            self.app.clone(),
+            Box::new(self.store_factory.clone()),
+            self.data_dictionary_provider.clone(),
+            log,
+            self.message_factory.clone(),
+            settings.clone()
+        );
+        self.hooks.apply(&mut session);
+        session.set_peer(self.peer.clone());
+        if let Some(reason) = certificate_mismatch(settings, session.session_id(), &self.peer) {
+            session.reject_logon(reason);
+        }
+        let (responder, events) = QueuedResponder::new();
+        session.set_responder(Box::new(responder));
+        let wakeup = self.wakeup.clone();
+        session.set_wakeup(Arc::new(move || wakeup.notify_one()));
+        if session.set_connected(&session_id).is_err() {
+            session.log().on_event("Disconnecting, the session is already connected");
+            return Err(ReactorError::Disconnect);
+        }
+        session.log_connected();
+        self.events = Some(events);
+        self.session = Some(session);
+        Ok(())
+    }
+}
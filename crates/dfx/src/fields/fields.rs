@@ -16,6 +16,8 @@ impl MsgType {
     pub const LOGOUT: &'static str = "5"; //TODO
     pub const LOGON: &'static str = "A";
     pub const BUSINESS_MESSAGE_REJECT: &'static str = "j";
+    pub const USER_REQUEST: &'static str = "BE";
+    pub const USER_RESPONSE: &'static str = "BF";
 
     pub fn new(msg_type: &str) -> Self {
         Self(Field::new(
@@ -8,6 +8,8 @@ pub trait Logger: Send + std::fmt::Debug {
     fn on_incoming(&self, incoming: &str);
     fn on_outgoing(&self, outgoing: &str);
     fn on_event(&self, event: &str);
+    /// Called on shutdown, once the session has logged out.
+    fn flush(&self) {}
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -81,8 +83,8 @@ impl FileLogger {
         let prefix = session_id.prefix();
         let messages_file_name = format!("{log_path}/{prefix}.messages");
         let event_file_name = format!("{log_path}/{prefix}.event");
-        let messages_file = OpenOptions::new().read(true).write(true).create(true).open(messages_file_name)?;
-        let event_file = OpenOptions::new().read(true).write(true).create(true).open(event_file_name)?;
+        let messages_file = OpenOptions::new().read(true).append(true).create(true).open(messages_file_name)?;
+        let event_file = OpenOptions::new().read(true).append(true).create(true).open(event_file_name)?;
         Ok(FileLogger {
             messages_file,
             event_file
@@ -108,6 +110,11 @@ impl Logger for FileLogger {
         file.write_all(event.as_bytes()).unwrap();
         writeln!(file).unwrap();
     }
+
+    fn flush(&self) {
+        self.messages_file.sync_data().unwrap();
+        self.event_file.sync_data().unwrap();
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -8,6 +8,8 @@ pub trait MessageStore: Send + std::fmt::Debug {
     fn reset(&mut self);
     fn creation_time(&self) -> Option<DateTime<Utc>>;
     fn refresh(&mut self);
+    /// Called on shutdown, once the session has logged out.
+    fn flush(&mut self) {}
 
     fn next_sender_msg_seq_num(&self) -> u32;
     fn set_next_sender_msg_seq_num(&mut self, seq_num: u32);
@@ -330,6 +332,13 @@ impl FileStore {
         Ok(())
     }
 
+    pub fn flush(&mut self) -> io::Result<()> {
+        for file in [&self.seq_nums_file, &self.msg_file, &self.header_file].into_iter().flatten() {
+            file.sync_data()?;
+        }
+        Ok(())
+    }
+
 }
 
 impl MessageStore for FileStore {
@@ -345,6 +354,10 @@ impl MessageStore for FileStore {
         self.refresh().unwrap();
     }
 
+    fn flush(&mut self) {
+        self.flush().unwrap();
+    }
+
     fn next_sender_msg_seq_num(&self) -> u32 {
         self.cache.next_sender_msg_seq_num()
     }
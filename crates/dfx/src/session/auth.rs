@@ -0,0 +1,159 @@
+use std::net::SocketAddr;
+
+use dfx_base::message::Message;
+use dfx_base::session_id::SessionId;
+use dfx_base::tags;
+
+/// Values of SessionStatus (1409), sent on Logon and Logout responses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogonStatus {
+    SessionActive,
+    PasswordChanged,
+    PasswordDueToExpire,
+    NewPasswordNotCompliant,
+    LogoutComplete,
+    InvalidCredentials,
+    AccountLocked,
+    LogonsNotAllowed,
+    PasswordExpired,
+}
+
+impl LogonStatus {
+    pub fn value(&self) -> u32 {
+        match self {
+            LogonStatus::SessionActive => 0,
+            LogonStatus::PasswordChanged => 1,
+            LogonStatus::PasswordDueToExpire => 2,
+            LogonStatus::NewPasswordNotCompliant => 3,
+            LogonStatus::LogoutComplete => 4,
+            LogonStatus::InvalidCredentials => 5,
+            LogonStatus::AccountLocked => 6,
+            LogonStatus::LogonsNotAllowed => 7,
+            LogonStatus::PasswordExpired => 8,
+        }
+    }
+}
+
+/// DER encoded certificate presented by the counterparty during the TLS handshake.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PeerCertificate {
+    der: Vec<u8>,
+}
+
+impl PeerCertificate {
+    pub fn from_der(der: Vec<u8>) -> Self {
+        PeerCertificate { der }
+    }
+    pub fn der(&self) -> &[u8] {
+        &self.der
+    }
+}
+
+/// Transport level identity of the counterparty of a session.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PeerIdentity {
+    address: Option<SocketAddr>,
+    certificate: Option<PeerCertificate>,
+}
+
+impl PeerIdentity {
+    pub fn new(address: Option<SocketAddr>, certificate: Option<PeerCertificate>) -> Self {
+        PeerIdentity {
+            address,
+            certificate,
+        }
+    }
+    pub fn address(&self) -> Option<SocketAddr> {
+        self.address
+    }
+    /// `None` for plain TCP connections or when no client certificate was requested.
+    pub fn certificate(&self) -> Option<&PeerCertificate> {
+        self.certificate.as_ref()
+    }
+}
+
+/// An incoming Logon, handed to a [`LogonAuthenticator`] before it is accepted.
+#[derive(Debug, Clone, Copy)]
+pub struct LogonRequest<'a> {
+    session_id: &'a SessionId,
+    message: &'a Message,
+    peer: &'a PeerIdentity,
+}
+
+impl<'a> LogonRequest<'a> {
+    pub(crate) fn new(session_id: &'a SessionId, message: &'a Message, peer: &'a PeerIdentity) -> Self {
+        LogonRequest {
+            session_id,
+            message,
+            peer,
+        }
+    }
+    pub fn session_id(&self) -> &SessionId {
+        self.session_id
+    }
+    pub fn message(&self) -> &Message {
+        self.message
+    }
+    pub fn peer(&self) -> &PeerIdentity {
+        self.peer
+    }
+    /// Username (553)
+    pub fn username(&self) -> Option<String> {
+        self.message.get_string(tags::Username).ok()
+    }
+    /// Password (554)
+    pub fn password(&self) -> Option<String> {
+        self.message.get_string(tags::Password).ok()
+    }
+    /// NewPassword (925)
+    pub fn new_password(&self) -> Option<String> {
+        self.message.get_string(tags::NewPassword).ok()
+    }
+}
+
+/// Outcome of a [`LogonAuthenticator`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogonDecision {
+    Accept,
+    /// Answer with a Logout carrying `text` and disconnect.
+    Reject { text: Option<String> },
+    /// Answer with a Logout with SessionStatus (1409) "Password expired" and disconnect, the
+    /// counterparty has to log on again with a NewPassword (925).
+    RequirePasswordChange { text: Option<String> },
+}
+
+impl LogonDecision {
+    pub fn reject(text: &str) -> Self {
+        LogonDecision::Reject {
+            text: Some(text.into()),
+        }
+    }
+    pub fn require_password_change(text: &str) -> Self {
+        LogonDecision::RequirePasswordChange {
+            text: Some(text.into()),
+        }
+    }
+}
+
+/// Decides whether an acceptor accepts an incoming Logon.
+///
+/// Runs after the Logon passed session level validation and `from_admin`, before the Logon
+/// response is sent and before [`Application::on_logon`](crate::session::Application::on_logon).
+pub trait LogonAuthenticator: Send + Sync {
+    fn authenticate(&self, request: &LogonRequest<'_>) -> LogonDecision;
+}
+
+impl<F> LogonAuthenticator for F
+where
+    F: Fn(&LogonRequest<'_>) -> LogonDecision + Send + Sync,
+{
+    fn authenticate(&self, request: &LogonRequest<'_>) -> LogonDecision {
+        self(request)
+    }
+}
+
+impl std::fmt::Debug for dyn LogonAuthenticator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("LogonAuthenticator")
+    }
+}
@@ -0,0 +1,303 @@
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+use std::net::IpAddr;
+
+use dfx_base::session_id::SessionId;
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+use x509_parser::{
+    asn1_rs::{BmpString, Oid, Tag},
+    certificate::X509Certificate,
+    extensions::GeneralName,
+    prelude::FromDer,
+    x509::AttributeTypeAndValue,
+};
+
+use super::PeerCertificate;
+
+/// A name a certificate is issued for, an attribute of its subject or a subjectAltName.
+///
+/// Subject attributes use their usual short name as key (`CN`, `O`, `OU`, `C`, `L`, `ST`, ...),
+/// subjectAltNames `DNS`, `EMAIL`, `URI` and `IP`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CertificateName {
+    key: String,
+    value: String,
+}
+
+impl CertificateName {
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
+impl PeerCertificate {
+    /// The subject attributes and subjectAltNames, empty if the certificate can't be parsed or
+    /// neither TLS backend is enabled.
+    pub fn names(&self) -> Vec<CertificateName> {
+        names(self.der()).unwrap_or_default()
+    }
+}
+
+/// `SSLCertificateCompIDs`, the CompIDs a client certificate may log on as.
+///
+/// Rules are separated by `;`, each one a `<key>:<pattern>` matched against the
+/// [`CertificateName`]s of the certificate, `*` matching any characters, followed by `>` and the
+/// comma separated CompIDs, e.g. `CN:TW>TW;DNS:*.example.com>GW1,GW2`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CertificateCompIds {
+    rules: Vec<CertificateRule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CertificateRule {
+    key: String,
+    pattern: String,
+    comp_ids: Vec<String>,
+}
+
+impl TryFrom<&str> for CertificateCompIds {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let rules = value
+            .split(';')
+            .map(str::trim)
+            .filter(|rule| !rule.is_empty())
+            .map(|rule| {
+                let (name, comp_ids) = rule.split_once('>').ok_or(())?;
+                let (key, pattern) = name.split_once(':').ok_or(())?;
+                let comp_ids: Vec<String> = comp_ids
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|comp_id| !comp_id.is_empty())
+                    .map(String::from)
+                    .collect();
+                if key.trim().is_empty() || comp_ids.is_empty() {
+                    return Err(());
+                }
+                Ok(CertificateRule {
+                    key: key.trim().to_ascii_uppercase(),
+                    pattern: pattern.trim().into(),
+                    comp_ids,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        match rules.is_empty() {
+            true => Err(()),
+            false => Ok(CertificateCompIds { rules }),
+        }
+    }
+}
+
+impl CertificateCompIds {
+    /// Why the counterparty of `session_id` may not log on with `certificate`, `None` if it may.
+    pub(crate) fn check(
+        &self,
+        session_id: &SessionId,
+        certificate: Option<&PeerCertificate>,
+    ) -> Option<String> {
+        let comp_id = session_id.target_comp_id();
+        let certificate = match certificate {
+            Some(certificate) => certificate,
+            None => return Some(format!("No client certificate for {comp_id}")),
+        };
+        let names = certificate.names();
+        let allowed = self.rules.iter().any(|rule| {
+            rule.comp_ids.iter().any(|allowed| allowed == comp_id)
+                && names.iter().any(|name| rule.matches(name))
+        });
+        match allowed {
+            true => None,
+            false => Some(format!("Client certificate not valid for {comp_id}")),
+        }
+    }
+}
+
+impl CertificateRule {
+    /// Whether `name` matches the rule, DNS names and email addresses ignoring case.
+    fn matches(&self, name: &CertificateName) -> bool {
+        if !name.key.eq_ignore_ascii_case(&self.key) {
+            return false;
+        }
+        match self.key.as_str() {
+            "DNS" | "EMAIL" => wildcard(
+                &self.pattern.to_ascii_lowercase(),
+                &name.value.to_ascii_lowercase(),
+            ),
+            _ => wildcard(&self.pattern, &name.value),
+        }
+    }
+}
+
+/// Whether `value` matches `pattern`, `*` matching any characters.
+fn wildcard(pattern: &str, value: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == value,
+        Some((prefix, rest)) => {
+            let value = match value.strip_prefix(prefix) {
+                Some(value) => value,
+                None => return false,
+            };
+            value
+                .char_indices()
+                .map(|(i, _)| i)
+                .chain([value.len()])
+                .any(|i| wildcard(rest, &value[i..]))
+        }
+    }
+}
+
+// without a TLS backend no peer sends a certificate, x509-parser is only pulled in with one
+#[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+fn names(_der: &[u8]) -> Option<Vec<CertificateName>> {
+    None
+}
+
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+fn names(der: &[u8]) -> Option<Vec<CertificateName>> {
+    let (_, certificate) = X509Certificate::from_der(der).ok()?;
+    let mut names = vec![];
+    for attribute in certificate.subject().iter_attributes() {
+        if let Some(value) = attribute_value(attribute) {
+            names.push(CertificateName {
+                key: attribute_key(attribute.attr_type()),
+                value,
+            });
+        }
+    }
+    let subject_alt_names = match certificate.subject_alternative_name().ok()? {
+        Some(extension) => &extension.value.general_names,
+        None => return Some(names),
+    };
+    for general_name in subject_alt_names {
+        let (key, value) = match general_name {
+            GeneralName::RFC822Name(email) => ("EMAIL", email.to_string()),
+            GeneralName::DNSName(dns) => ("DNS", dns.to_string()),
+            GeneralName::URI(uri) => ("URI", uri.to_string()),
+            GeneralName::IPAddress(ip) => match ip.len() {
+                4 => ("IP", IpAddr::from(<[u8; 4]>::try_from(*ip).ok()?).to_string()),
+                16 => ("IP", IpAddr::from(<[u8; 16]>::try_from(*ip).ok()?).to_string()),
+                _ => continue,
+            },
+            _ => continue,
+        };
+        names.push(CertificateName {
+            key: key.into(),
+            value,
+        });
+    }
+    Some(names)
+}
+
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+fn attribute_key(oid: &Oid) -> String {
+    let oid = oid.to_id_string();
+    let key = match oid.as_str() {
+        "2.5.4.3" => "CN",
+        "2.5.4.5" => "SERIALNUMBER",
+        "2.5.4.6" => "C",
+        "2.5.4.7" => "L",
+        "2.5.4.8" => "ST",
+        "2.5.4.10" => "O",
+        "2.5.4.11" => "OU",
+        "1.2.840.113549.1.9.1" => "EMAILADDRESS",
+        "0.9.2342.19200300.100.1.1" => "UID",
+        "0.9.2342.19200300.100.1.25" => "DC",
+        _ => return oid,
+    };
+    key.into()
+}
+
+/// The value of a string attribute, `None` for other types.
+#[cfg(any(feature = "native-tls", feature = "rustls"))]
+fn attribute_value(attribute: &AttributeTypeAndValue) -> Option<String> {
+    let value = attribute.attr_value();
+    match value.tag() {
+        Tag::BmpString => BmpString::try_from(value).ok().map(|value| value.string()),
+        Tag::T61String => std::str::from_utf8(value.data).ok().map(String::from),
+        _ => attribute.as_str().ok().map(String::from),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wildcard() {
+        assert!(wildcard("TW", "TW"));
+        assert!(!wildcard("TW", "TWO"));
+        assert!(wildcard("*.example.com", "tw.example.com"));
+        assert!(!wildcard("*.example.com", "example.com"));
+        assert!(wildcard("T*", "T"));
+        assert!(wildcard("*W*", "TWO"));
+        assert!(!wildcard("*X*", "TWO"));
+    }
+
+    #[test]
+    fn test_parse_comp_ids() {
+        let comp_ids = CertificateCompIds::try_from("CN:TW>TW; dns:*.example.com>GW1,GW2").unwrap();
+        assert_eq!(comp_ids.rules.len(), 2);
+        assert_eq!(comp_ids.rules[1].key, "DNS");
+        assert_eq!(comp_ids.rules[1].comp_ids, ["GW1", "GW2"]);
+        let comp_ids = CertificateCompIds::try_from("URI:spiffe://example.com/tw>TW").unwrap();
+        assert_eq!(comp_ids.rules[0].pattern, "spiffe://example.com/tw");
+        assert!(CertificateCompIds::try_from("CN:TW").is_err());
+        assert!(CertificateCompIds::try_from("TW>TW").is_err());
+        assert!(CertificateCompIds::try_from("CN:TW>").is_err());
+        assert!(CertificateCompIds::try_from("").is_err());
+    }
+
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    fn certificate(der: &[u8]) -> PeerCertificate {
+        PeerCertificate::from_der(der.to_vec())
+    }
+
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    fn keys_and_values(certificate: &PeerCertificate) -> Vec<String> {
+        let names = certificate.names();
+        names.iter().map(|name| format!("{}={}", name.key, name.value)).collect()
+    }
+
+    #[test]
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    fn test_names() {
+        // BMPString attributes, a multi-valued RDN and a critical subjectAltName
+        let names = certificate(include_bytes!("../../tests/certs/names.der"));
+        assert_eq!(
+            keys_and_values(&names),
+            ["O=dfx", "CN=TW", "OU=fix", "DNS=TW.Example.com", "EMAIL=TW@Example.com", "IP=127.0.0.1"]
+        );
+        let no_extensions = certificate(include_bytes!("../../tests/certs/no_extensions.der"));
+        assert_eq!(keys_and_values(&no_extensions), ["CN=TW"]);
+    }
+
+    #[test]
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    fn test_malformed_certificate_fails_closed() {
+        let der = include_bytes!("../../tests/certs/names.der");
+        let session_id = SessionId::new("FIX.4.4", "ISLD", "", "", "TW", "", "");
+        let comp_ids = CertificateCompIds::try_from("CN:TW>TW").unwrap();
+        assert_eq!(comp_ids.check(&session_id, Some(&certificate(der))), None);
+        for malformed in [&der[..der.len() - 1], &der[1..], &[0x30, 0x84, 0xff, 0xff, 0xff, 0xff], &[]] {
+            let malformed = certificate(malformed);
+            assert!(malformed.names().is_empty());
+            assert!(comp_ids.check(&session_id, Some(&malformed)).is_some());
+        }
+    }
+
+    #[test]
+    #[cfg(any(feature = "native-tls", feature = "rustls"))]
+    fn test_check_ignores_case_of_dns_and_email() {
+        let names = certificate(include_bytes!("../../tests/certs/names.der"));
+        let session_id = SessionId::new("FIX.4.4", "ISLD", "", "", "TW", "", "");
+        for rule in ["DNS:*.example.COM>TW", "email:tw@example.com>TW"] {
+            let comp_ids = CertificateCompIds::try_from(rule).unwrap();
+            assert_eq!(comp_ids.check(&session_id, Some(&names)), None, "{rule}");
+        }
+        let comp_ids = CertificateCompIds::try_from("CN:tw>TW").unwrap();
+        assert!(comp_ids.check(&session_id, Some(&names)).is_some());
+    }
+}
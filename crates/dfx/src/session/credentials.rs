@@ -0,0 +1,210 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::sync::Mutex;
+
+use super::LogonStatus;
+
+/// Reasons a [`CredentialStore`] refuses a logon or password change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CredentialError {
+    UnknownUser,
+    InvalidPassword,
+    AccountLocked,
+    PasswordExpired,
+    NewPasswordNotCompliant,
+}
+
+impl CredentialError {
+    /// SessionStatus (1409) sent on the Logout rejecting a Logon.
+    pub fn logon_status(&self) -> LogonStatus {
+        match self {
+            CredentialError::UnknownUser | CredentialError::InvalidPassword => {
+                LogonStatus::InvalidCredentials
+            }
+            CredentialError::AccountLocked => LogonStatus::AccountLocked,
+            CredentialError::PasswordExpired => LogonStatus::PasswordExpired,
+            CredentialError::NewPasswordNotCompliant => LogonStatus::NewPasswordNotCompliant,
+        }
+    }
+
+    /// UserStatus (926) sent on the UserResponse to a UserRequest.
+    pub fn user_status(&self) -> UserStatus {
+        match self {
+            CredentialError::UnknownUser => UserStatus::UserNotRecognised,
+            CredentialError::InvalidPassword => UserStatus::PasswordIncorrect,
+            _ => UserStatus::Other,
+        }
+    }
+}
+
+impl Display for CredentialError {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CredentialError::UnknownUser | CredentialError::InvalidPassword => {
+                fmt.write_str("Invalid username or password")
+            }
+            CredentialError::AccountLocked => fmt.write_str("Account locked"),
+            CredentialError::PasswordExpired => fmt.write_str("Password expired"),
+            CredentialError::NewPasswordNotCompliant => {
+                fmt.write_str("New password does not comply with policy")
+            }
+        }
+    }
+}
+
+/// Values of UserStatus (926).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserStatus {
+    LoggedIn,
+    NotLoggedIn,
+    UserNotRecognised,
+    PasswordIncorrect,
+    PasswordChanged,
+    Other,
+}
+
+impl UserStatus {
+    pub fn value(&self) -> u32 {
+        match self {
+            UserStatus::LoggedIn => 1,
+            UserStatus::NotLoggedIn => 2,
+            UserStatus::UserNotRecognised => 3,
+            UserStatus::PasswordIncorrect => 4,
+            UserStatus::PasswordChanged => 5,
+            UserStatus::Other => 6,
+        }
+    }
+}
+
+/// Usernames and passwords checked by an acceptor.
+///
+/// When configured the acceptor requires Username (553) and Password (554) on every Logon,
+/// changes the password when the Logon carries a NewPassword (925) and answers UserRequest (BE)
+/// messages itself instead of passing them to the application.
+pub trait CredentialStore: Send + Sync {
+    fn authenticate(&self, username: &str, password: &str) -> Result<(), CredentialError>;
+    fn change_password(
+        &self,
+        username: &str,
+        password: &str,
+        new_password: &str,
+    ) -> Result<(), CredentialError>;
+    fn contains_user(&self, username: &str) -> bool;
+}
+
+impl std::fmt::Debug for dyn CredentialStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("CredentialStore")
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Account {
+    password: String,
+    expired: bool,
+    locked: bool,
+}
+
+/// In memory [`CredentialStore`], new passwords must be non empty and differ from the old one.
+#[derive(Debug, Default)]
+pub struct MemoryCredentialStore {
+    accounts: Mutex<HashMap<String, Account>>,
+}
+
+impl MemoryCredentialStore {
+    pub fn new() -> Self {
+        MemoryCredentialStore::default()
+    }
+
+    pub fn add_user(&self, username: &str, password: &str) -> &Self {
+        let account = Account {
+            password: password.into(),
+            expired: false,
+            locked: false,
+        };
+        self.accounts.lock().unwrap().insert(username.into(), account);
+        self
+    }
+
+    /// The user has to change the password on the next logon.
+    pub fn expire_password(&self, username: &str) -> &Self {
+        if let Some(account) = self.accounts.lock().unwrap().get_mut(username) {
+            account.expired = true;
+        }
+        self
+    }
+
+    pub fn set_locked(&self, username: &str, locked: bool) -> &Self {
+        if let Some(account) = self.accounts.lock().unwrap().get_mut(username) {
+            account.locked = locked;
+        }
+        self
+    }
+
+    fn check(account: Option<&Account>, password: &str) -> Result<(), CredentialError> {
+        match account {
+            None => Err(CredentialError::UnknownUser),
+            Some(account) if account.password != password => Err(CredentialError::InvalidPassword),
+            Some(account) if account.locked => Err(CredentialError::AccountLocked),
+            Some(_) => Ok(()),
+        }
+    }
+}
+
+impl CredentialStore for MemoryCredentialStore {
+    fn authenticate(&self, username: &str, password: &str) -> Result<(), CredentialError> {
+        let accounts = self.accounts.lock().unwrap();
+        let account = accounts.get(username);
+        Self::check(account, password)?;
+        match account {
+            Some(account) if account.expired => Err(CredentialError::PasswordExpired),
+            _ => Ok(()),
+        }
+    }
+
+    fn change_password(
+        &self,
+        username: &str,
+        password: &str,
+        new_password: &str,
+    ) -> Result<(), CredentialError> {
+        let mut accounts = self.accounts.lock().unwrap();
+        Self::check(accounts.get(username), password)?;
+        if new_password.is_empty() || new_password == password {
+            return Err(CredentialError::NewPasswordNotCompliant);
+        }
+        if let Some(account) = accounts.get_mut(username) {
+            account.password = new_password.into();
+            account.expired = false;
+        }
+        Ok(())
+    }
+
+    fn contains_user(&self, username: &str) -> bool {
+        self.accounts.lock().unwrap().contains_key(username)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CredentialError, CredentialStore, MemoryCredentialStore};
+
+    #[test]
+    fn memory_store() {
+        let store = MemoryCredentialStore::new();
+        store.add_user("trader", "secret").expire_password("trader");
+
+        assert_eq!(store.authenticate("other", "secret"), Err(CredentialError::UnknownUser));
+        assert_eq!(store.authenticate("trader", "wrong"), Err(CredentialError::InvalidPassword));
+        assert_eq!(store.authenticate("trader", "secret"), Err(CredentialError::PasswordExpired));
+        assert_eq!(
+            store.change_password("trader", "secret", "secret"),
+            Err(CredentialError::NewPasswordNotCompliant)
+        );
+        assert_eq!(store.change_password("trader", "secret", "changed"), Ok(()));
+        assert_eq!(store.authenticate("trader", "changed"), Ok(()));
+
+        store.set_locked("trader", true);
+        assert_eq!(store.authenticate("trader", "changed"), Err(CredentialError::AccountLocked));
+    }
+}
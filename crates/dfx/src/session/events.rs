@@ -0,0 +1,234 @@
+use std::fmt::Display;
+use std::net::SocketAddr;
+use std::sync::mpsc::Sender;
+use std::sync::mpsc::SyncSender;
+use std::sync::Arc;
+
+use dfx_base::session_id::SessionId;
+
+/// What a session stopped waiting for, see [`SessionEvent::TimedOut`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionTimeout {
+    LogonRequest,
+    LogonResponse,
+    LogoutResponse,
+    Heartbeat,
+}
+
+/// Session lifecycle events, published to [`SessionEventSubscriber`]s.
+///
+/// The same information is written to [`Logger::on_event`](crate::logging::Logger::on_event)
+/// as text, the events are meant for monitoring code that needs to react to them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionEvent {
+    /// An initiator is opening a connection.
+    Connecting { address: SocketAddr },
+    ConnectFailed { address: SocketAddr, reason: String },
+    Connected,
+    Disconnected { reason: String },
+    LogonSent,
+    LoggedOn,
+    LogonRejected { reason: Option<String> },
+    LogoutSent { reason: Option<String> },
+    LogoutReceived,
+    TimedOut(SessionTimeout),
+    TestRequestSent,
+    /// An incoming MsgSeqNum was higher than expected.
+    SequenceGap { expected: u32, received: u32 },
+    ResendRequestSent { begin_seq_num: u32, end_seq_num: u32 },
+    ResendRequestReceived { begin_seq_num: u32, end_seq_num: u32 },
+    SequenceResetReceived { new_seq_num: u32, gap_fill: bool },
+    RejectSent { ref_seq_num: Option<u32>, reason: String },
+    RejectReceived { ref_seq_num: Option<u32>, text: Option<String> },
+    BusinessRejectSent { ref_seq_num: u32, reason: String },
+}
+
+impl Display for SessionTimeout {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionTimeout::LogonRequest => fmt.write_str("logon request"),
+            SessionTimeout::LogonResponse => fmt.write_str("logon response"),
+            SessionTimeout::LogoutResponse => fmt.write_str("logout response"),
+            SessionTimeout::Heartbeat => fmt.write_str("heartbeat"),
+        }
+    }
+}
+
+impl Display for SessionEvent {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionEvent::Connecting { address } => {
+                fmt.write_fmt(format_args!("connecting to {address}"))
+            }
+            SessionEvent::ConnectFailed { address, reason } => {
+                fmt.write_fmt(format_args!("connect to {address} failed: {reason}"))
+            }
+            SessionEvent::Connected => fmt.write_str("connected"),
+            SessionEvent::Disconnected { reason } => {
+                fmt.write_fmt(format_args!("disconnected: {reason}"))
+            }
+            SessionEvent::LogonSent => fmt.write_str("logon sent"),
+            SessionEvent::LoggedOn => fmt.write_str("logged on"),
+            SessionEvent::LogonRejected { reason: Some(reason) } => {
+                fmt.write_fmt(format_args!("logon rejected: {reason}"))
+            }
+            SessionEvent::LogonRejected { reason: None } => fmt.write_str("logon rejected"),
+            SessionEvent::LogoutSent { reason: Some(reason) } => {
+                fmt.write_fmt(format_args!("logout sent: {reason}"))
+            }
+            SessionEvent::LogoutSent { reason: None } => fmt.write_str("logout sent"),
+            SessionEvent::LogoutReceived => fmt.write_str("logout received"),
+            SessionEvent::TimedOut(timeout) => {
+                fmt.write_fmt(format_args!("timed out waiting for {timeout}"))
+            }
+            SessionEvent::TestRequestSent => fmt.write_str("test request sent"),
+            SessionEvent::SequenceGap { expected, received } => fmt.write_fmt(format_args!(
+                "sequence gap, expected {expected} but received {received}"
+            )),
+            SessionEvent::ResendRequestSent {
+                begin_seq_num,
+                end_seq_num,
+            } => fmt.write_fmt(format_args!(
+                "resend requested {begin_seq_num}..{end_seq_num}"
+            )),
+            SessionEvent::ResendRequestReceived {
+                begin_seq_num,
+                end_seq_num,
+            } => fmt.write_fmt(format_args!(
+                "resend request received {begin_seq_num}..{end_seq_num}"
+            )),
+            SessionEvent::SequenceResetReceived {
+                new_seq_num,
+                gap_fill,
+            } => fmt.write_fmt(format_args!(
+                "sequence reset received, new MsgSeqNum {new_seq_num} (gap fill: {gap_fill})"
+            )),
+            SessionEvent::RejectSent {
+                ref_seq_num,
+                reason,
+            } => match ref_seq_num {
+                Some(ref_seq_num) => {
+                    fmt.write_fmt(format_args!("reject sent for {ref_seq_num}: {reason}"))
+                }
+                None => fmt.write_fmt(format_args!("reject sent: {reason}")),
+            },
+            SessionEvent::RejectReceived { ref_seq_num, text } => {
+                fmt.write_str("reject received")?;
+                if let Some(ref_seq_num) = ref_seq_num {
+                    fmt.write_fmt(format_args!(" for {ref_seq_num}"))?;
+                }
+                if let Some(text) = text {
+                    fmt.write_fmt(format_args!(": {text}"))?;
+                }
+                Ok(())
+            }
+            SessionEvent::BusinessRejectSent {
+                ref_seq_num,
+                reason,
+            } => fmt.write_fmt(format_args!(
+                "business reject sent for {ref_seq_num}: {reason}"
+            )),
+        }
+    }
+}
+
+/// Receives the [`SessionEvent`]s of every session of an acceptor or initiator.
+///
+/// Called on the session thread, implementations should hand the event off rather than block.
+/// Closures and channel senders implement this trait, a full [`SyncSender`] drops the event.
+pub trait SessionEventSubscriber: Send + Sync {
+    fn on_event(&self, session_id: &SessionId, event: &SessionEvent);
+}
+
+impl<F> SessionEventSubscriber for F
+where
+    F: Fn(&SessionId, &SessionEvent) + Send + Sync,
+{
+    fn on_event(&self, session_id: &SessionId, event: &SessionEvent) {
+        self(session_id, event)
+    }
+}
+
+impl SessionEventSubscriber for SyncSender<(SessionId, SessionEvent)> {
+    fn on_event(&self, session_id: &SessionId, event: &SessionEvent) {
+        let _ = self.try_send((session_id.clone(), event.clone()));
+    }
+}
+
+impl SessionEventSubscriber for Sender<(SessionId, SessionEvent)> {
+    fn on_event(&self, session_id: &SessionId, event: &SessionEvent) {
+        let _ = self.send((session_id.clone(), event.clone()));
+    }
+}
+
+/// Set of [`SessionEventSubscriber`]s, shared between the sessions created by an acceptor or
+/// initiator.
+#[derive(Clone, Default)]
+pub struct SessionEvents {
+    subscribers: Vec<Arc<dyn SessionEventSubscriber>>,
+}
+
+impl std::fmt::Debug for SessionEvents {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SessionEvents")
+            .field("subscribers", &self.subscribers.len())
+            .finish()
+    }
+}
+
+impl SessionEvents {
+    pub fn new() -> Self {
+        SessionEvents::default()
+    }
+
+    pub fn subscribe<S: SessionEventSubscriber + 'static>(&mut self, subscriber: S) -> &mut Self {
+        self.subscribers.push(Arc::new(subscriber));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.subscribers.is_empty()
+    }
+
+    pub(crate) fn publish(&self, session_id: &SessionId, event: SessionEvent) {
+        for subscriber in &self.subscribers {
+            subscriber.on_event(session_id, &event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc::sync_channel;
+
+    use dfx_base::session_id::SessionId;
+
+    use super::{SessionEvent, SessionEvents};
+
+    #[test]
+    fn publish_to_channel() {
+        let session_id = SessionId::new("FIX.4.4", "SENDER", "", "", "TARGET", "", "");
+        let (tx, rx) = sync_channel(1);
+        let mut events = SessionEvents::new();
+        events.subscribe(tx);
+
+        let event = SessionEvent::ResendRequestSent {
+            begin_seq_num: 100,
+            end_seq_num: 200,
+        };
+        events.publish(&session_id, event.clone());
+        // the channel is full, the event is dropped instead of blocking the session
+        events.publish(&session_id, SessionEvent::Connected);
+
+        assert_eq!(rx.try_recv().unwrap(), (session_id, event));
+        assert!(rx.try_recv().is_err());
+        assert_eq!(
+            SessionEvent::ResendRequestSent {
+                begin_seq_num: 100,
+                end_seq_num: 200
+            }
+            .to_string(),
+            "resend requested 100..200"
+        );
+    }
+}
@@ -0,0 +1,247 @@
+use std::sync::mpsc::sync_channel;
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::mpsc::SyncSender;
+use std::time::Duration;
+
+use chashmap::CHashMap;
+use dfx_base::session_id::SessionId;
+use lazy_static::lazy_static;
+
+use super::check_blocking;
+use super::send_command;
+use super::SessionError;
+
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(10);
+
+lazy_static! {
+    // Sessions disabled through SessionHandle::logout, kept across reconnects.
+    static ref DISABLED_SESSIONS: CHashMap<SessionId, ()> = CHashMap::new();
+}
+
+pub(crate) fn is_session_enabled(session_id: &SessionId) -> bool {
+    !DISABLED_SESSIONS.contains_key(session_id)
+}
+
+fn set_session_enabled(session_id: &SessionId, enabled: bool) {
+    if enabled {
+        DISABLED_SESSIONS.remove(session_id);
+    } else {
+        DISABLED_SESSIONS.insert(session_id.clone(), ());
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum SessionCommand {
+    Logon,
+    Logout(Option<String>),
+    Reconnect(Option<String>),
+    Reset(Option<String>),
+    SetNextSenderMsgSeqNum(u32),
+    SetNextTargetMsgSeqNum(u32),
+    Status,
+}
+
+/// A [`SessionCommand`] with the channel the session replies on once it has been executed.
+pub(crate) type SessionCommandRequest = (SessionCommand, SyncSender<SessionStatus>);
+
+/// Snapshot of a connected session, returned by [`SessionHandle::status`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionStatus {
+    enabled: bool,
+    sent_logon: bool,
+    received_logon: bool,
+    next_sender_msg_seq_num: u32,
+    next_target_msg_seq_num: u32,
+    heartbeat_jitter: Option<Duration>,
+    max_heartbeat_jitter: Duration,
+}
+
+impl SessionStatus {
+    pub(crate) fn new(
+        enabled: bool,
+        sent_logon: bool,
+        received_logon: bool,
+        next_sender_msg_seq_num: u32,
+        next_target_msg_seq_num: u32,
+        heartbeat_jitter: Option<Duration>,
+        max_heartbeat_jitter: Duration,
+    ) -> Self {
+        SessionStatus {
+            enabled,
+            sent_logon,
+            received_logon,
+            next_sender_msg_seq_num,
+            next_target_msg_seq_num,
+            heartbeat_jitter,
+            max_heartbeat_jitter,
+        }
+    }
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+    pub fn logged_on(&self) -> bool {
+        self.sent_logon && self.received_logon
+    }
+    pub fn sent_logon(&self) -> bool {
+        self.sent_logon
+    }
+    pub fn received_logon(&self) -> bool {
+        self.received_logon
+    }
+    pub fn next_sender_msg_seq_num(&self) -> u32 {
+        self.next_sender_msg_seq_num
+    }
+    pub fn next_target_msg_seq_num(&self) -> u32 {
+        self.next_target_msg_seq_num
+    }
+    /// How long after HeartBtInt the last heartbeat was sent, [None] before the first one.
+    pub fn heartbeat_jitter(&self) -> Option<Duration> {
+        self.heartbeat_jitter
+    }
+    /// The largest [`heartbeat_jitter`](SessionStatus::heartbeat_jitter) of this connection.
+    pub fn max_heartbeat_jitter(&self) -> Duration {
+        self.max_heartbeat_jitter
+    }
+}
+
+/// Control a session by [`SessionId`] from outside the engine.
+///
+/// Commands are executed by the session on its own thread, the calls block until the session
+/// has handled them and fail with [`SessionError::WouldBlock`] from application callbacks of the
+/// tokio or mio transports, and from the session's own callbacks on the threaded transport.
+/// Enabling and disabling also applies to sessions that are not connected: a disabled initiator
+/// does not reconnect and a disabled acceptor refuses logons.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionHandle {
+    session_id: SessionId,
+}
+
+#[allow(clippy::result_large_err)]
+impl SessionHandle {
+    pub fn new(session_id: SessionId) -> Self {
+        SessionHandle { session_id }
+    }
+
+    pub fn session_id(&self) -> &SessionId {
+        &self.session_id
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        is_session_enabled(&self.session_id)
+    }
+
+    /// `false` when the session is not connected.
+    pub fn is_logged_on(&self) -> bool {
+        self.status().map(|s| s.logged_on()).unwrap_or(false)
+    }
+
+    pub fn status(&self) -> Result<SessionStatus, SessionError> {
+        self.execute(SessionCommand::Status)
+    }
+
+    /// Enables the session, a connected initiator sends a Logon on its next tick.
+    pub fn logon(&self) -> Result<(), SessionError> {
+        set_session_enabled(&self.session_id, true);
+        match self.execute(SessionCommand::Logon) {
+            Ok(_) | Err(SessionError::NotConnected(_)) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Disables the session and, if logged on, sends a Logout with `reason` as Text.
+    pub fn logout(&self, reason: Option<&str>) -> Result<(), SessionError> {
+        set_session_enabled(&self.session_id, false);
+        match self.execute(SessionCommand::Logout(reason.map(|r| r.into()))) {
+            Ok(_) | Err(SessionError::NotConnected(_)) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Logs out and drops the connection, an initiator reconnects after its ReconnectInterval.
+    pub fn reconnect(&self, reason: Option<&str>) -> Result<(), SessionError> {
+        self.execute(SessionCommand::Reconnect(reason.map(|r| r.into())))
+            .map(|_| ())
+    }
+
+    /// Logs out, disconnects and resets the sequence numbers to 1.
+    pub fn reset(&self, reason: Option<&str>) -> Result<(), SessionError> {
+        self.execute(SessionCommand::Reset(reason.map(|r| r.into())))
+            .map(|_| ())
+    }
+
+    pub fn set_next_sender_msg_seq_num(&self, seq_num: u32) -> Result<(), SessionError> {
+        self.execute(SessionCommand::SetNextSenderMsgSeqNum(seq_num))
+            .map(|_| ())
+    }
+
+    pub fn set_next_target_msg_seq_num(&self, seq_num: u32) -> Result<(), SessionError> {
+        self.execute(SessionCommand::SetNextTargetMsgSeqNum(seq_num))
+            .map(|_| ())
+    }
+
+    fn execute(&self, command: SessionCommand) -> Result<SessionStatus, SessionError> {
+        check_blocking(&self.session_id)?;
+        let (tx, rx) = sync_channel(1);
+        send_command(&self.session_id, (command, tx))?;
+        rx.recv_timeout(COMMAND_TIMEOUT).map_err(|e| match e {
+            RecvTimeoutError::Timeout => SessionError::CommandTimeout(self.session_id.clone()),
+            RecvTimeoutError::Disconnected => SessionError::NotConnected(self.session_id.clone()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dfx_base::session_id::SessionId;
+
+    use super::SessionHandle;
+    use crate::session::SessionError;
+
+    #[test]
+    fn disconnected_session() {
+        let handle = SessionHandle::new(SessionId::new("FIX.4.4", "HANDLE", "", "", "TEST", "", ""));
+        assert!(handle.is_enabled());
+        assert!(!handle.is_logged_on());
+        assert!(matches!(handle.status(), Err(SessionError::NotConnected(_))));
+        assert!(matches!(handle.set_next_sender_msg_seq_num(10), Err(SessionError::NotConnected(_))));
+
+        assert!(handle.logout(Some("maintenance")).is_ok());
+        assert!(!handle.is_enabled());
+        assert!(handle.logon().is_ok());
+        assert!(handle.is_enabled());
+    }
+
+    #[test]
+    fn refuses_to_block_the_session_thread() {
+        use crate::session::SessionThread;
+
+        let session_id = SessionId::new("FIX.4.4", "THREAD", "", "", "TEST", "", "");
+        let other = SessionHandle::new(SessionId::new("FIX.4.4", "OTHER", "", "", "TEST", "", ""));
+        let handle = SessionHandle::new(session_id.clone());
+        {
+            let _thread = SessionThread::enter(&session_id);
+            assert!(matches!(handle.status(), Err(SessionError::WouldBlock(_))));
+            assert!(matches!(other.status(), Err(SessionError::NotConnected(_))));
+        }
+        assert!(matches!(handle.status(), Err(SessionError::NotConnected(_))));
+    }
+
+    #[test]
+    #[cfg(any(feature = "tokio", feature = "mio"))]
+    fn refuses_to_block_a_reactor_thread() {
+        use crate::session::{ReactorThread, Session};
+        use dfx_base::message::Message;
+
+        let session_id = SessionId::new("FIX.4.4", "REACTOR", "", "", "TEST", "", "");
+        let handle = SessionHandle::new(session_id.clone());
+        {
+            let _reactor = ReactorThread::enter();
+            assert!(matches!(handle.status(), Err(SessionError::WouldBlock(_))));
+            assert!(matches!(
+                Session::send_to_session_sync(&session_id, Message::default()),
+                Err(SessionError::WouldBlock(_))
+            ));
+        }
+        assert!(matches!(handle.status(), Err(SessionError::NotConnected(_))));
+    }
+}
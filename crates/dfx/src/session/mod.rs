@@ -12,3 +12,17 @@ mod responder;
 pub(crate) use responder::*;
 mod reset_range;
 pub(crate) use reset_range::*;
+mod validation;
+pub use validation::*;
+mod handle;
+pub use handle::*;
+mod events;
+pub use events::*;
+mod auth;
+pub use auth::*;
+mod certificate;
+pub use certificate::*;
+mod credentials;
+pub use credentials::*;
+mod resend;
+pub use resend::*;
@@ -0,0 +1,132 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use dfx_base::message::Message;
+use dfx_base::session_id::SessionId;
+
+/// How a stored message is answered in reply to a ResendRequest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResendAction {
+    /// Resend the message with PossDupFlag (43) and OrigSendingTime (122).
+    Resend,
+    /// Skip the message, it is covered by a SequenceReset-GapFill.
+    GapFill,
+}
+
+/// A custom resend decision, registered per MsgType in [`ResendPolicy`].
+///
+/// `age` is the time since the message was originally sent.
+pub trait ResendRule: Send + Sync {
+    fn action(&self, message: &Message, age: Duration, session_id: &SessionId) -> ResendAction;
+}
+
+impl<F> ResendRule for F
+where
+    F: Fn(&Message, Duration, &SessionId) -> ResendAction + Send + Sync,
+{
+    fn action(&self, message: &Message, age: Duration, session_id: &SessionId) -> ResendAction {
+        self(message, age, session_id)
+    }
+}
+
+/// Decides per MsgType which stored messages are resent and which are gap filled.
+///
+/// Messages without rules are resent, [`Application::to_app`](crate::session::Application::to_app)
+/// still runs for resent application messages and can refuse them with `DoNotSend`.
+#[derive(Clone, Default)]
+pub struct ResendPolicy {
+    rules: BTreeMap<String, Vec<Arc<dyn ResendRule>>>,
+}
+
+impl std::fmt::Debug for ResendPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let counts: BTreeMap<&String, usize> = self.rules.iter().map(|(k, v)| (k, v.len())).collect();
+        f.debug_struct("ResendPolicy").field("rules", &counts).finish()
+    }
+}
+
+impl ResendPolicy {
+    pub fn new() -> Self {
+        ResendPolicy::default()
+    }
+
+    /// Never resend messages of type `msg_type`.
+    pub fn gap_fill(&mut self, msg_type: &str) -> &mut Self {
+        self.add(msg_type, |_: &Message, _: Duration, _: &SessionId| ResendAction::GapFill)
+    }
+
+    /// Gap fill messages of type `msg_type` that were sent more than `max_age` ago.
+    pub fn max_age(&mut self, msg_type: &str, max_age: Duration) -> &mut Self {
+        self.add(msg_type, move |_: &Message, age: Duration, _: &SessionId| {
+            if age > max_age {
+                ResendAction::GapFill
+            } else {
+                ResendAction::Resend
+            }
+        })
+    }
+
+    /// Register `rule` for messages of type `msg_type`, the first rule asking for a gap fill wins.
+    pub fn add<R: ResendRule + 'static>(&mut self, msg_type: &str, rule: R) -> &mut Self {
+        self.rules
+            .entry(msg_type.into())
+            .or_default()
+            .push(Arc::new(rule));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    pub fn action(
+        &self,
+        message: &Message,
+        msg_type: &str,
+        age: Duration,
+        session_id: &SessionId,
+    ) -> ResendAction {
+        let gap_fill = self
+            .rules
+            .get(msg_type)
+            .map(|rules| {
+                rules
+                    .iter()
+                    .any(|rule| rule.action(message, age, session_id) == ResendAction::GapFill)
+            })
+            .unwrap_or(false);
+        if gap_fill {
+            ResendAction::GapFill
+        } else {
+            ResendAction::Resend
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use dfx_base::message::Message;
+    use dfx_base::session_id::SessionId;
+
+    use super::{ResendAction, ResendPolicy};
+
+    #[test]
+    fn gap_fill_by_msg_type_and_age() {
+        let session_id = SessionId::new("FIX.4.4", "SENDER", "", "", "TARGET", "", "");
+        let mut policy = ResendPolicy::new();
+        policy
+            .gap_fill("R")
+            .max_age("D", Duration::from_secs(30));
+
+        let message = Message::default();
+        let young = Duration::from_secs(5);
+        let old = Duration::from_secs(60);
+        assert_eq!(policy.action(&message, "R", young, &session_id), ResendAction::GapFill);
+        assert_eq!(policy.action(&message, "D", young, &session_id), ResendAction::Resend);
+        assert_eq!(policy.action(&message, "D", old, &session_id), ResendAction::GapFill);
+        assert_eq!(policy.action(&message, "8", old, &session_id), ResendAction::Resend);
+    }
+}
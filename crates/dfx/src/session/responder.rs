@@ -45,3 +45,28 @@ impl Responder for ChannelResponder {
         self.tx.send(ResponderEvent::Disconnect).unwrap()
     }
 }
+
+/// Queues messages for a reactor that writes them once the session returns.
+#[cfg(any(feature = "tokio", feature = "mio"))]
+pub(crate) struct QueuedResponder {
+    tx: Sender<ResponderEvent>,
+}
+
+#[cfg(any(feature = "tokio", feature = "mio"))]
+impl QueuedResponder {
+    pub fn new() -> (Self, Receiver<ResponderEvent>) {
+        let (tx, rx) = mpsc::channel();
+        (QueuedResponder { tx }, rx)
+    }
+}
+
+#[cfg(any(feature = "tokio", feature = "mio"))]
+impl Responder for QueuedResponder {
+    fn send(&mut self, message: String) -> bool {
+        self.tx.send(ResponderEvent::Send(message)).is_ok()
+    }
+
+    fn disconnect(&mut self) {
+        let _ = self.tx.send(ResponderEvent::Disconnect);
+    }
+}
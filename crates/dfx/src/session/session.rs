@@ -1,7 +1,12 @@
+use std::cell::{Cell, RefCell};
 use std::cmp;
 use std::cmp::min;
+use std::collections::HashSet;
 use std::sync::mpsc::sync_channel;
 use std::sync::mpsc::Receiver;
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::mpsc::TryRecvError;
+use std::sync::Arc;
 use std::sync::mpsc::SyncSender;
 use std::time::Duration;
 use std::time::Instant;
@@ -16,6 +21,7 @@ use dfx_base::fix_values::BusinessRejectReason;
 use dfx_base::fix_values::SessionRejectReason;
 use lazy_static::lazy_static;
 
+use crate::connection::LogoutOutcome;
 use crate::fields::*;
 use crate::logging::LogFactory;
 use crate::logging::Logger;
@@ -32,9 +38,29 @@ use dfx_base::fix_values::BeginString;
 use crate::message_store::MessageStoreFactory;
 use crate::session::Application;
 use crate::session::ApplicationError;
+use crate::session::is_session_enabled;
+use crate::session::CredentialError;
+use crate::session::CredentialStore;
+use crate::session::LogonAuthenticator;
+use crate::session::LogonDecision;
+use crate::session::ResendAction;
+use crate::session::ResendPolicy;
+use crate::session::LogonRequest;
+use crate::session::LogonStatus;
+use crate::session::PeerIdentity;
 use crate::session::Responder;
+use crate::session::SessionCommand;
+use crate::session::SessionCommandRequest;
+use crate::session::SessionEvent;
+use crate::session::SessionEvents;
+use crate::session::SessionTimeout;
+use crate::session::UserStatus;
+use crate::session::SessionStatus;
 use crate::session::SessionSchedule;
 use crate::session::SessionState;
+use crate::session::ValidationRuleError;
+use crate::session::ValidationRules;
+use dfx_base::message::Header;
 use dfx_base::message::Message;
 use dfx_base::message::MessageParseError;
 use dfx_base::message_factory::MessageFactory;
@@ -50,30 +76,319 @@ use super::SessionSetting;
 const _BUF_SIZE: usize = 4096;
 
 lazy_static! {
-    static ref SESSION_MAP: CHashMap<SessionId, SyncSender<Message>> = CHashMap::new();
+    static ref SESSION_MAP: CHashMap<SessionId, SessionChannels> = CHashMap::new();
+}
+
+const OUTBOUND_QUEUE_SIZE: usize = 512;
+
+/// Wakes up the reactor of a session when messages or commands are queued for it.
+pub(crate) type Wakeup = Arc<dyn Fn() + Send + Sync>;
+
+struct SessionChannels {
+    outbound: SyncSender<OutboundMessage>,
+    commands: SyncSender<SessionCommandRequest>,
+    wakeup: Option<Wakeup>,
+}
+
+impl SessionChannels {
+    fn wake(&self) {
+        if let Some(wakeup) = self.wakeup.as_ref() {
+            wakeup();
+        }
+    }
+}
+
+struct OutboundMessage {
+    message: Message,
+    reply: Option<SyncSender<Result<u32, SessionError>>>,
+}
+
+/// Reply to a ResendRequest that is still being sent.
+///
+/// Stored messages are loaded `resend_chunk_size` at a time, when the resend is paced the
+/// remaining range is continued on the next ticks.
+#[derive(Debug)]
+struct PendingResend {
+    // message that caused the resend, for LastMsgSeqNumProcessed on the gap fills
+    received_message: Message,
+    next_seq_num: u32,
+    end_seq_num: u32,
+    // NewSeqNo of the final gap fill
+    gap_fill_end_seq_num: u32,
+    // first MsgSeqNum of a gap fill that has not been sent yet, 0 when there is none
+    gap_begin_seq_num: u32,
+    current_seq_num: u32,
+    started: Instant,
+    loaded: u64,
+}
+
+/// Application version of a message on a FIXT session.
+///
+/// Taken from ApplVerID (1128) and CstmApplVerID (1129) in the header, or else from the
+/// DefaultApplVerID (1137) and DefaultCstmApplVerID (1408) exchanged on Logon.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct ApplVersion {
+    appl_ver_id: Option<String>,
+    cstm_appl_ver_id: Option<String>,
+}
+
+impl ApplVersion {
+    fn from_header(
+        header: &Header,
+        default_appl_ver_id: Option<&String>,
+        default_cstm_appl_ver_id: Option<&String>,
+    ) -> Self {
+        let cstm_appl_ver_id = header.get_string(tags::CstmApplVerID).ok();
+        match header.get_string(tags::ApplVerID) {
+            Ok(appl_ver_id) => ApplVersion {
+                appl_ver_id: Some(appl_ver_id),
+                cstm_appl_ver_id,
+            },
+            Err(_) => ApplVersion {
+                appl_ver_id: default_appl_ver_id.cloned(),
+                cstm_appl_ver_id: cstm_appl_ver_id.or_else(|| default_cstm_appl_ver_id.cloned()),
+            },
+        }
+    }
+
+    // custom application versions have their dictionary registered under the CstmApplVerID
+    fn dictionary_key(&self) -> Option<&str> {
+        self.cstm_appl_ver_id
+            .as_deref()
+            .or(self.appl_ver_id.as_deref())
+            .map(ApplVerID::from_begin_string)
+    }
+
+    fn tag(&self) -> Tag {
+        match self.cstm_appl_ver_id {
+            Some(_) => tags::CstmApplVerID,
+            None => tags::ApplVerID,
+        }
+    }
+}
+
+thread_local! {
+    // set while a tokio task or the mio reactor runs sessions on this thread
+    static ON_REACTOR: Cell<bool> = const { Cell::new(false) };
+    // the session a thread of the threaded transport runs
+    static SESSION_THREAD: RefCell<Option<SessionId>> = const { RefCell::new(None) };
+}
+
+/// Marks the current thread as the one of the threaded transport running `session_id` until
+/// dropped.
+///
+/// Only this thread drains the session's queues, waiting there for the session's reply would
+/// deadlock, so such calls fail with [`SessionError::WouldBlock`].
+pub(crate) struct SessionThread(Option<SessionId>);
+
+impl SessionThread {
+    pub(crate) fn enter(session_id: &SessionId) -> Self {
+        SessionThread(SESSION_THREAD.replace(Some(session_id.clone())))
+    }
+}
+
+impl Drop for SessionThread {
+    fn drop(&mut self) {
+        SESSION_THREAD.set(self.0.take());
+    }
+}
+
+/// Marks the current thread as running sessions of a tokio or mio transport until dropped.
+///
+/// Waiting there for a session's reply would stall every session of the reactor, or deadlock
+/// when it is the session waited for, so such calls fail with [`SessionError::WouldBlock`].
+#[cfg(any(feature = "tokio", feature = "mio"))]
+pub(crate) struct ReactorThread(bool);
+
+#[cfg(any(feature = "tokio", feature = "mio"))]
+impl ReactorThread {
+    pub(crate) fn enter() -> Self {
+        ReactorThread(ON_REACTOR.replace(true))
+    }
+}
+
+#[cfg(any(feature = "tokio", feature = "mio"))]
+impl Drop for ReactorThread {
+    fn drop(&mut self) {
+        ON_REACTOR.set(self.0);
+    }
+}
+
+#[allow(clippy::result_large_err)]
+pub(crate) fn check_blocking(session_id: &SessionId) -> Result<(), SessionError> {
+    let on_session_thread = SESSION_THREAD.with_borrow(|running| running.as_ref() == Some(session_id));
+    match ON_REACTOR.get() || on_session_thread {
+        true => Err(SessionError::WouldBlock(session_id.clone())),
+        false => Ok(()),
+    }
+}
+
+/// Result of a message queued with [`Session::try_send_to_session`].
+pub struct SendReceipt {
+    session_id: SessionId,
+    receiver: Receiver<Result<u32, SessionError>>,
+}
+
+#[allow(clippy::result_large_err)]
+impl SendReceipt {
+    /// Blocks until the session has sent the message and returns its MsgSeqNum.
+    ///
+    /// Fails with [`SessionError::WouldBlock`] when called from an application callback of the
+    /// tokio or mio transports, or of the same session on the threaded transport, use
+    /// [`SendReceipt::try_result`] there.
+    pub fn wait(&self) -> Result<u32, SessionError> {
+        check_blocking(&self.session_id)?;
+        self.receiver
+            .recv()
+            .unwrap_or_else(|_| Err(SessionError::NotConnected(self.session_id.clone())))
+    }
+
+    pub fn wait_timeout(&self, timeout: Duration) -> Result<u32, SessionError> {
+        check_blocking(&self.session_id)?;
+        match self.receiver.recv_timeout(timeout) {
+            Ok(result) => result,
+            Err(RecvTimeoutError::Timeout) => {
+                Err(SessionError::CommandTimeout(self.session_id.clone()))
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                Err(SessionError::NotConnected(self.session_id.clone()))
+            }
+        }
+    }
+
+    /// `None` while the message is still queued.
+    pub fn try_result(&self) -> Option<Result<u32, SessionError>> {
+        match self.receiver.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => {
+                Some(Err(SessionError::NotConnected(self.session_id.clone())))
+            }
+        }
+    }
 }
 
 #[allow(non_snake_case)]
+#[allow(clippy::result_large_err)]
 pub mod Session {
 
-    use super::{SessionError, SESSION_MAP};
+    use std::sync::mpsc::{sync_channel, SyncSender, TrySendError};
+
+    use super::{check_blocking, OutboundMessage, SendReceipt, SessionError, SESSION_MAP};
     use dfx_base::{message::Message, session_id::SessionId};
 
-    pub fn send_to_session(session_id: &SessionId, message: Message) -> Result<(), SessionError> {
+    fn outbound(session_id: &SessionId) -> Result<SyncSender<OutboundMessage>, SessionError> {
         match SESSION_MAP.get(session_id) {
-            Some(session) => session.send(message).unwrap(),
-            None => return Err(SessionError::SessionNotFound),
+            Some(session) => Ok(session.outbound.clone()),
+            None => Err(SessionError::SessionNotFound),
         }
+    }
+
+    fn wake(session_id: &SessionId) {
+        if let Some(session) = SESSION_MAP.get(session_id) {
+            session.wake();
+        }
+    }
+
+    /// Queues `message` for sending, blocking while the outbound queue is full.
+    ///
+    /// While the session is connected but not logged on the message is sequenced and stored
+    /// without being written, the counterparty receives it when it requests the gap.
+    pub fn send_to_session(session_id: &SessionId, message: Message) -> Result<(), SessionError> {
+        outbound(session_id)?
+            .send(OutboundMessage {
+                message,
+                reply: None,
+            })
+            .map_err(|_| SessionError::NotConnected(session_id.clone()))?;
+        wake(session_id);
         Ok(())
     }
+
+    /// Queues `message` and blocks until it has been sequenced, persisted and written.
+    ///
+    /// Returns the MsgSeqNum the message was sent with. Fails with [`SessionError::WouldBlock`]
+    /// when called from an application callback of the tokio or mio transports, whose sessions
+    /// share threads, or of the same session on the threaded transport, use
+    /// [`try_send_to_session`] there.
+    pub fn send_to_session_sync(
+        session_id: &SessionId,
+        message: Message,
+    ) -> Result<u32, SessionError> {
+        check_blocking(session_id)?;
+        let (tx, rx) = sync_channel(1);
+        outbound(session_id)?
+            .send(OutboundMessage {
+                message,
+                reply: Some(tx),
+            })
+            .map_err(|_| SessionError::NotConnected(session_id.clone()))?;
+        wake(session_id);
+        SendReceipt {
+            session_id: session_id.clone(),
+            receiver: rx,
+        }
+        .wait()
+    }
+
+    /// Queues `message` without blocking, fails with [`SessionError::QueueFull`] when the
+    /// outbound queue is full.
+    pub fn try_send_to_session(
+        session_id: &SessionId,
+        message: Message,
+    ) -> Result<SendReceipt, SessionError> {
+        let (tx, rx) = sync_channel(1);
+        outbound(session_id)?
+            .try_send(OutboundMessage {
+                message,
+                reply: Some(tx),
+            })
+            .map_err(|e| match e {
+                TrySendError::Full(_) => SessionError::QueueFull(session_id.clone()),
+                TrySendError::Disconnected(_) => SessionError::NotConnected(session_id.clone()),
+            })?;
+        wake(session_id);
+        Ok(SendReceipt {
+            session_id: session_id.clone(),
+            receiver: rx,
+        })
+    }
 }
-fn connect(session_id: &SessionId) -> Result<Receiver<Message>, InternalSessionError> {
+#[allow(clippy::result_large_err)]
+pub(crate) fn send_command(
+    session_id: &SessionId,
+    request: SessionCommandRequest,
+) -> Result<(), SessionError> {
+    let (commands, wakeup) = match SESSION_MAP.get(session_id) {
+        Some(session) => (session.commands.clone(), session.wakeup.clone()),
+        None => return Err(SessionError::NotConnected(session_id.clone())),
+    };
+    commands
+        .send(request)
+        .map_err(|_| SessionError::NotConnected(session_id.clone()))?;
+    if let Some(wakeup) = wakeup {
+        wakeup();
+    }
+    Ok(())
+}
+fn connect(
+    session_id: &SessionId,
+    wakeup: Option<Wakeup>,
+) -> Result<(Receiver<OutboundMessage>, Receiver<SessionCommandRequest>), InternalSessionError> {
     if SESSION_MAP.contains_key(session_id) {
         return Err(InternalSessionError::AlreadyConnected);
     }
-    let (tx, rx) = sync_channel(512);
-    SESSION_MAP.insert_new(session_id.clone(), tx);
-    Ok(rx)
+    let (outbound, outbound_rx) = sync_channel(OUTBOUND_QUEUE_SIZE);
+    let (commands, commands_rx) = sync_channel(16);
+    SESSION_MAP.insert_new(
+        session_id.clone(),
+        SessionChannels {
+            outbound,
+            commands,
+            wakeup,
+        },
+    );
+    Ok((outbound_rx, commands_rx))
 }
 fn disconnect_session(session_id: &SessionId) {
     SESSION_MAP.remove(&session_id);
@@ -83,35 +398,60 @@ fn disconnect_session(session_id: &SessionId) {
 pub(crate) struct ISession<App, DDP, Log, MF> {
     application: App,
     session_id: SessionId,
-    _data_dictionary_provider: DDP, // TODO: REMOVE candidate
+    data_dictionary_provider: DDP,
     schedule: SessionSchedule,
     msg_factory: MF,
     app_does_early_intercept: bool,
     sender_default_appl_ver_id: Option<String>,
-    target_default_appl_ver_id: Option<u32>,
+    target_default_appl_ver_id: Option<String>,
+    target_default_cstm_appl_ver_id: Option<String>,
     session_data_dictionary: DataDictionary,     //Option?
     application_data_dictionary: DataDictionary, //Option?
+    use_data_dictionary: bool,
     log: Log,
     state: SessionState<Log>,
     persist_messages: bool,
     reset_on_disconnect: bool,
     send_redundant_resend_requests: bool,
-    _resend_session_level_rejects: bool,
+    resend_session_level_rejects: bool,
     validate_length_and_checksum: bool,
     check_comp_id: bool,
     time_stamp_precision: DateTimeFormat,
     enable_last_msg_seq_num_processed: bool,
+    enable_next_expected_msg_seq_num: bool,
     max_messages_in_resend_request: u32,
+    resend_chunk_size: u32,
+    max_resend_messages_per_second: u32,
+    pending_resend: Option<PendingResend>,
+    // MsgSeqNum of the Logon we sent on this connection
+    sent_logon_seq_num: u32,
     send_logout_before_timeout_disconnect: bool,
     _ignore_poss_dup_resend_requests: bool,
     requires_orig_sending_time: bool,
     check_latency: bool,
     max_latency: u32,
     responder: Option<Box<dyn Responder>>,
+    validation_rules: ValidationRules,
+    events: SessionEvents,
+    authenticator: Option<Arc<dyn LogonAuthenticator>>,
+    credential_store: Option<Arc<dyn CredentialStore>>,
+    resend_policy: ResendPolicy,
+    // SessionStatus for the Logon response
+    logon_status: Option<LogonStatus>,
+    // users logged on through UserRequest
+    logged_on_users: HashSet<String>,
+    peer: PeerIdentity,
+    // Logout text for any Logon, set when the peer may not log on as this session
+    logon_rejection: Option<String>,
     refresh_on_logon: bool,
     reset_on_logon: bool,
     reset_on_logout: bool,
-    outbound: Option<Receiver<Message>>,
+    outbound: Option<Receiver<OutboundMessage>>,
+    commands: Option<Receiver<SessionCommandRequest>>,
+    wakeup: Option<Wakeup>,
+    // set once the acceptor or initiator shuts down
+    shutdown: Option<LogoutOutcome>,
+    shutdown_deadline: Option<Instant>,
 }
 
 fn add_data_dictionaries<D: DataDictionaryProvider>(provider: &mut D, settings: &SessionSetting) {
@@ -138,7 +478,7 @@ fn add_data_dictionaries<D: DataDictionaryProvider>(provider: &mut D, settings:
                     settings.validation_options().validate_user_defined_fields(),
                 );
 
-                provider.add_application_data_dictionary(appl_ver_id, dd);
+                add_application_data_dictionary(provider, appl_ver_id, dd);
 
                 let path = match options.transport_data_dictionary() {
                     Some(path) => path,
@@ -180,14 +520,26 @@ fn add_data_dictionaries<D: DataDictionaryProvider>(provider: &mut D, settings:
                 settings.validation_options().validate_user_defined_fields(),
             );
             provider.add_session_data_dictionary(settings.session_id().begin_string(), dd.clone());
-            provider.add_application_data_dictionary(
-                ApplVerID::from_begin_string(settings.session_id().begin_string()),
-                dd,
-            );
+            add_application_data_dictionary(provider, settings.session_id().begin_string(), dd);
         }
     }
 }
 
+/// Registers `dd` under the ApplVerID enum value (`9`) and the BeginString (`FIX.5.0SP2`) of
+/// `appl_ver_id`, which may be given as either.
+fn add_application_data_dictionary<D: DataDictionaryProvider>(
+    provider: &mut D,
+    appl_ver_id: &str,
+    dd: DataDictionary,
+) {
+    let appl_ver_id = ApplVerID::from_begin_string(appl_ver_id);
+    let begin_string = ApplVerID::to_begin_string(appl_ver_id);
+    if begin_string != appl_ver_id {
+        provider.add_application_data_dictionary(begin_string, dd.clone());
+    }
+    provider.add_application_data_dictionary(appl_ver_id, dd);
+}
+
 impl<App, DDP, Log, MF> ISession<App, DDP, Log, MF>
 where
     App: Application + Clone + 'static,
@@ -211,7 +563,9 @@ where
             .clone();
         let application_data_dictionary = if settings.session_id().is_fixt() {
             data_dictionary_provider
-                .get_application_data_dictionary(settings.default_appl_ver_id().unwrap())
+                .get_application_data_dictionary(ApplVerID::from_begin_string(
+                    settings.default_appl_ver_id().unwrap(),
+                ))
                 .clone()
         } else {
             session_data_dictionary.clone()
@@ -226,6 +580,12 @@ where
         );
         state.set_logon_timeout(settings.connection().logon_timeout());
         state.set_logout_timeout(settings.connection().logout_timeout());
+        state.set_test_request_delay_multiplier(
+            settings.validation_options().test_request_delay_multiplier(),
+        );
+        state.set_heart_bt_timeout_multiplier(
+            settings.validation_options().heart_bt_timeout_multiplier(),
+        );
 
         if !is_session_time(&settings.schedule().clone()) {
             // Reset("Out of SessionTime (Session construction)")
@@ -252,7 +612,7 @@ where
         ISession {
             application,
             session_id,
-            _data_dictionary_provider: data_dictionary_provider,
+            data_dictionary_provider,
             schedule: settings.schedule().clone(),
             msg_factory,
             //TODO app is IApplicationExt
@@ -261,8 +621,10 @@ where
                 .default_appl_ver_id()
                 .map(|v| ApplVerID::from_begin_string(v).into()),
             target_default_appl_ver_id: None,
+            target_default_cstm_appl_ver_id: None,
             session_data_dictionary,
             application_data_dictionary,
+            use_data_dictionary: settings.validation_options().use_data_dictionary(),
             log,
             state,
             persist_messages: !matches!(settings.persistence(), Persistence::None),
@@ -270,7 +632,7 @@ where
             send_redundant_resend_requests: settings
                 .validation_options()
                 .send_redundant_resend_requests(),
-            _resend_session_level_rejects: settings
+            resend_session_level_rejects: settings
                 .validation_options()
                 .resend_session_level_rejects(),
             validate_length_and_checksum: settings
@@ -281,9 +643,18 @@ where
             enable_last_msg_seq_num_processed: settings
                 .validation_options()
                 .enable_last_msg_seq_num_processed(),
+            enable_next_expected_msg_seq_num: settings
+                .validation_options()
+                .enable_next_expected_msg_seq_num(),
             max_messages_in_resend_request: settings
                 .validation_options()
                 .max_messages_in_resend_request(),
+            resend_chunk_size: settings.validation_options().resend_chunk_size(),
+            max_resend_messages_per_second: settings
+                .validation_options()
+                .max_resend_messages_per_second(),
+            pending_resend: None,
+            sent_logon_seq_num: 0,
             send_logout_before_timeout_disconnect: settings
                 .validation_options()
                 .send_logout_before_disconnect_from_timeout(),
@@ -294,10 +665,23 @@ where
             check_latency: settings.validation_options().check_latency(),
             max_latency: settings.validation_options().max_latency(),
             responder: None,
+            validation_rules: ValidationRules::default(),
+            events: SessionEvents::default(),
+            authenticator: None,
+            credential_store: None,
+            resend_policy: ResendPolicy::default(),
+            logon_status: None,
+            logged_on_users: HashSet::new(),
+            peer: PeerIdentity::default(),
+            logon_rejection: None,
             refresh_on_logon: settings.validation_options().refresh_on_logon(),
             reset_on_logon: settings.validation_options().reset_on_logon(),
             reset_on_logout: settings.validation_options().reset_on_logout(),
             outbound: None,
+            commands: None,
+            wakeup: None,
+            shutdown: None,
+            shutdown_deadline: None,
         }
     }
 
@@ -305,29 +689,230 @@ where
         self.responder = Some(responder);
     }
 
+    pub(crate) fn set_validation_rules(&mut self, validation_rules: ValidationRules) {
+        self.validation_rules = validation_rules;
+    }
+
+    pub(crate) fn set_events(&mut self, events: SessionEvents) {
+        self.events = events;
+    }
+
+    pub(crate) fn set_authenticator(&mut self, authenticator: Option<Arc<dyn LogonAuthenticator>>) {
+        self.authenticator = authenticator;
+    }
+
+    pub(crate) fn set_credential_store(&mut self, credential_store: Option<Arc<dyn CredentialStore>>) {
+        self.credential_store = credential_store;
+    }
+
+    pub(crate) fn set_resend_policy(&mut self, resend_policy: ResendPolicy) {
+        self.resend_policy = resend_policy;
+    }
+
+    pub(crate) fn set_peer(&mut self, peer: PeerIdentity) {
+        self.peer = peer;
+    }
+
+    /// Answers the Logon with a Logout carrying `reason` and disconnects.
+    pub(crate) fn reject_logon(&mut self, reason: String) {
+        self.logon_rejection = Some(reason);
+    }
+
+    /// Logs the new connection and the endpoint on the other side of it.
+    pub(crate) fn log_connected(&mut self) {
+        let message = match self.peer.address() {
+            Some(address) => format!("Connection succeeded {} with {address}", self.session_id),
+            None => format!("Connection succeeded {}", self.session_id),
+        };
+        self.log.on_event(&message);
+    }
+
+    /// Called whenever messages or commands are queued, `next()` then no longer waits for them.
+    #[cfg(any(feature = "tokio", feature = "mio"))]
+    pub(crate) fn set_wakeup(&mut self, wakeup: Wakeup) {
+        self.wakeup = Some(wakeup);
+    }
+
+    /// Disables the session and logs it out with `reason`, a session that isn't logged on is
+    /// disconnected right away. The Logout response is awaited for up to `LogoutTimeout`.
+    pub(crate) fn shutdown(&mut self, reason: &str) {
+        if self.shutdown.is_some() {
+            return;
+        }
+        self.state.set_is_enabled(false);
+        if self.responder.is_none() || !self.is_logged_on() {
+            self.shutdown = Some(LogoutOutcome::NotLoggedOn);
+            if self.responder.is_some() {
+                self.disconnect(reason);
+            }
+            return;
+        }
+        self.shutdown = Some(LogoutOutcome::TimedOut);
+        self.shutdown_deadline =
+            Some(Instant::now() + Duration::from_secs(self.state.logout_timeout().into()));
+        if !self.state.sent_logout() {
+            self.log.on_event("Initiated logout request");
+            self.generate_logout(Some(reason.into()), None);
+        }
+    }
+
+    /// How the session ended after [`ISession::shutdown`], `None` if it wasn't shut down.
+    pub(crate) fn shutdown_outcome(&self) -> Option<LogoutOutcome> {
+        self.shutdown
+    }
+
+    /// Flushes the message store and the logger.
+    pub(crate) fn flush(&mut self) {
+        self.state.flush();
+        self.log.flush();
+    }
+
+    fn publish(&self, event: SessionEvent) {
+        self.events.publish(&self.session_id, event);
+    }
+
     pub(crate) fn set_connected(
         &mut self,
         session_id: &SessionId,
     ) -> Result<(), InternalSessionError> {
-        let receiver = connect(&session_id);
-        self.outbound = Some(receiver?);
+        let (outbound, commands) = connect(session_id, self.wakeup.clone())?;
+        self.outbound = Some(outbound);
+        self.commands = Some(commands);
+        self.state.set_is_enabled(is_session_enabled(session_id));
+        self.publish(SessionEvent::Connected);
         Ok(())
     }
 
     pub(crate) fn set_disconnected(&mut self, session_id: &SessionId) {
         disconnect_session(session_id);
         self.outbound = None;
+        self.commands = None;
     }
 
-    fn process_outbound(&mut self) {
-        if let Some(receiver) = self.outbound.as_mut() {
-            match receiver.recv_timeout(Duration::from_millis(1)) {
-                Ok(mut msg) => {
-                    self.initialize_header(&mut msg, None);
-                    self.send_raw(msg, 0).unwrap()
+    fn process_commands(&mut self) {
+        let requests: Vec<SessionCommandRequest> = match self.commands.as_ref() {
+            Some(receiver) => receiver.try_iter().collect(),
+            None => return,
+        };
+        for (command, reply) in requests {
+            self.execute_command(command);
+            let _ = reply.send(self.status());
+        }
+    }
+
+    fn execute_command(&mut self, command: SessionCommand) {
+        match command {
+            SessionCommand::Logon => {
+                self.log.on_event("Session enabled");
+                self.state.set_is_enabled(true);
+                self.state.set_logout_reason(None);
+            }
+            SessionCommand::Logout(reason) => {
+                self.log.on_event("Session disabled");
+                self.state.set_is_enabled(false);
+                self.state.set_logout_reason(reason);
+            }
+            SessionCommand::Reconnect(reason) => {
+                let reason = reason.unwrap_or_else(|| "Reconnect requested".into());
+                if self.is_logged_on() {
+                    self.generate_logout(Some(reason.clone()), None);
                 }
+                self.disconnect(&reason);
+            }
+            SessionCommand::Reset(reason) => {
+                let reason = reason.as_deref().unwrap_or("Reset requested");
+                self.reset(Some(reason), Some(reason));
+            }
+            SessionCommand::SetNextSenderMsgSeqNum(seq_num) => {
+                self.log
+                    .on_event(format!("Next sender MsgSeqNum set to {seq_num}").as_str());
+                self.state.set_next_sender_msg_seq_num(seq_num);
+            }
+            SessionCommand::SetNextTargetMsgSeqNum(seq_num) => {
+                self.log
+                    .on_event(format!("Next target MsgSeqNum set to {seq_num}").as_str());
+                self.state.set_next_target_msg_seq_num(seq_num);
+            }
+            SessionCommand::Status => {}
+        }
+    }
+
+    fn status(&self) -> SessionStatus {
+        SessionStatus::new(
+            self.state.is_enabled(),
+            self.state.sent_logon(),
+            self.state.received_logon(),
+            self.state.next_sender_msg_seq_num(),
+            self.state.next_target_msg_seq_num(),
+            self.state.last_heartbeat_jitter(),
+            self.state.max_heartbeat_jitter(),
+        )
+    }
+
+    fn process_outbound(&mut self) {
+        // drain everything queued since the last tick, up to one queue worth
+        let wait = match self.wakeup {
+            Some(_) => Duration::ZERO,
+            None => Duration::from_millis(1),
+        };
+        let pending: Vec<OutboundMessage> = match self.outbound.as_ref() {
+            Some(receiver) => match receiver.recv_timeout(wait) {
+                Ok(first) => std::iter::once(first)
+                    .chain(receiver.try_iter().take(OUTBOUND_QUEUE_SIZE - 1))
+                    .collect(),
                 Err(_) => return,
-            };
+            },
+            None => return,
+        };
+        for outbound in pending {
+            // only fire and forget messages are kept for the counterparty's ResendRequest
+            let store_logged_out = outbound.reply.is_none();
+            let result = self.send_outbound(outbound.message, store_logged_out);
+            if let Err(e) = &result {
+                self.log
+                    .on_event(format!("Outbound message not sent: {e:?}").as_str());
+            }
+            if let Some(reply) = outbound.reply {
+                let _ = reply.send(result);
+            }
+        }
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn send_outbound(&mut self, message: Message, store_logged_out: bool) -> Result<u32, SessionError> {
+        if self.responder.is_none() {
+            return Err(SessionError::NotConnected(self.session_id.clone()));
+        }
+        let logged_on = self.is_logged_on();
+        if !logged_on && !store_logged_out {
+            return Err(SessionError::NotLoggedOn(self.session_id.clone()));
+        }
+        match self.prepare_raw(message, 0) {
+            Ok(mut message) => {
+                let msg_seq_num = message
+                    .header()
+                    .get_int(tags::MsgSeqNum)
+                    .map_err(|e| SessionError::SendFailed(self.session_id.clone(), e))?;
+                let message_string = message.to_string_mut();
+                self.persist(&message, &message_string);
+                // once persisted the message is sequenced, a failed write is recovered by a resend
+                match logged_on {
+                    true => {
+                        self.send(message_string);
+                    }
+                    false => self.log.on_event(
+                        format!("Not logged on, stored message {msg_seq_num} without sending it")
+                            .as_str(),
+                    ),
+                }
+                Ok(msg_seq_num)
+            }
+            Err(ApplicationError::DoNotSend) => {
+                Err(SessionError::DoNotSend(self.session_id.clone()))
+            }
+            Err(ApplicationError::FieldMapError(e)) => {
+                Err(SessionError::SendFailed(self.session_id.clone(), e))
+            }
         }
     }
     pub(crate) fn next(&mut self) {
@@ -335,7 +920,32 @@ where
             return;
         }
 
-        self.process_outbound();
+        self.process_commands();
+        if self.responder.is_none() {
+            return;
+        }
+
+        if self
+            .shutdown_deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            self.publish(SessionEvent::TimedOut(SessionTimeout::LogoutResponse));
+            self.disconnect("Timed out waiting for logout response");
+            return;
+        }
+
+        if self.pending_resend.is_some() {
+            if let Err(e) = self.continue_resend() {
+                self.log
+                    .on_event(format!("Error while resending messages: {e:?}").as_str());
+                self.pending_resend = None;
+            }
+        }
+        // messages queued by the application are sent after the resend so they are not
+        // received ahead of the gap
+        if self.pending_resend.is_none() {
+            self.process_outbound();
+        }
 
         if !self.is_session_time() {
             if self.state.is_initiator() {
@@ -368,18 +978,22 @@ where
             if self.state.should_send_logon() && self.is_time_to_generate_logon() {
                 if self.generate_logon() {
                     self.log.on_event("Initiated logon request");
+                    self.publish(SessionEvent::LogonSent);
                 } else {
                     self.log.on_event("Error during logon request initiation");
                 }
             } else if !self.state.should_send_logon() && self.state.logon_timed_out() {
+                self.publish(SessionEvent::TimedOut(SessionTimeout::LogonRequest));
                 self.disconnect("Timed out waiting for logon request");
             } else if self.state.sent_logon() && self.state.logon_timed_out() {
+                self.publish(SessionEvent::TimedOut(SessionTimeout::LogonResponse));
                 self.disconnect("Timed out waiting for logon response");
             }
             return;
         }
 
         if self.state.logout_timed_out() {
+            self.publish(SessionEvent::TimedOut(SessionTimeout::LogoutResponse));
             self.disconnect("Timed out waiting for logout response");
         }
 
@@ -392,6 +1006,7 @@ where
         }
 
         if self.state.timed_out() {
+            self.publish(SessionEvent::TimedOut(SessionTimeout::Heartbeat));
             if self.send_logout_before_timeout_disconnect {
                 self.generate_logout(None, None);
             }
@@ -400,8 +1015,10 @@ where
             self.generate_test_request("TEST");
             self.state
                 .set_test_request_counter(self.state.test_request_counter() + 1);
-            self.log.on_event("Sent test request TEST")
+            self.log.on_event("Sent test request TEST");
+            self.publish(SessionEvent::TestRequestSent);
         } else if self.state.need_heartbeat() {
+            self.state.record_heartbeat_jitter();
             self.generate_heartbeat();
         }
     }
@@ -454,6 +1071,11 @@ where
         self.reset_on_logon
     }
 
+    fn sends_next_expected_msg_seq_num(&self) -> bool {
+        self.enable_next_expected_msg_seq_num
+            && self.session_id.begin_string() >= BeginString::FIX44
+    }
+
     fn generate_logon(&mut self) -> bool {
         let mut logon = self
             .msg_factory
@@ -482,11 +1104,18 @@ where
         if self.should_send_reset() {
             logon.set_field_deref(ResetSeqNumFlag::new(true), None);
         }
+        if self.sends_next_expected_msg_seq_num() {
+            logon.set_tag_value(
+                tags::NextExpectedMsgSeqNum,
+                self.state.next_target_msg_seq_num() as i64,
+            );
+        }
 
         self.initialize_header(&mut logon, None);
         self.state.set_last_received_time_dt(Instant::now());
         self.state.set_test_request_counter(0);
         self.state.set_sent_logon(true);
+        self.sent_logon_seq_num = self.state.next_sender_msg_seq_num();
         self.send_raw(logon, 0).is_ok()
     }
     fn generate_logon_other(&mut self, other: &Message) -> bool {
@@ -502,6 +1131,17 @@ where
             );
         }
         logon.set_field_base(other.get_field(tags::HeartBtInt).unwrap().clone(), None);
+        if let Some(session_status) = self.logon_status.take() {
+            logon.set_tag_value(tags::SessionStatus, format!("{}", session_status.value()));
+        }
+        if self.sends_next_expected_msg_seq_num() {
+            // the Logon being answered is counted unless it opened a gap
+            let next_expected = match other.header().get_int(tags::MsgSeqNum) {
+                Ok(seq_num) if seq_num == self.state.next_target_msg_seq_num() => seq_num + 1,
+                _ => self.state.next_target_msg_seq_num(),
+            };
+            logon.set_tag_value(tags::NextExpectedMsgSeqNum, next_expected as i64);
+        }
 
         if self.enable_last_msg_seq_num_processed {
             if let Some(seq) = other.header().get_field(tags::MsgSeqNum) {
@@ -516,6 +1156,7 @@ where
         }
 
         self.initialize_header(&mut logon, None);
+        self.sent_logon_seq_num = self.state.next_sender_msg_seq_num();
         let _sent_logon = self.send_raw(logon, 0).unwrap();
         let sent_logon = true; //FIXME check if logon works?
         self.state.set_sent_logon(sent_logon);
@@ -523,6 +1164,15 @@ where
     }
 
     fn generate_logout(&mut self, reason: Option<String>, other: Option<Message>) -> bool {
+        self.generate_logout_with_status(reason, other, None)
+    }
+
+    fn generate_logout_with_status(
+        &mut self,
+        reason: Option<String>,
+        other: Option<Message>,
+        session_status: Option<LogonStatus>,
+    ) -> bool {
         let mut logout = self
             .msg_factory
             .create(&self.session_id.begin_string(), MsgType::LOGOUT)
@@ -531,6 +1181,9 @@ where
         if matches!(reason.as_ref(), Some(text) if !text.is_empty()) {
             logout.set_tag_value(tags::Text, reason.as_ref().unwrap().as_str());
         }
+        if let Some(session_status) = session_status {
+            logout.set_tag_value(tags::SessionStatus, format!("{}", session_status.value()));
+        }
         if matches!(other.as_ref(), Some(_)) && self.enable_last_msg_seq_num_processed {
             if other
                 .as_ref()
@@ -555,6 +1208,9 @@ where
         }
         let sent_logout = matches!(self.send_raw(logout, 0), Ok(v) if v);
         self.state.set_sent_logout(sent_logout);
+        if sent_logout {
+            self.publish(SessionEvent::LogoutSent { reason });
+        }
         sent_logout
     }
 
@@ -611,6 +1267,9 @@ where
             );
             responder.disconnect();
             self.responder = None;
+            self.publish(SessionEvent::Disconnected {
+                reason: reason.into(),
+            });
         } else {
             self.log.on_event(
                 format!(
@@ -636,12 +1295,34 @@ where
             self.state.reset(Some("ResetOnDisconnect"));
         }
         self.state.set_resend_range_begin_end(0, 0, None);
+        self.pending_resend = None;
+    }
+
+    fn send_raw(&mut self, message: Message, seq_num: u32) -> Result<bool, FieldMapError> {
+        match self.prepare_raw(message, seq_num) {
+            Ok(mut message) => {
+                let message_string = message.to_string_mut();
+                if 0 == seq_num {
+                    self.persist(&message, &message_string);
+                }
+                Ok(self.send(message_string))
+            }
+            Err(e) => match e {
+                ApplicationError::DoNotSend => Ok(false),
+                ApplicationError::FieldMapError(e) => Err(e),
+            },
+        }
     }
 
-    fn send_raw(&mut self, mut message: Message, seq_num: u32) -> Result<bool, FieldMapError> {
+    /// Initializes the header and passes `message` through `to_admin` or `to_app`.
+    fn prepare_raw(
+        &mut self,
+        mut message: Message,
+        seq_num: u32,
+    ) -> Result<Message, ApplicationError> {
         let msg_type = message.header().get_string(tags::MsgType)?;
         self.initialize_header(&mut message, Some(seq_num));
-        let message = if Message::is_admin_msg_type(msg_type.as_bytes()) {
+        if Message::is_admin_msg_type(msg_type.as_bytes()) {
             let mut message = self.application.to_admin(message, &self.session_id)?;
             if MsgType::LOGON == msg_type && !self.state.received_reset() {
                 let reset = if message.is_field_set(tags::ResetSeqNumFlag) {
@@ -666,20 +1347,6 @@ where
             self.application
                 .to_app(&mut message, &self.session_id)
                 .map(|_| message)
-        };
-
-        match message {
-            Ok(mut message) => {
-                let message_string = message.to_string_mut();
-                if 0 == seq_num {
-                    self.persist(&message, &message_string);
-                }
-                Ok(self.send(message_string))
-            }
-            Err(e) => match e {
-                ApplicationError::DoNotSend => Ok(false),
-                ApplicationError::FieldMapError(e) => Err(e),
-            },
         }
     }
     fn send(&mut self, message: String) -> bool {
@@ -814,81 +1481,103 @@ where
                 .reset(Some("New session (detected in next::msg(message))"))
         }
 
-        let result = self.next_msg_handler(msg);
+        if let Err(e) = self.next_msg_handler(msg) {
+            self.handle_msg_error(e);
+        }
 
-        if let Err(e) = result {
-            match e {
-                SessionHandleMessageError::InvalidMessageError(e) => {
-                    self.log.on_event(&e.message());
-                }
-                SessionHandleMessageError::MessageParseError {
-                    message,
-                    parse_error,
-                } => {
-                    self.log
-                        .on_event(format!("MessageParse Error: {parse_error:?}").as_str());
-                    let field = parse_error.as_tag();
-                    let reason = parse_error.as_session_reject();
-                    match Message::new(&message) {
-                        Ok(msg) => {
-                            self.generate_reject(msg, reason.unwrap(), field).unwrap();
-                        }
-                        Err(err) => {
-                            self.log
-                                .on_event(format!("Skipping message due to {err:?}.").as_str());
-                        }
-                    }
-                }
-                SessionHandleMessageError::TagException(msg, e) => {
-                    if let Some(msg) = e.inner() {
-                        self.log.on_event(msg.as_str());
+        self.next()
+    }
+
+    fn handle_msg_error(&mut self, e: SessionHandleMessageError) {
+        match e {
+            SessionHandleMessageError::InvalidMessageError(e) => {
+                self.log.on_event(&e.message());
+            }
+            SessionHandleMessageError::MessageParseError {
+                message,
+                parse_error,
+            } => {
+                self.log
+                    .on_event(format!("MessageParse Error: {parse_error:?}").as_str());
+                let field = parse_error.as_tag();
+                let reason = parse_error.as_session_reject();
+                match Message::new(&message) {
+                    Ok(msg) => {
+                        self.generate_reject(msg, reason.unwrap(), field).unwrap();
                     }
-                    self.generate_reject(msg, e.session_reject_reason().clone(), Some(e.field()))
-                        .unwrap();
-                }
-                SessionHandleMessageError::UnsupportedVersion {
-                    message,
-                    expected,
-                    actual,
-                } => {
-                    let result = message.header().get_string(tags::MsgType);
-                    if matches!(result, Ok(v) if MsgType::LOGOUT == v) {
-                        self.next_logout(message).unwrap();
-                    } else {
-                        self.log.on_event(
-                            format!("Received version {actual} but expected {expected}").as_str(),
-                        );
-                        self.generate_logout(
-                            Some(format!("Incorrect BeginString ({actual})")),
-                            None,
-                        );
-                        self.state.incr_next_target_msg_seq_num();
+                    Err(err) => {
+                        self.log
+                            .on_event(format!("Skipping message due to {err:?}.").as_str());
                     }
                 }
-                SessionHandleMessageError::String(s) => self.log.on_event(&s),
-                SessionHandleMessageError::FieldMapError(fm) => todo!("{fm:?}"),
-                SessionHandleMessageError::ConversionError(conv) => todo!("{conv:?}"),
-                SessionHandleMessageError::LogonReject { reason } => {
-                    let disconnect_msg = match &reason {
-                        Some(r) => format!("Application LogonReject: {r}"),
-                        None => "Application LogonReject".into(),
-                    };
-                    self.generate_logout(reason, None);
-                    self.disconnect(&disconnect_msg);
+            }
+            SessionHandleMessageError::TagException(msg, e) => {
+                if let Some(msg) = e.inner() {
+                    self.log.on_event(msg.as_str());
                 }
-                SessionHandleMessageError::UnknownMessageType { message, msg_type } => {
-                    self.log
-                        .on_event(format!("Unsupported message type: {}", msg_type).as_str());
-                    self.generate_business_message_reject(
-                        message,
-                        BusinessRejectReason::UNKNOWN_MESSAGE_TYPE(),
-                    )
+                self.generate_reject(msg, e.session_reject_reason().clone(), Some(e.field()))
                     .unwrap();
+            }
+            SessionHandleMessageError::UnsupportedVersion {
+                message,
+                expected,
+                actual,
+            } => {
+                let result = message.header().get_string(tags::MsgType);
+                if matches!(result, Ok(v) if MsgType::LOGOUT == v) {
+                    self.next_logout(message).unwrap();
+                } else {
+                    self.log.on_event(
+                        format!("Received version {actual} but expected {expected}").as_str(),
+                    );
+                    self.generate_logout(
+                        Some(format!("Incorrect BeginString ({actual})")),
+                        None,
+                    );
+                    self.state.incr_next_target_msg_seq_num();
                 }
             }
+            SessionHandleMessageError::String(s) => self.log.on_event(&s),
+            SessionHandleMessageError::FieldMapError(fm) => todo!("{fm:?}"),
+            SessionHandleMessageError::ConversionError(conv) => todo!("{conv:?}"),
+            SessionHandleMessageError::LogonReject {
+                reason,
+                session_status,
+            } => {
+                self.publish(SessionEvent::LogonRejected {
+                    reason: reason.clone(),
+                });
+                let disconnect_msg = match &reason {
+                    Some(r) => format!("Application LogonReject: {r}"),
+                    None => "Application LogonReject".into(),
+                };
+                self.generate_logout_with_status(reason, None, session_status);
+                self.disconnect(&disconnect_msg);
+            }
+            SessionHandleMessageError::UnknownMessageType { message, msg_type } => {
+                self.log
+                    .on_event(format!("Unsupported message type: {}", msg_type).as_str());
+                self.generate_business_message_reject(
+                    message,
+                    BusinessRejectReason::UNKNOWN_MESSAGE_TYPE(),
+                    None,
+                )
+                .unwrap();
+            }
+            SessionHandleMessageError::ValidationRule(message, e) => match e {
+                ValidationRuleError::SessionReject { reason, field } => {
+                    if let Err(SessionHandleMessageError::String(s)) =
+                        self.generate_reject(message, reason, field)
+                    {
+                        self.log.on_event(&s);
+                    }
+                }
+                ValidationRuleError::BusinessReject { reason, field } => {
+                    self.generate_business_message_reject(message, reason, field)
+                        .unwrap();
+                }
+            },
         }
-
-        self.next()
     }
 
     fn next_msg_handler(&mut self, msg: Vec<u8>) -> Result<(), SessionHandleMessageError> {
@@ -905,12 +1594,17 @@ where
             }
         })?;
         let mut message = self.msg_factory.create(begin_string.as_str(), msg_type)?;
+        let application_data_dictionary = self.parsing_data_dictionary(
+            &msg,
+            self.target_default_appl_ver_id.as_ref(),
+            self.target_default_cstm_appl_ver_id.as_ref(),
+        );
         message
             .from_string(
                 &msg,
                 self.validate_length_and_checksum,
                 Some(&self.session_data_dictionary),
-                Some(&self.application_data_dictionary),
+                Some(application_data_dictionary),
                 Some(&self.msg_factory),
                 false,
             )
@@ -943,21 +1637,31 @@ where
 
         if MsgType::LOGON == msg_type {
             if self.session_id.is_fixt() {
-                self.target_default_appl_ver_id = Some(message.get_int(tags::DefaultApplVerID)?);
+                self.target_default_appl_ver_id =
+                    Some(message.get_string(tags::DefaultApplVerID)?);
+                self.target_default_cstm_appl_ver_id =
+                    message.get_string(tags::DefaultCstmApplVerID).ok();
             } else {
-                self.target_default_appl_ver_id = Some(Message::get_appl_ver_id(&begin_string)?);
+                self.target_default_appl_ver_id =
+                    Some(Message::get_appl_ver_id(&begin_string)?.to_string());
             }
         }
 
         let validation_result =
             if self.session_id.is_fixt() && !Message::is_admin_msg_type(msg_type.as_bytes()) {
-                DataDictionary::validate(
-                    &message,
-                    Some(&self.session_data_dictionary),
-                    &self.application_data_dictionary,
-                    &begin_string,
-                    msg_type,
-                )
+                let version = self.inbound_appl_version(message.header());
+                match self.application_data_dictionary_for(&version) {
+                    Some(application_data_dictionary) => DataDictionary::validate(
+                        &message,
+                        Some(&self.session_data_dictionary),
+                        application_data_dictionary,
+                        &begin_string,
+                        msg_type,
+                    ),
+                    None => Err(MessageValidationError::TagException(
+                        TagException::incorrect_tag_value(version.tag()),
+                    )),
+                }
             } else {
                 DataDictionary::validate(
                     &message,
@@ -1006,6 +1710,18 @@ where
             self.next_sequence_reset(message)
         } else if MsgType::RESEND_REQUEST == msg_type {
             self.next_resend_request(message)
+        } else if self.handles_user_requests(msg_type) {
+            self.next_user_request(message)
+        } else if MsgType::REJECT == msg_type {
+            let event = SessionEvent::RejectReceived {
+                ref_seq_num: message.get_int(tags::RefSeqNum).ok(),
+                text: message.get_string(tags::Text).ok(),
+            };
+            if self.verify(message)?.is_some() {
+                self.publish(event);
+                self.state.incr_next_target_msg_seq_num();
+            }
+            Ok(())
         } else if self.verify(message)?.is_none() {
             Ok(())
         } else {
@@ -1035,7 +1751,9 @@ where
                     if msg_type == MsgType::LOGON || msg_type == MsgType::RESEND_REQUEST {
                         self.state.incr_next_target_msg_seq_num();
                     } else {
-                        self.handle_msg(msg, &begin_string, &msg_type).unwrap();
+                        if let Err(e) = self.handle_msg(msg, &begin_string, &msg_type) {
+                            self.handle_msg_error(e);
+                        }
                     }
                 }
                 e => todo!("session::next_queued {e:?}"),
@@ -1083,6 +1801,64 @@ where
             return Ok(());
         }
 
+        let next_expected_msg_seq_num = if self.sends_next_expected_msg_seq_num() {
+            logon.get_int(tags::NextExpectedMsgSeqNum).ok()
+        } else {
+            None
+        };
+        if let Some(next_expected) = next_expected_msg_seq_num {
+            let next_sender = self.state.next_sender_msg_seq_num();
+            if next_expected > next_sender {
+                return Err(LogonReject {
+                    reason: Some(format!(
+                        "NextExpectedMsgSeqNum too high, expecting at most {next_sender} but received {next_expected}"
+                    )),
+                }
+                .into());
+            }
+        }
+
+        if !self.state.is_initiator() && !self.state.is_enabled() {
+            return Err(LogonReject {
+                reason: Some("Session is disabled".into()),
+            }
+            .into());
+        }
+
+        if !self.state.is_initiator() {
+            if let Some(reason) = self.logon_rejection.clone() {
+                return Err(LogonReject { reason: Some(reason) }.into());
+            }
+            if let Err(e) = self.check_credentials(&logon) {
+                return Err(SessionHandleMessageError::LogonReject {
+                    reason: Some(format!("{e}")),
+                    session_status: Some(e.logon_status()),
+                });
+            }
+            let session_status = match self.authenticate(&logon) {
+                LogonDecision::Accept => None,
+                LogonDecision::Reject { text } => Some((text, None)),
+                LogonDecision::RequirePasswordChange { text } => {
+                    Some((text, Some(LogonStatus::PasswordExpired)))
+                }
+            };
+            if let Some((reason, session_status)) = session_status {
+                return Err(SessionHandleMessageError::LogonReject {
+                    reason,
+                    session_status,
+                });
+            }
+            match self.change_password(&logon) {
+                Ok(logon_status) => self.logon_status = logon_status,
+                Err(e) => {
+                    return Err(SessionHandleMessageError::LogonReject {
+                        reason: Some(format!("{e}")),
+                        session_status: Some(e.logon_status()),
+                    })
+                }
+            }
+        }
+
         self.state.set_received_logon(true);
         self.log().on_event("Received logon");
 
@@ -1096,24 +1872,221 @@ where
         self.state.set_sent_reset(false);
         self.state.set_received_reset(false);
 
+        if let Some(next_expected) = next_expected_msg_seq_num {
+            self.resend_next_expected(&logon, next_expected)?;
+        }
+
         let msg_seq_num = logon.header().get_int(tags::MsgSeqNum)?;
         if self.is_target_too_high(msg_seq_num) && !received_reset {
-            self.do_target_too_high(logon, msg_seq_num)?;
+            if next_expected_msg_seq_num.is_some() {
+                // our Logon carried NextExpectedMsgSeqNum, the counterparty resends the gap
+                // without a ResendRequest
+                self.log.on_event(
+                    format!(
+                        "MsgSeqNum too high, expecting {} but received {}, waiting for the counterparty to resend",
+                        self.state.next_target_msg_seq_num(),
+                        msg_seq_num
+                    )
+                    .as_str(),
+                );
+                self.publish(SessionEvent::SequenceGap {
+                    expected: self.state.next_target_msg_seq_num(),
+                    received: msg_seq_num,
+                });
+                self.state.queue(msg_seq_num, logon);
+            } else {
+                self.do_target_too_high(logon, msg_seq_num)?;
+            }
         } else {
             self.state.incr_next_target_msg_seq_num()
         }
 
         if self.is_logged_on() {
             self.application.on_logon(&self.session_id)?;
+            self.publish(SessionEvent::LoggedOn);
         }
         Ok(())
     }
+    /// Resends everything from the counterparty's NextExpectedMsgSeqNum, gap filling our Logon.
+    ///
+    /// Messages an initiator queued while waiting for the Logon reply were stored without being
+    /// sent, they follow our Logon and are resent as well.
+    #[allow(clippy::result_large_err)]
+    fn resend_next_expected(
+        &mut self,
+        logon: &Message,
+        next_expected: u32,
+    ) -> Result<(), SessionHandleMessageError> {
+        let last_seq_num = self.state.next_sender_msg_seq_num().saturating_sub(1);
+        // a Logon that is the last message was just sent, it isn't missing
+        let end_seq_num = match last_seq_num == self.sent_logon_seq_num {
+            true => last_seq_num.saturating_sub(1),
+            false => last_seq_num,
+        };
+        if next_expected > end_seq_num {
+            return Ok(());
+        }
+        self.log.on_event(
+            format!(
+                "Received NextExpectedMsgSeqNum {}, resending {} to {}",
+                next_expected, next_expected, end_seq_num
+            )
+            .as_str(),
+        );
+        self.resend_messages(logon, next_expected, end_seq_num)
+    }
+
+    /// Checks Username and Password against the credential store, an expired password is
+    /// accepted when the Logon carries a NewPassword.
+    fn check_credentials(&self, logon: &Message) -> Result<(), CredentialError> {
+        let store = match self.credential_store.as_ref() {
+            Some(store) => store,
+            None => return Ok(()),
+        };
+        let username = logon
+            .get_string(tags::Username)
+            .map_err(|_| CredentialError::UnknownUser)?;
+        let password = logon.get_string(tags::Password).unwrap_or_default();
+        match store.authenticate(&username, &password) {
+            Err(CredentialError::PasswordExpired) if logon.is_field_set(tags::NewPassword) => Ok(()),
+            result => result,
+        }
+    }
+
+    /// Applies the NewPassword of an accepted Logon.
+    fn change_password(&self, logon: &Message) -> Result<Option<LogonStatus>, CredentialError> {
+        let store = match self.credential_store.as_ref() {
+            Some(store) => store,
+            None => return Ok(None),
+        };
+        let new_password = match logon.get_string(tags::NewPassword) {
+            Ok(new_password) => new_password,
+            Err(_) => return Ok(None),
+        };
+        let username = logon
+            .get_string(tags::Username)
+            .map_err(|_| CredentialError::UnknownUser)?;
+        let password = logon.get_string(tags::Password).unwrap_or_default();
+        store.change_password(&username, &password, &new_password)?;
+        self.log
+            .on_event(format!("Password changed for {username}").as_str());
+        Ok(Some(LogonStatus::PasswordChanged))
+    }
+
+    fn handles_user_requests(&self, msg_type: &str) -> bool {
+        MsgType::USER_REQUEST == msg_type
+            && self.credential_store.is_some()
+            && !self.state.is_initiator()
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn next_user_request(&mut self, message: Message) -> Result<(), SessionHandleMessageError> {
+        let message = match self.verify(message)? {
+            Some(message) => message,
+            None => return Ok(()),
+        };
+        let store = self.credential_store.clone().unwrap();
+        let (user_request_id, username, user_request_type) = match (
+            message.get_string(tags::UserRequestID),
+            message.get_string(tags::Username),
+            message.get_int(tags::UserRequestType),
+        ) {
+            (Ok(user_request_id), Ok(username), Ok(user_request_type)) => {
+                (user_request_id, username, user_request_type)
+            }
+            (Err(_), _, _) => return self.reject_user_request(message, tags::UserRequestID),
+            (_, Err(_), _) => return self.reject_user_request(message, tags::Username),
+            (_, _, Err(_)) => return self.reject_user_request(message, tags::UserRequestType),
+        };
+        let password = message.get_string(tags::Password).unwrap_or_default();
+
+        let (user_status, text) = match user_request_type {
+            // LogOnUser
+            1 => match store.authenticate(&username, &password) {
+                Ok(()) => {
+                    self.logged_on_users.insert(username.clone());
+                    (UserStatus::LoggedIn, None)
+                }
+                Err(e) => (e.user_status(), Some(format!("{e}"))),
+            },
+            // LogOffUser
+            2 => {
+                self.logged_on_users.remove(&username);
+                (UserStatus::NotLoggedIn, None)
+            }
+            // ChangePasswordForUser
+            3 => {
+                let new_password = message.get_string(tags::NewPassword).unwrap_or_default();
+                match store.change_password(&username, &password, &new_password) {
+                    Ok(()) => (UserStatus::PasswordChanged, None),
+                    Err(e) => (e.user_status(), Some(format!("{e}"))),
+                }
+            }
+            // RequestIndividualUserStatus
+            4 => {
+                if self.logged_on_users.contains(&username) {
+                    (UserStatus::LoggedIn, None)
+                } else if store.contains_user(&username) {
+                    (UserStatus::NotLoggedIn, None)
+                } else {
+                    (UserStatus::UserNotRecognised, None)
+                }
+            }
+            other => (
+                UserStatus::Other,
+                Some(format!("Unsupported UserRequestType ({other})")),
+            ),
+        };
+
+        let mut response = self
+            .msg_factory
+            .create(self.session_id.begin_string(), MsgType::USER_RESPONSE)?;
+        response.set_tag_value(tags::UserRequestID, user_request_id.as_str());
+        response.set_tag_value(tags::Username, username.as_str());
+        response.set_tag_value(tags::UserStatus, format!("{}", user_status.value()));
+        if let Some(text) = text {
+            response.set_tag_value(tags::UserStatusText, text.as_str());
+        }
+        self.state.incr_next_target_msg_seq_num();
+        self.initialize_header(&mut response, None);
+        self.send_raw(response, 0)?;
+        Ok(())
+    }
+
+    /// Rejects a UserRequest without `tag` or with an invalid value for it.
+    #[allow(clippy::result_large_err)]
+    fn reject_user_request(
+        &mut self,
+        message: Message,
+        tag: Tag,
+    ) -> Result<(), SessionHandleMessageError> {
+        let reason = match message.is_field_set(tag) {
+            true => SessionRejectReason::INCORRECT_DATA_FORMAT_FOR_VALUE(),
+            false => SessionRejectReason::REQUIRED_TAG_MISSING(),
+        };
+        self.generate_reject(message, reason, Some(tag))?;
+        Ok(())
+    }
+
+    fn authenticate(&self, logon: &Message) -> LogonDecision {
+        match self.authenticator.as_ref() {
+            Some(authenticator) => {
+                authenticator.authenticate(&LogonRequest::new(&self.session_id, logon, &self.peer))
+            }
+            None => LogonDecision::Accept,
+        }
+    }
+
     fn next_logout(&mut self, logout: Message) -> Result<(), SessionHandleMessageError> {
         let logout = self.verify_opt(logout, false, false)?;
         if logout.is_none() {
             return Ok(());
         }
         let logout = logout.unwrap();
+        self.publish(SessionEvent::LogoutReceived);
+        if self.shutdown.is_some() {
+            self.shutdown = Some(LogoutOutcome::LoggedOut);
+        }
 
         let reason = if !self.state.sent_logout() {
             let reason = "Received logout request";
@@ -1165,6 +2138,10 @@ where
 
         if message.is_field_set(tags::NewSeqNo) {
             let new_seq_no = message.get_int(tags::NewSeqNo)?;
+            self.publish(SessionEvent::SequenceResetReceived {
+                new_seq_num: new_seq_no,
+                gap_fill: is_gap_fill,
+            });
             self.log.on_event(
                 format!(
                     "Received SequenceReset FROM: {} TO: {}",
@@ -1192,101 +2169,188 @@ where
             if !(self._ignore_poss_dup_resend_requests
                 && resend_request.header().is_field_set(tags::PossDupFlag))
             {
-                let mut msg_seq_num;
                 let beg_seq_no = resend_request.get_int(tags::BeginSeqNo)?;
                 let mut end_seq_no = resend_request.get_int(tags::EndSeqNo)?;
                 self.log.on_event(
                     format!("Got resend request from {beg_seq_no} to {end_seq_no}").as_str(),
                 );
+                self.publish(SessionEvent::ResendRequestReceived {
+                    begin_seq_num: beg_seq_no,
+                    end_seq_num: end_seq_no,
+                });
 
                 if end_seq_no == 999999 || end_seq_no == 0 {
                     end_seq_no = self.state.next_sender_msg_seq_num() - 1;
                 }
+                self.resend_messages(&resend_request, beg_seq_no, end_seq_no)?;
+            }
+            let msg_seq_num = resend_request.header().get_int(tags::MsgSeqNum)?;
+            if !self.is_target_too_high(msg_seq_num) && !self.is_target_too_low(msg_seq_num) {
+                self.state.incr_next_target_msg_seq_num();
+            }
+            Ok(())
+        } else {
+            Ok(())
+        }
+    }
 
-                if !self.persist_messages {
-                    end_seq_no += 1;
-                    let next = self.state.next_sender_msg_seq_num();
-                    if end_seq_no > next {
-                        end_seq_no = next;
-                    }
-                    self.generate_sequence_reset(&resend_request, beg_seq_no, end_seq_no)?;
-                    msg_seq_num = resend_request.header().get_int(tags::MsgSeqNum)?;
-                    if !self.is_target_too_high(msg_seq_num) && !self.is_target_too_low(msg_seq_num)
-                    {
-                        self.state.incr_next_target_msg_seq_num();
-                    }
-                    return Ok(());
-                }
+    /// Resends the stored messages from `beg_seq_no` to `end_seq_no`, replacing session level
+    /// messages and messages the application refuses to resend with a SequenceReset-GapFill.
+    ///
+    /// A resend that is still running is abandoned, the new range is sent from the start.
+    #[allow(clippy::result_large_err)]
+    fn resend_messages(
+        &mut self,
+        received_message: &Message,
+        beg_seq_no: u32,
+        end_seq_no: u32,
+    ) -> Result<(), SessionHandleMessageError> {
+        let next_seq_num = self.state.next_sender_msg_seq_num();
+        let gap_fill_end_seq_num = min(end_seq_no + 1, next_seq_num);
+        if !self.persist_messages {
+            self.generate_sequence_reset(received_message, beg_seq_no, gap_fill_end_seq_num)?;
+            return Ok(());
+        }
 
-                let mut current = beg_seq_no;
-                let mut begin = 0;
-                for msg_str in self.state.get_messages(beg_seq_no, end_seq_no) {
-                    let mut msg = Message::default();
-                    msg.from_string(
-                        msg_str.as_bytes(),
-                        true,
-                        Some(&self.session_data_dictionary),
-                        Some(&self.application_data_dictionary),
-                        Some(&self.msg_factory),
-                        false,
-                    )
-                    .map_err(|mp| {
-                        SessionHandleMessageError::MessageParseError {
-                            message: msg_str.into_bytes(),
-                            parse_error: mp,
-                        }
-                    })?;
-                    msg_seq_num = msg.header().get_int(tags::MsgSeqNum)?;
+        if let Some(pending) = self.pending_resend.take() {
+            self.log.on_event(
+                format!(
+                    "Abandoning resend of {} to {}",
+                    pending.next_seq_num, pending.end_seq_num
+                )
+                .as_str(),
+            );
+        }
+        self.pending_resend = Some(PendingResend {
+            received_message: received_message.clone(),
+            next_seq_num: beg_seq_no,
+            end_seq_num: end_seq_no,
+            gap_fill_end_seq_num,
+            gap_begin_seq_num: 0,
+            current_seq_num: beg_seq_no,
+            started: Instant::now(),
+            loaded: 0,
+        });
+        self.continue_resend()
+    }
+
+    /// Sends the next chunks of the pending resend, as many as MaxResendMessagesPerSecond allows.
+    #[allow(clippy::result_large_err)]
+    fn continue_resend(&mut self) -> Result<(), SessionHandleMessageError> {
+        let mut resend = match self.pending_resend.take() {
+            Some(resend) => resend,
+            None => return Ok(()),
+        };
 
-                    if current != msg_seq_num && begin == 0 {
-                        begin = current;
-                    }
+        while resend.next_seq_num <= resend.end_seq_num {
+            let allowed = if self.max_resend_messages_per_second == 0 {
+                u64::MAX
+            } else {
+                let elapsed = resend.started.elapsed().as_millis() as u64;
+                elapsed * self.max_resend_messages_per_second as u64 / 1000 + 1
+            };
+            let budget = allowed.saturating_sub(resend.loaded);
+            if budget == 0 {
+                self.pending_resend = Some(resend);
+                return Ok(());
+            }
+            let chunk_size = min(self.resend_chunk_size as u64, budget) as u32;
+            let chunk_end = min(
+                resend.end_seq_num,
+                resend.next_seq_num.saturating_add(chunk_size - 1),
+            );
+            self.resend_chunk(&mut resend, chunk_end)?;
+            resend.loaded += (chunk_end - resend.next_seq_num + 1) as u64;
+            resend.next_seq_num = chunk_end + 1;
+        }
 
-                    if msg.is_admin()
-                        && !(self._resend_session_level_rejects
-                            && msg.header().get_string(tags::MsgType)? == MsgType::REJECT)
-                    {
-                        if begin == 0 {
-                            begin = msg_seq_num;
-                        }
-                    } else {
-                        self.initialize_resend_fields(&mut msg);
-                        let approved = self.resend_approved(msg);
-                        if let Some(mut msg) = approved {
-                            if begin != 0 {
-                                self.generate_sequence_reset(&resend_request, begin, msg_seq_num)?;
-                            }
-
-                            self.send(msg.to_string_mut());
-                            begin = 0;
-                        } else {
-                            continue;
-                        }
-                    }
-                    current = msg_seq_num + 1;
+        let begin = if resend.gap_begin_seq_num == 0 {
+            resend.current_seq_num
+        } else {
+            resend.gap_begin_seq_num
+        };
+        if resend.gap_fill_end_seq_num > begin {
+            self.generate_sequence_reset(
+                &resend.received_message,
+                begin,
+                resend.gap_fill_end_seq_num,
+            )?;
+        }
+        Ok(())
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn resend_chunk(
+        &mut self,
+        resend: &mut PendingResend,
+        chunk_end: u32,
+    ) -> Result<(), SessionHandleMessageError> {
+        for msg_str in self.state.get_messages(resend.next_seq_num, chunk_end) {
+            let mut msg = Message::default();
+            let application_data_dictionary = self.parsing_data_dictionary(
+                msg_str.as_bytes(),
+                self.sender_default_appl_ver_id.as_ref(),
+                None,
+            );
+            msg.from_string(
+                msg_str.as_bytes(),
+                true,
+                Some(&self.session_data_dictionary),
+                Some(application_data_dictionary),
+                Some(&self.msg_factory),
+                false,
+            )
+            .map_err(|mp| {
+                SessionHandleMessageError::MessageParseError {
+                    message: msg_str.into_bytes(),
+                    parse_error: mp,
                 }
+            })?;
+            let msg_seq_num = msg.header().get_int(tags::MsgSeqNum)?;
 
-                let next_seq_num = self.state.next_sender_msg_seq_num();
-                end_seq_no += 1;
-                if end_seq_no > next_seq_num {
-                    end_seq_no = next_seq_num;
+            if resend.current_seq_num != msg_seq_num && resend.gap_begin_seq_num == 0 {
+                resend.gap_begin_seq_num = resend.current_seq_num;
+            }
+
+            let msg_type = msg.header().get_string(tags::MsgType)?;
+            let is_admin = msg.is_admin();
+            if is_admin && !(self.resend_session_level_rejects && msg_type == MsgType::REJECT) {
+                if resend.gap_begin_seq_num == 0 {
+                    resend.gap_begin_seq_num = msg_seq_num;
                 }
-                if begin == 0 {
-                    begin = current;
+            } else {
+                if self.resend_action(&msg, &msg_type) == ResendAction::GapFill {
+                    self.log.on_event(
+                        format!("Gap filling {msg_type} {msg_seq_num} instead of resending")
+                            .as_str(),
+                    );
+                    continue;
                 }
+                self.initialize_resend_fields(&mut msg);
+                // session level rejects are not handed to the application
+                let approved = if is_admin {
+                    Some(msg)
+                } else {
+                    self.resend_approved(msg)
+                };
+                if let Some(mut msg) = approved {
+                    if resend.gap_begin_seq_num != 0 {
+                        self.generate_sequence_reset(
+                            &resend.received_message,
+                            resend.gap_begin_seq_num,
+                            msg_seq_num,
+                        )?;
+                    }
 
-                if end_seq_no > begin {
-                    self.generate_sequence_reset(&resend_request, begin, end_seq_no)?;
+                    self.send(msg.to_string_mut());
+                    resend.gap_begin_seq_num = 0;
+                } else {
+                    continue;
                 }
             }
-            let msg_seq_num = resend_request.header().get_int(tags::MsgSeqNum)?;
-            if !self.is_target_too_high(msg_seq_num) && !self.is_target_too_low(msg_seq_num) {
-                self.state.incr_next_target_msg_seq_num();
-            }
-            Ok(())
-        } else {
-            Ok(())
+            resend.current_seq_num = msg_seq_num + 1;
         }
+        Ok(())
     }
 
     /// This will pass the message into the from_admin / from_app methods from the Application
@@ -1343,13 +2407,15 @@ where
                 } else if let Some(chunk) = range.chunk_end_seq_num {
                     if msg_seq_num >= chunk {
                         self.log.on_event(format!("Chunked ResendRequest for messages FROM: {} TO: {} has been satisfied.", range.begin_seq_num, chunk).as_str());
+                        // a gap fill may have skipped past the end of the chunk
+                        let new_chunk_begin_seq_no = cmp::max(chunk, msg_seq_num) + 1;
                         let new_chunk_end_seq_no = cmp::min(
                             range.end_seq_num,
-                            chunk + self.max_messages_in_resend_request,
+                            new_chunk_begin_seq_no + self.max_messages_in_resend_request - 1,
                         );
                         self.generate_resend_request_range(
                             message.header().get_string(tags::BeginString)?,
-                            chunk + 1,
+                            new_chunk_begin_seq_no,
                             new_chunk_end_seq_no,
                         )?;
                         self.state
@@ -1376,9 +2442,16 @@ where
         self.state.set_last_received_time_dt(Instant::now());
         self.state.set_test_request_counter(0);
 
+        if let Err(e) = self
+            .validation_rules
+            .validate(&message, &msg_type, &self.session_id)
+        {
+            return Err(SessionHandleMessageError::ValidationRule(message, e));
+        }
+
         if Message::is_admin_msg_type(msg_type.as_bytes()) {
             self.application.from_admin(&message, &self.session_id)?
-        } else {
+        } else if !self.handles_user_requests(&msg_type) {
             self.application.from_app(&message, &self.session_id)?
         }
         Ok(Some(message))
@@ -1413,6 +2486,10 @@ where
             )
             .as_str(),
         );
+        self.publish(SessionEvent::SequenceGap {
+            expected: self.state.next_target_msg_seq_num(),
+            received: msg_seq_num,
+        });
         self.state.queue(msg_seq_num, msg);
 
         if self.state.resend_requested() {
@@ -1488,6 +2565,10 @@ where
                 )
                 .as_str(),
             );
+            self.publish(SessionEvent::ResendRequestSent {
+                begin_seq_num: start_seq_num,
+                end_seq_num,
+            });
             Ok(true)
         } else {
             self.log.on_event(
@@ -1510,6 +2591,10 @@ where
     ) -> Result<bool, SessionHandleMessageError> {
         self.log
             .on_event(format!("Reject: {}", reason.reason()).as_str());
+        self.publish(SessionEvent::RejectSent {
+            ref_seq_num: message.header().get_int(tags::MsgSeqNum).ok(),
+            reason: reason.reason().into(),
+        });
         let field = field.unwrap_or(0);
 
         let begin_string = &self.session_id.begin_string();
@@ -1540,6 +2625,12 @@ where
             if msg_type.len() > 0 {
                 reject.set_tag_value(tags::RefMsgType, &msg_type);
             }
+            if self.session_id.is_fixt()
+                && !msg_type.is_empty()
+                && !Message::is_admin_msg_type(msg_type.as_bytes())
+            {
+                self.set_ref_appl_version(&mut reject, message.header());
+            }
             if (&BeginString::FIX42 == begin_string
                 && reason.tag() <= SessionRejectReason::INVALID_MSGTYPE().tag()/*.value*/)
                 || begin_string > &BeginString::FIX42
@@ -1760,6 +2851,90 @@ where
         self.insert_sending_time(msg);
     }
 
+    fn inbound_appl_version(&self, header: &Header) -> ApplVersion {
+        ApplVersion::from_header(
+            header,
+            self.target_default_appl_ver_id.as_ref(),
+            self.target_default_cstm_appl_ver_id.as_ref(),
+        )
+    }
+
+    /// Dictionary of the application version, [None] if the provider has none for it.
+    fn application_data_dictionary_for(&self, version: &ApplVersion) -> Option<&DataDictionary> {
+        if !self.use_data_dictionary {
+            return Some(&self.application_data_dictionary);
+        }
+        match version.dictionary_key() {
+            Some(key) => self
+                .data_dictionary_provider
+                .find_application_data_dictionary(key),
+            None => Some(&self.application_data_dictionary),
+        }
+    }
+
+    /// Application dictionary used to parse `msg`.
+    ///
+    /// On FIXT sessions the header is read first to find the ApplVerID, unknown versions are
+    /// parsed with the default dictionary and rejected during validation.
+    fn parsing_data_dictionary(
+        &self,
+        msg: &[u8],
+        default_appl_ver_id: Option<&String>,
+        default_cstm_appl_ver_id: Option<&String>,
+    ) -> &DataDictionary {
+        if !self.session_id.is_fixt() || !self.use_data_dictionary {
+            return &self.application_data_dictionary;
+        }
+        match Message::identify_type(msg) {
+            Ok(msg_type) if !Message::is_admin_msg_type(msg_type.as_bytes()) => {}
+            _ => return &self.application_data_dictionary,
+        }
+        let mut header = Message::default();
+        let parsed = header.from_string(
+            msg,
+            false,
+            Some(&self.session_data_dictionary),
+            None,
+            Some(&self.msg_factory),
+            true,
+        );
+        if parsed.is_err() {
+            return &self.application_data_dictionary;
+        }
+        let version = ApplVersion::from_header(
+            header.header(),
+            default_appl_ver_id,
+            default_cstm_appl_ver_id,
+        );
+        self.application_data_dictionary_for(&version)
+            .unwrap_or(&self.application_data_dictionary)
+    }
+
+    /// RefApplVerID (1130) and RefCstmApplVerID (1131) of a rejected application message that
+    /// carried its own ApplVerID, messages on the Logon default are rejected without them.
+    fn set_ref_appl_version(&self, reject: &mut Message, header: &Header) {
+        if let Ok(appl_ver_id) = header.get_string(tags::ApplVerID) {
+            reject.set_tag_value(tags::RefApplVerID, appl_ver_id);
+        }
+        if let Ok(cstm_appl_ver_id) = header.get_string(tags::CstmApplVerID) {
+            reject.set_tag_value(tags::RefCstmApplVerID, cstm_appl_ver_id);
+        }
+    }
+
+    fn resend_action(&self, msg: &Message, msg_type: &str) -> ResendAction {
+        if self.resend_policy.is_empty() {
+            return ResendAction::Resend;
+        }
+        let age = msg
+            .header()
+            .get_datetime(tags::SendingTime)
+            .ok()
+            .and_then(|sending_time| (Utc::now() - sending_time).to_std().ok())
+            .unwrap_or_default();
+        self.resend_policy
+            .action(msg, msg_type, age, &self.session_id)
+    }
+
     fn resend_approved(&mut self, mut msg: Message) -> Option<Message> {
         match self.application.to_app(&mut msg, &self.session_id) {
             Err(ApplicationError::DoNotSend) => None,
@@ -1772,6 +2947,7 @@ where
         &mut self,
         message: Message,
         business_reject_reason: BusinessRejectReason,
+        field: Option<Tag>,
     ) -> Result<(), SessionHandleMessageError> {
         let msg_type = message.header().get_string(tags::MsgType)?;
         let msg_seq_num = message.header().get_int(tags::MsgSeqNum)?;
@@ -1781,6 +2957,9 @@ where
                 self.session_id.begin_string(),
                 MsgType::BUSINESS_MESSAGE_REJECT,
             )?;
+            if self.session_id.is_fixt() {
+                self.set_ref_appl_version(&mut reject, message.header());
+            }
             reject.set_tag_value(tags::RefMsgType, msg_type);
             reject.set_tag_value(
                 tags::BusinessRejectReason,
@@ -1801,11 +2980,20 @@ where
 
         self.initialize_header(&mut reject, None);
         reject.set_tag_value(tags::RefSeqNum, format!("{}", msg_seq_num));
+        if let Some(field) = field {
+            if self.session_id.begin_string() >= BeginString::FIX42 {
+                reject.set_tag_value(tags::RefTagID, format!("{}", field));
+            }
+        }
         self.state.incr_next_target_msg_seq_num();
 
         reject.set_tag_value(tags::Text, reason);
         self.log
             .on_event("Reject sent for Message: {msg_seq_num} Reason:{reason}");
+        self.publish(SessionEvent::BusinessRejectSent {
+            ref_seq_num: msg_seq_num,
+            reason: reason.into(),
+        });
         self.send_raw(reject, 0)?;
         Ok(())
     }
@@ -1820,6 +3008,14 @@ pub enum SessionError {
     NotConnected(SessionId),
     NotLoggedOn(SessionId),
     SessionNotFound,
+    CommandTimeout(SessionId),
+    QueueFull(SessionId),
+    /// Waiting for the session's reply would block a thread running sessions of the tokio or
+    /// mio transports, or the thread running this session on the threaded transport.
+    WouldBlock(SessionId),
+    /// The application vetoed the message in `to_app`.
+    DoNotSend(SessionId),
+    SendFailed(SessionId, FieldMapError),
 }
 
 #[derive(Debug, Clone)]
@@ -1850,10 +3046,13 @@ pub(crate) enum SessionHandleMessageError {
     },
     // DataDictionaryError
     TagException(Message, TagException),
+    // ValidationRules::validate
+    ValidationRule(Message, ValidationRuleError),
     //TODO?
     String(String),
     LogonReject {
         reason: Option<String>,
+        session_status: Option<LogonStatus>,
     },
     // Handle same way?
     FieldMapError(FieldMapError),
@@ -1882,7 +3081,10 @@ impl From<String> for SessionHandleMessageError {
 }
 impl From<LogonReject> for SessionHandleMessageError {
     fn from(e: LogonReject) -> Self {
-        SessionHandleMessageError::LogonReject { reason: e.reason }
+        SessionHandleMessageError::LogonReject {
+            reason: e.reason,
+            session_status: None,
+        }
     }
 }
 impl From<FieldMapError> for SessionHandleMessageError {
@@ -1905,3 +3107,38 @@ impl From<FromAppError> for SessionHandleMessageError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use dfx_base::data_dictionary_provider::{DataDictionaryProvider, DefaultDataDictionaryProvider};
+
+    use super::add_data_dictionaries;
+    use crate::session::SessionSettings;
+
+    #[test]
+    fn registers_application_dictionary_by_appl_ver_id_and_begin_string() {
+        for default_appl_ver_id in ["FIX.5.0SP2", "9"] {
+            let data = format!(
+                "
+[DEFAULT]
+ConnectionType=acceptor
+SocketAcceptPort=5000
+SenderCompID=ISLD
+TargetCompID=TW
+UseDataDictionary=Y
+[SESSION]
+BeginString=FIXT.1.1
+DefaultApplVerID={default_appl_ver_id}
+AppDataDictionary=../../spec/FIX50SP2.xml
+TransportDataDictionary=../../spec/FIXT11.xml
+"
+            );
+            let settings = SessionSettings::from_string(&data).unwrap();
+            let mut provider = DefaultDataDictionaryProvider::new();
+            add_data_dictionaries(&mut provider, &settings.sessions()[0]);
+            assert!(provider.find_application_data_dictionary("9").is_some());
+            assert!(provider.find_application_data_dictionary("FIX.5.0SP2").is_some());
+            assert!(provider.find_application_data_dictionary("6").is_none());
+        }
+    }
+}
@@ -6,6 +6,7 @@ use crate::logging::Logger;
 use dfx_base::message::Message;
 use crate::message_store::MessageStore;
 use std::collections::BTreeMap;
+use std::time::Duration;
 use std::time::Instant;
 
 use crate::session::ResetRange;
@@ -25,6 +26,11 @@ pub(crate) struct SessionState<Log> {
     heartbeat_int_ms: u64,
     last_received_time_dt: Instant,
     last_sent_time_dt: Instant,
+    test_request_delay_multiplier: f64,
+    heart_bt_timeout_multiplier: f64,
+    // how late the last heartbeat went out, and the worst seen on this connection
+    last_heartbeat_jitter: Option<Duration>,
+    max_heartbeat_jitter: Duration,
     logon_timeout: u32,
     logon_timeout_ms: u64,
     logout_timeout: u32,
@@ -56,6 +62,10 @@ impl<Log: Logger> SessionState<Log> {
             heartbeat_int_ms: (heartbeat_int as u64) * 1000,
             last_received_time_dt: Instant::now(),
             last_sent_time_dt: Instant::now(),
+            test_request_delay_multiplier: 1.2,
+            heart_bt_timeout_multiplier: 2.4,
+            last_heartbeat_jitter: None,
+            max_heartbeat_jitter: Duration::ZERO,
             logon_timeout: 10,
             logon_timeout_ms: 10 * 1000,
             logout_timeout: 2,
@@ -99,6 +109,7 @@ impl<Log: Logger> SessionState<Log> {
         timed_out(
             Instant::now(),
             self.heartbeat_int_ms.into(),
+            self.heart_bt_timeout_multiplier,
             self.last_received_time_dt,
         )
     }
@@ -122,10 +133,21 @@ impl<Log: Logger> SessionState<Log> {
         need_test_request(
             Instant::now(),
             self.heartbeat_int_ms.into(),
+            self.test_request_delay_multiplier,
             self.last_received_time_dt,
             self.test_request_counter,
         )
     }
+    /// Records how long after HeartBtInt the heartbeat being sent now is.
+    pub(crate) fn record_heartbeat_jitter(&mut self) {
+        let jitter = heartbeat_jitter(
+            Instant::now(),
+            self.heartbeat_int_ms.into(),
+            self.last_sent_time_dt,
+        );
+        self.last_heartbeat_jitter = Some(jitter);
+        self.max_heartbeat_jitter = self.max_heartbeat_jitter.max(jitter);
+    }
     pub(crate) fn resend_requested(&self) -> bool {
         self.resend_range
             .as_ref()
@@ -281,6 +303,7 @@ impl<Log: Logger> SessionState<Log> {
     /// Set the session state's heartbeat int.
     pub(crate) fn set_heartbeat_int(&mut self, heartbeat_int: u32) {
         self.heartbeat_int = heartbeat_int;
+        self.heartbeat_int_ms = (heartbeat_int as u64) * 1000;
     }
 
     /// Get the session state's heartbeat int ms.
@@ -343,6 +366,22 @@ impl<Log: Logger> SessionState<Log> {
         self.logon_timeout = logon_timeout;
     }
 
+    pub(crate) fn set_test_request_delay_multiplier(&mut self, multiplier: f64) {
+        self.test_request_delay_multiplier = multiplier;
+    }
+
+    pub(crate) fn set_heart_bt_timeout_multiplier(&mut self, multiplier: f64) {
+        self.heart_bt_timeout_multiplier = multiplier;
+    }
+
+    pub(crate) fn last_heartbeat_jitter(&self) -> Option<Duration> {
+        self.last_heartbeat_jitter
+    }
+
+    pub(crate) fn max_heartbeat_jitter(&self) -> Duration {
+        self.max_heartbeat_jitter
+    }
+
     /// Get the session state's logon timeout ms.
     pub(crate) fn logon_timeout_ms(&self) -> u64 {
         self.logon_timeout_ms
@@ -470,6 +509,10 @@ impl<Log: Logger> SessionState<Log> {
         self.msg_store.refresh()
     }
 
+    pub(crate) fn flush(&mut self) {
+        self.msg_store.flush()
+    }
+
     pub(crate) fn next_sender_msg_seq_num(&self) -> u32 {
         self.msg_store.next_sender_msg_seq_num()
     }
@@ -528,10 +571,11 @@ pub(crate) fn logon_timed_out(
 pub(crate) fn timed_out(
     now: Instant,
     heart_bt_int_millis: u128,
+    heart_bt_timeout_multiplier: f64,
     last_received_time: Instant,
 ) -> bool {
     let elapsed = (now - last_received_time).as_millis();
-    elapsed as f64 >= (2.4 * heart_bt_int_millis as f64)
+    elapsed as f64 >= (heart_bt_timeout_multiplier * heart_bt_int_millis as f64)
 }
 
 /// All time args are in milliseconds
@@ -567,9 +611,20 @@ pub(crate) fn need_heartbeat(
 pub(crate) fn need_test_request(
     now: Instant,
     heartbeat_int_ms: u128,
+    test_request_delay_multiplier: f64,
     last_received_time: Instant,
     test_request_counter: u32,
 ) -> bool {
     (now - last_received_time).as_millis()
-        >= (1.2 * ((test_request_counter as u128 + 1) * heartbeat_int_ms) as f64) as u128
+        >= (test_request_delay_multiplier
+            * ((test_request_counter as u128 + 1) * heartbeat_int_ms) as f64) as u128
+}
+
+/// Time past `heartbeat_int_ms` since the last message was sent.
+pub(crate) fn heartbeat_jitter(
+    now: Instant,
+    heartbeat_int_ms: u128,
+    last_sent_time: Instant,
+) -> Duration {
+    (now - last_sent_time).saturating_sub(Duration::from_millis(heartbeat_int_ms as u64))
 }
@@ -1,14 +1,15 @@
-use std::{net::ToSocketAddrs, fs::File, io::Read};
+use std::{collections::BTreeMap, net::{SocketAddr, ToSocketAddrs}};
 
 use chrono::NaiveTime;
 
 use dfx_base::session_id::SessionId;
 use dfx_base::fields::converters::datetime::DateTimeFormat;
-use native_tls::{Protocol, TlsConnector, Identity, Certificate, TlsAcceptor};
-use crate::session::SessionSchedule;
+use crate::connection::tls::{self, TlsConfig};
+use crate::session::{CertificateCompIds, SessionSchedule};
 
 use super::{
-    ConnectionType, SessionSetting, SessionSettingsError, SettingOption, SettingsConnection, SocketOptions, LoggingOptions, Persistence, ValidationOptions, SslOptions,
+    ConnectionType, SessionSetting, SessionSettingsError, SettingOption, SettingsConnection, SocketOptions, LoggingOptions, Persistence, ValidationOptions, SslOptions, SslSettings,
+    FailoverStrategy, ReconnectOptions, ProxyType, ProxyOptions, ProxyCredentials, Endpoint,
 };
 
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
@@ -40,7 +41,12 @@ pub(crate) struct DynamicSessionSettingBuilder {
     resend_session_level_rejects: Option<String>,
     time_stamp_precision: Option<String>,
     enable_last_msg_seq_num_processed: Option<String>,
+    enable_next_expected_msg_seq_num: Option<String>,
     max_messages_in_resend_request: Option<String>,
+    resend_chunk_size: Option<String>,
+    max_resend_messages_per_second: Option<String>,
+    test_request_delay_multiplier: Option<String>,
+    heart_bt_timeout_multiplier: Option<String>,
     send_logout_before_disconnect_from_timeout: Option<String>,
     ignore_poss_dup_resend_requests: Option<String>,
     requires_orig_sending_time: Option<String>,
@@ -60,14 +66,22 @@ pub(crate) struct DynamicSessionSettingBuilder {
 
     // initiator options
     reconnect_interval: Option<String>,
+    reconnect_max_interval: Option<String>,
+    reconnect_jitter: Option<String>,
+    reconnect_max_attempts: Option<String>,
+    failover_strategy: Option<String>,
     heart_bt_int: Option<String>,
     logon_timeout: Option<String>,
     logout_timeout: Option<String>,
     socket_connect_host: Option<String>,
     socket_connect_port: Option<String>,
-    //TODO
-    socket_connect_hosts: Option<String>, // initiator<n> failover
-    socket_connect_ports: Option<String>, // initiator<n> failover
+    socket_connect_hosts: BTreeMap<u32, String>, // initiator<n> failover
+    socket_connect_ports: BTreeMap<u32, String>, // initiator<n> failover
+    proxy_type: Option<String>,
+    proxy_host: Option<String>,
+    proxy_port: Option<String>,
+    proxy_user: Option<String>,
+    proxy_password: Option<String>,
 
     // acceptor options
     socket_accept_host: Option<String>,
@@ -102,6 +116,7 @@ pub(crate) struct DynamicSessionSettingBuilder {
     ssl_certificate_password: Option<String>,
     ssl_require_client_certificate: Option<String>,
     ssl_ca_certificate: Option<String>,
+    ssl_certificate_comp_ids: Option<String>,
 }
 
 pub(crate) struct Validated(DynamicSessionSettingBuilder);
@@ -138,7 +153,22 @@ impl DynamicSessionSettingBuilder {
             SettingOption::SocketAcceptPort => self.socket_accept_port = Some(value.into()),
             SettingOption::SocketConnectHost => self.socket_connect_host = Some(value.into()),
             SettingOption::SocketConnectPort => self.socket_connect_port = Some(value.into()),
+            SettingOption::SocketConnectHostN(n) => {
+                self.socket_connect_hosts.insert(n, value.into());
+            }
+            SettingOption::SocketConnectPortN(n) => {
+                self.socket_connect_ports.insert(n, value.into());
+            }
+            SettingOption::FailoverStrategy => self.failover_strategy = Some(value.into()),
             SettingOption::ReconnectInterval => self.reconnect_interval = Some(value.into()),
+            SettingOption::ReconnectMaxInterval => self.reconnect_max_interval = Some(value.into()),
+            SettingOption::ReconnectJitter => self.reconnect_jitter = Some(value.into()),
+            SettingOption::ReconnectMaxAttempts => self.reconnect_max_attempts = Some(value.into()),
+            SettingOption::ProxyType => self.proxy_type = Some(value.into()),
+            SettingOption::ProxyHost => self.proxy_host = Some(value.into()),
+            SettingOption::ProxyPort => self.proxy_port = Some(value.into()),
+            SettingOption::ProxyUser => self.proxy_user = Some(value.into()),
+            SettingOption::ProxyPassword => self.proxy_password = Some(value.into()),
             SettingOption::FileLogPath => self.file_log_path = Some(value.into()),
             SettingOption::DebugFileLogPath => self.debug_file_log_path = Some(value.into()),
             SettingOption::FileStorePath => self.file_store_path = Some(value.into()),
@@ -182,9 +212,22 @@ impl DynamicSessionSettingBuilder {
             SettingOption::EnableLastMsgSeqNumProcessed => {
                 self.enable_last_msg_seq_num_processed = Some(value.into())
             }
+            SettingOption::EnableNextExpectedMsgSeqNum => {
+                self.enable_next_expected_msg_seq_num = Some(value.into())
+            }
             SettingOption::MaxMessagesInResendRequest => {
                 self.max_messages_in_resend_request = Some(value.into())
             }
+            SettingOption::ResendChunkSize => self.resend_chunk_size = Some(value.into()),
+            SettingOption::MaxResendMessagesPerSecond => {
+                self.max_resend_messages_per_second = Some(value.into())
+            }
+            SettingOption::TestRequestDelayMultiplier => {
+                self.test_request_delay_multiplier = Some(value.into())
+            }
+            SettingOption::HeartBtTimeoutMultiplier => {
+                self.heart_bt_timeout_multiplier = Some(value.into())
+            }
             SettingOption::SendLogoutBeforeDisconnectFromTimeout => {
                 self.send_logout_before_disconnect_from_timeout = Some(value.into())
             }
@@ -221,6 +264,7 @@ impl DynamicSessionSettingBuilder {
                 self.ssl_require_client_certificate = Some(value.into())
             }
             SettingOption::SSLCACertificate => self.ssl_ca_certificate = Some(value.into()),
+            SettingOption::SSLCertificateCompIDs => self.ssl_certificate_comp_ids = Some(value.into()),
         }
     }
 
@@ -295,9 +339,24 @@ impl DynamicSessionSettingBuilder {
         self.enable_last_msg_seq_num_processed = self
             .enable_last_msg_seq_num_processed
             .or(other.enable_last_msg_seq_num_processed.clone());
+        self.enable_next_expected_msg_seq_num = self
+            .enable_next_expected_msg_seq_num
+            .or(other.enable_next_expected_msg_seq_num.clone());
         self.max_messages_in_resend_request = self
             .max_messages_in_resend_request
             .or(other.max_messages_in_resend_request.clone());
+        self.resend_chunk_size = self
+            .resend_chunk_size
+            .or(other.resend_chunk_size.clone());
+        self.max_resend_messages_per_second = self
+            .max_resend_messages_per_second
+            .or(other.max_resend_messages_per_second.clone());
+        self.test_request_delay_multiplier = self
+            .test_request_delay_multiplier
+            .or(other.test_request_delay_multiplier.clone());
+        self.heart_bt_timeout_multiplier = self
+            .heart_bt_timeout_multiplier
+            .or(other.heart_bt_timeout_multiplier.clone());
         self.send_logout_before_disconnect_from_timeout = self
             .send_logout_before_disconnect_from_timeout
             .or(other.send_logout_before_disconnect_from_timeout.clone());
@@ -339,6 +398,14 @@ impl DynamicSessionSettingBuilder {
 
         // initiator options
         self.reconnect_interval = self.reconnect_interval.or(other.reconnect_interval.clone());
+        self.reconnect_max_interval = self
+            .reconnect_max_interval
+            .or(other.reconnect_max_interval.clone());
+        self.reconnect_jitter = self.reconnect_jitter.or(other.reconnect_jitter.clone());
+        self.reconnect_max_attempts = self
+            .reconnect_max_attempts
+            .or(other.reconnect_max_attempts.clone());
+        self.failover_strategy = self.failover_strategy.or(other.failover_strategy.clone());
         self.heart_bt_int = self.heart_bt_int.or(other.heart_bt_int.clone());
         self.logon_timeout = self.logon_timeout.or(other.logon_timeout.clone());
         self.logout_timeout = self.logout_timeout.or(other.logout_timeout.clone());
@@ -348,12 +415,17 @@ impl DynamicSessionSettingBuilder {
         self.socket_connect_port = self
             .socket_connect_port
             .or(other.socket_connect_port.clone());
-        self.socket_connect_hosts = self
-            .socket_connect_hosts
-            .or(other.socket_connect_hosts.clone());
-        self.socket_connect_ports = self
-            .socket_connect_ports
-            .or(other.socket_connect_ports.clone());
+        for (n, host) in &other.socket_connect_hosts {
+            self.socket_connect_hosts.entry(*n).or_insert_with(|| host.clone());
+        }
+        for (n, port) in &other.socket_connect_ports {
+            self.socket_connect_ports.entry(*n).or_insert_with(|| port.clone());
+        }
+        self.proxy_type = self.proxy_type.or(other.proxy_type.clone());
+        self.proxy_host = self.proxy_host.or(other.proxy_host.clone());
+        self.proxy_port = self.proxy_port.or(other.proxy_port.clone());
+        self.proxy_user = self.proxy_user.or(other.proxy_user.clone());
+        self.proxy_password = self.proxy_password.or(other.proxy_password.clone());
 
         // acceptor options
         self.socket_accept_host = self.socket_accept_host.or(other.socket_accept_host.clone());
@@ -402,6 +474,7 @@ impl DynamicSessionSettingBuilder {
             .ssl_require_client_certificate
             .or(other.ssl_require_client_certificate.clone());
         self.ssl_ca_certificate = self.ssl_ca_certificate.or(other.ssl_ca_certificate.clone());
+        self.ssl_certificate_comp_ids = self.ssl_certificate_comp_ids.or(other.ssl_certificate_comp_ids.clone());
         self
     }
 
@@ -430,6 +503,54 @@ impl DynamicSessionSettingBuilder {
             errors.push("TargetCompID must be set.".into());
         }
 
+        for n in self.socket_connect_hosts.keys() {
+            if !self.socket_connect_ports.contains_key(n) {
+                errors.push(format!("SocketConnectHost{n} is set without SocketConnectPort{n}."));
+            }
+        }
+        for n in self.socket_connect_ports.keys() {
+            if !self.socket_connect_hosts.contains_key(n) {
+                errors.push(format!("SocketConnectPort{n} is set without SocketConnectHost{n}."));
+            }
+        }
+
+        if let Some(Err(_)) = self.failover_strategy.as_deref().map(FailoverStrategy::try_from) {
+            errors.push("FailoverStrategy must be set to either 'RoundRobin' or 'Priority'.".into());
+        }
+
+        match self.proxy_type.as_deref().map(ProxyType::try_from) {
+            Some(Ok(_)) if self.proxy_host.is_none() || self.proxy_port.is_none() => {
+                errors.push("ProxyHost and ProxyPort must be set when ProxyType is set.".into())
+            }
+            Some(Err(_)) => errors.push("ProxyType must be set to either 'socks5' or 'http'.".into()),
+            _ => (),
+        }
+        if self.proxy_user.is_some() != self.proxy_password.is_some() {
+            errors.push("ProxyUser and ProxyPassword must be set together.".into());
+        }
+
+        if let Some(Err(_)) = self.ssl_certificate_comp_ids.as_deref().map(CertificateCompIds::try_from) {
+            errors.push("SSLCertificateCompIDs must be a list of '<name>:<pattern>><CompID>,...' separated by ';'.".into());
+        }
+        // the client certificate is only checked by acceptors verifying it with rustls
+        if self.ssl_certificate_comp_ids.is_some() {
+            if matches!(conn_type, Some(Ok(ConnectionType::Initiator))) {
+                errors.push("SSLCertificateCompIDs is only supported by acceptors.".into());
+            }
+            if self.ssl_enable.as_deref() != Some("Y") {
+                errors.push("SSLCertificateCompIDs requires SSLEnable=Y.".into());
+            }
+            if self.ssl_require_client_certificate.as_deref() != Some("Y") {
+                errors.push("SSLCertificateCompIDs requires SSLRequireClientCertificate=Y.".into());
+            }
+            if self.ssl_ca_certificate.is_none() {
+                errors.push("SSLCertificateCompIDs requires SSLCACertificate.".into());
+            }
+            if cfg!(not(feature = "rustls")) {
+                errors.push("SSLCertificateCompIDs requires the rustls feature.".into());
+            }
+        }
+
         if errors.len() > 0 {
             Err(SessionSettingsError::ValidationErrors(errors))
         } else {
@@ -467,19 +588,41 @@ impl DynamicSessionSettingBuilder {
                 logon_timeout: self.logon_timeout.map(|v| v.parse().ok()).flatten().unwrap_or(10),
                 logout_timeout: self.logout_timeout.map(|v| v.parse().ok()).flatten().unwrap_or(2),
             },
-            "initiator" => SettingsConnection::Initiator {
-                connect_addr: format!(
-                    "{}:{}",
-                    self.socket_connect_host.as_ref().unwrap(),
-                    self.socket_connect_port.unwrap()
-                )
-                .to_socket_addrs()
-                .unwrap()
-                .next().unwrap(),
-                reconnect_interval: self.reconnect_interval.map(|v| v.parse().ok()).flatten().unwrap_or(30),
-                heart_bt_int: self.heart_bt_int.map(|v| v.parse().ok()).flatten().unwrap_or(30),
-                logon_timeout: self.logon_timeout.map(|v| v.parse().ok()).flatten().unwrap_or(10),
-                logout_timeout: self.logout_timeout.map(|v| v.parse().ok()).flatten().unwrap_or(2),
+            "initiator" => {
+                let proxy = match (self.proxy_type, self.proxy_host, self.proxy_port) {
+                    (Some(proxy_type), Some(host), Some(port)) => Some(ProxyOptions {
+                        proxy_type: proxy_type.as_str().try_into()?,
+                        addr: resolve(SettingOption::ProxyHost.into(), &host, &port)?,
+                        credentials: self.proxy_user.zip(self.proxy_password)
+                            .map(|(user, password)| ProxyCredentials { user, password }),
+                    }),
+                    _ => None,
+                };
+                let proxy_addr = proxy.as_ref().map(|proxy| proxy.addr);
+                SettingsConnection::Initiator {
+                    connect_addr: endpoint(
+                        SettingOption::SocketConnectHost.into(),
+                        self.socket_connect_host.as_deref().unwrap_or_default(),
+                        self.socket_connect_port.as_deref().unwrap_or_default(),
+                        proxy_addr,
+                    )?,
+                    failover_addrs: self
+                        .socket_connect_hosts
+                        .iter()
+                        .map(|(n, host)| endpoint(format!("SocketConnectHost{n}"), host, &self.socket_connect_ports[n], proxy_addr))
+                        .collect::<Result<_, _>>()?,
+                    proxy,
+                    reconnect: reconnect_options(
+                        self.reconnect_interval,
+                        self.reconnect_max_interval,
+                        self.reconnect_jitter,
+                        self.reconnect_max_attempts,
+                        self.failover_strategy,
+                    ),
+                    heart_bt_int: self.heart_bt_int.map(|v| v.parse().ok()).flatten().unwrap_or(30),
+                    logon_timeout: self.logon_timeout.map(|v| v.parse().ok()).flatten().unwrap_or(10),
+                    logout_timeout: self.logout_timeout.map(|v| v.parse().ok()).flatten().unwrap_or(2),
+                }
             },
             _ => unreachable!(),
         };
@@ -555,7 +698,12 @@ impl DynamicSessionSettingBuilder {
             .resend_session_level_rejects(self.resend_session_level_rejects.map(|v| v == "Y").unwrap_or(false))
             .time_stamp_precision(self.time_stamp_precision.map(|v| v.try_into().ok()).flatten().unwrap_or(DateTimeFormat::Seconds))
             .enable_last_msg_seq_num_processed(self.enable_last_msg_seq_num_processed.map(|v| v == "Y").unwrap_or(false))
+            .enable_next_expected_msg_seq_num(self.enable_next_expected_msg_seq_num.map(|v| v == "Y").unwrap_or(false))
             .max_messages_in_resend_request(self.max_messages_in_resend_request.map(|v| v.parse().ok()).flatten().unwrap_or(0))
+            .resend_chunk_size(self.resend_chunk_size.and_then(|v| v.parse().ok()).filter(|v| *v > 0).unwrap_or(1000))
+            .max_resend_messages_per_second(self.max_resend_messages_per_second.and_then(|v| v.parse().ok()).unwrap_or(0))
+            .test_request_delay_multiplier(self.test_request_delay_multiplier.and_then(|v| v.parse().ok()).filter(|v: &f64| *v > 0.0).unwrap_or(1.2))
+            .heart_bt_timeout_multiplier(self.heart_bt_timeout_multiplier.and_then(|v| v.parse().ok()).filter(|v: &f64| *v > 0.0).unwrap_or(2.4))
             .send_logout_before_disconnect_from_timeout(self.send_logout_before_disconnect_from_timeout.map(|v| v == "Y").unwrap_or(false))
             .ignore_poss_dup_resend_requests(self.ignore_poss_dup_resend_requests.map(|v| v == "Y").unwrap_or(false))
             .requires_orig_sending_time(self.requires_orig_sending_time.map(|v| v == "Y").unwrap_or(true))
@@ -573,50 +721,45 @@ impl DynamicSessionSettingBuilder {
             .build().unwrap();
         builder.validation_options(validation_options);
 
-        let ssl_options = match (self.ssl_enable.as_ref().map(|s| s.as_str()), is_initiator) {
-            (Some(_x @ "Y"), true) => {
-                let mut builder = TlsConnector::builder();
-                if let Some(certificate_file) = self.ssl_certificate {
-                    let mut file = File::open(certificate_file).unwrap();
-                    let mut buf = vec![];
-                    file.read_to_end(&mut buf).unwrap();
-                    let certificate = Certificate::from_pem(&buf).unwrap();
-                    let identity = Identity::from_pkcs12(&certificate.to_der().unwrap(), self.ssl_certificate_password.as_ref().unwrap()).unwrap();
-                    builder.identity(identity);
-                }
-                builder.min_protocol_version(self.ssl_min_protocol.as_ref().map(|r| protocol(r)).flatten());
-                builder.max_protocol_version(self.ssl_max_protocol.as_ref().map(|r| protocol(r)).flatten());
-                builder.use_sni(self.ssl_use_sni.as_ref().map(|v| v == "Y").unwrap_or(true));
-                builder.danger_accept_invalid_certs(self.ssl_accept_invalid_certs.as_ref().map(|v| v == "Y").unwrap_or(false));
-                builder.danger_accept_invalid_hostnames(self.ssl_accept_invalid_hostnames.as_ref().map(|v| v == "Y").unwrap_or(false));
-                builder.disable_built_in_roots(self.ssl_disable_built_in_roots.as_ref().map(|v| v == "Y").unwrap_or(false));
-
-                // root_certificates: vec![],
-                // #[cfg(feature = "alpn")]
-                // alpn: vec![],
-
-                let host = self.socket_connect_host.as_ref().cloned();
-                let domain = self.ssl_server_name.as_ref().cloned()
-                    .or(host)
-                    .unwrap_or_else(|| String::from(""));
-                let initiator = builder
-                    .build()
-                    .unwrap();
-                Some(SslOptions::Initiator { initiator, domain })
-            },
-            (Some(_x @ "Y"), false) => {
-                let mut file = File::open(self.ssl_certificate.unwrap()).unwrap();
-                let mut buf = vec![];
-                file.read_to_end(&mut buf).unwrap();
-                let certificate = Certificate::from_pem(&buf).unwrap();
-                let identity = Identity::from_pkcs12(&certificate.to_der().unwrap(), self.ssl_certificate_password.as_ref().unwrap()).unwrap();
-                let mut builder = TlsAcceptor::builder(identity);
-                builder.min_protocol_version(self.ssl_min_protocol.as_ref().map(|r| protocol(r)).flatten());
-                builder.max_protocol_version(self.ssl_max_protocol.as_ref().map(|r| protocol(r)).flatten());
-                let acceptor = builder
-                    .build()
-                    .unwrap();
-                Some(SslOptions::Acceptor { acceptor })
+        let ssl_settings = match self.ssl_enable.as_deref() {
+            Some("Y") => Some(SslSettings {
+                certificate: self.ssl_certificate.clone(),
+                min_protocol: self.ssl_min_protocol.clone(),
+                max_protocol: self.ssl_max_protocol.clone(),
+                require_client_certificate: self.ssl_require_client_certificate.clone(),
+                ca_certificate: self.ssl_ca_certificate.clone(),
+            }),
+            _ => None,
+        };
+        builder.ssl_settings(ssl_settings);
+        builder.certificate_comp_ids(
+            self.ssl_certificate_comp_ids.as_deref().and_then(|v| CertificateCompIds::try_from(v).ok()),
+        );
+
+        let ssl_options = match self.ssl_enable.as_deref() {
+            Some("Y") => {
+                let yes = |v: &Option<String>, default| v.as_ref().map(|v| v == "Y").unwrap_or(default);
+                let config = TlsConfig {
+                    certificate: self.ssl_certificate.clone(),
+                    certificate_password: self.ssl_certificate_password.clone(),
+                    ca_certificate: self.ssl_ca_certificate.clone(),
+                    require_client_certificate: yes(&self.ssl_require_client_certificate, false),
+                    min_protocol: self.ssl_min_protocol.clone(),
+                    max_protocol: self.ssl_max_protocol.clone(),
+                    use_sni: yes(&self.ssl_use_sni, true),
+                    accept_invalid_certs: yes(&self.ssl_accept_invalid_certs, false),
+                    accept_invalid_hostnames: yes(&self.ssl_accept_invalid_hostnames, false),
+                    disable_built_in_roots: yes(&self.ssl_disable_built_in_roots, false),
+                };
+                let ssl_options = if is_initiator {
+                    let domain = self.ssl_server_name.clone()
+                        .or_else(|| self.socket_connect_host.clone())
+                        .unwrap_or_default();
+                    tls::connector(&config).map(|initiator| SslOptions::Initiator { initiator, domain })
+                } else {
+                    tls::acceptor(&config).map(|acceptor| SslOptions::Acceptor { acceptor })
+                };
+                Some(ssl_options.map_err(SessionSettingsError::TlsError)?)
             },
             _ => None,
         };
@@ -625,12 +768,59 @@ impl DynamicSessionSettingBuilder {
     }
 }
 
-fn protocol(protocol: &str) -> Option<Protocol> {
-    match protocol {
-        "TLSv1.0" => Some(Protocol::Tlsv10),
-        "TLSv1.1" => Some(Protocol::Tlsv11),
-        "TLSv1.2" => Some(Protocol::Tlsv12),
-        "SSLv3" => Some(Protocol::Sslv3),
-        _ => None
+fn resolve(setting: String, host: &str, port: &str) -> Result<SocketAddr, SessionSettingsError> {
+    let endpoint = format!("{host}:{port}");
+    endpoint
+        .to_socket_addrs()
+        .ok()
+        .and_then(|mut addrs| addrs.next())
+        .ok_or(SessionSettingsError::InvalidValue {
+            setting,
+            value: endpoint,
+        })
+}
+
+/// `host:port` of an initiator, resolved unless connecting through the proxy at `proxy_addr`.
+fn endpoint(
+    setting: String,
+    host: &str,
+    port: &str,
+    proxy_addr: Option<SocketAddr>,
+) -> Result<Endpoint, SessionSettingsError> {
+    let invalid = || SessionSettingsError::InvalidValue {
+        setting: setting.clone(),
+        value: format!("{host}:{port}"),
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let addr = match proxy_addr {
+        Some(proxy_addr) => proxy_addr,
+        None => (host, port.parse().map_err(|_| invalid())?)
+            .to_socket_addrs()
+            .ok()
+            .and_then(|mut addrs| addrs.next())
+            .ok_or_else(invalid)?,
+    };
+    Ok(Endpoint {
+        host: host.into(),
+        port: port.parse().map_err(|_| invalid())?,
+        addr,
+    })
+}
+
+fn reconnect_options(
+    interval: Option<String>,
+    max_interval: Option<String>,
+    jitter: Option<String>,
+    max_attempts: Option<String>,
+    strategy: Option<String>,
+) -> ReconnectOptions {
+    let interval = interval.and_then(|v| v.parse().ok()).unwrap_or(30);
+    ReconnectOptions {
+        interval,
+        // no backoff unless a larger maximum is configured
+        max_interval: max_interval.and_then(|v| v.parse().ok()).unwrap_or(interval).max(interval),
+        jitter: jitter.and_then(|v| v.parse().ok()).filter(|v: &f64| (0.0..=1.0).contains(v)).unwrap_or(0.0),
+        max_attempts: max_attempts.and_then(|v| v.parse().ok()).unwrap_or(0),
+        strategy: strategy.and_then(|v| v.as_str().try_into().ok()).unwrap_or_default(),
     }
 }
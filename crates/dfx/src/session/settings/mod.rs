@@ -36,7 +36,18 @@ pub(crate) enum SettingOption {
     SocketAcceptPort,
     SocketConnectHost,
     SocketConnectPort,
+    SocketConnectHostN(u32),
+    SocketConnectPortN(u32),
+    FailoverStrategy,
     ReconnectInterval,
+    ReconnectMaxInterval,
+    ReconnectJitter,
+    ReconnectMaxAttempts,
+    ProxyType,
+    ProxyHost,
+    ProxyPort,
+    ProxyUser,
+    ProxyPassword,
     FileLogPath,
     DebugFileLogPath,
     FileStorePath,
@@ -60,7 +71,12 @@ pub(crate) enum SettingOption {
     MillisecondsInTimeStamp,
     TimeStampPrecision,
     EnableLastMsgSeqNumProcessed,
+    EnableNextExpectedMsgSeqNum,
     MaxMessagesInResendRequest,
+    ResendChunkSize,
+    MaxResendMessagesPerSecond,
+    TestRequestDelayMultiplier,
+    HeartBtTimeoutMultiplier,
     SendLogoutBeforeDisconnectFromTimeout,
     SocketNodelay,
     SocketSendBufferSize,
@@ -86,6 +102,7 @@ pub(crate) enum SettingOption {
     SSLCertificatePassword,
     SSLRequireClientCertificate,
     SSLCACertificate,
+    SSLCertificateCompIDs,
 }
 
 #[derive(Debug, Default, Clone)]
@@ -110,6 +127,8 @@ pub enum SessionSettingsError {
         reason: String,
         line_number: usize,
     },
+    /// The `SSL*` settings could not be turned into a TLS configuration.
+    TlsError(String),
 }
 
 impl TryFrom<&str> for SettingOption {
@@ -141,7 +160,16 @@ impl TryFrom<&str> for SettingOption {
             "SocketAcceptPort" => Ok(Self::SocketAcceptPort),
             "SocketConnectHost" => Ok(Self::SocketConnectHost),
             "SocketConnectPort" => Ok(Self::SocketConnectPort),
+            "FailoverStrategy" => Ok(Self::FailoverStrategy),
             "ReconnectInterval" => Ok(Self::ReconnectInterval),
+            "ReconnectMaxInterval" => Ok(Self::ReconnectMaxInterval),
+            "ReconnectJitter" => Ok(Self::ReconnectJitter),
+            "ReconnectMaxAttempts" => Ok(Self::ReconnectMaxAttempts),
+            "ProxyType" => Ok(Self::ProxyType),
+            "ProxyHost" => Ok(Self::ProxyHost),
+            "ProxyPort" => Ok(Self::ProxyPort),
+            "ProxyUser" => Ok(Self::ProxyUser),
+            "ProxyPassword" => Ok(Self::ProxyPassword),
             "FileLogPath" => Ok(Self::FileLogPath),
             "DebugFileLogPath" => Ok(Self::DebugFileLogPath),
             "FileStorePath" => Ok(Self::FileStorePath),
@@ -165,7 +193,12 @@ impl TryFrom<&str> for SettingOption {
             "MillisecondsInTimeStamp" => Ok(Self::MillisecondsInTimeStamp),
             "TimeStampPrecision" => Ok(Self::TimeStampPrecision),
             "EnableLastMsgSeqNumProcessed" => Ok(Self::EnableLastMsgSeqNumProcessed),
+            "EnableNextExpectedMsgSeqNum" => Ok(Self::EnableNextExpectedMsgSeqNum),
             "MaxMessagesInResendRequest" => Ok(Self::MaxMessagesInResendRequest),
+            "ResendChunkSize" => Ok(Self::ResendChunkSize),
+            "MaxResendMessagesPerSecond" => Ok(Self::MaxResendMessagesPerSecond),
+            "TestRequestDelayMultiplier" => Ok(Self::TestRequestDelayMultiplier),
+            "HeartBtTimeoutMultiplier" => Ok(Self::HeartBtTimeoutMultiplier),
             "SendLogoutBeforeDisconnectFromTimeout" => {
                 Ok(Self::SendLogoutBeforeDisconnectFromTimeout)
             }
@@ -193,7 +226,18 @@ impl TryFrom<&str> for SettingOption {
             "SSLCertificatePassword" => Ok(Self::SSLCertificatePassword),
             "SSLRequireClientCertificate" => Ok(Self::SSLRequireClientCertificate),
             "SSLCACertificate" => Ok(Self::SSLCACertificate),
-            _ => Err(Self::Error::NoSuchSetting(value.into())),
+            "SSLCertificateCompIDs" => Ok(Self::SSLCertificateCompIDs),
+            // SocketConnectHost<n>/SocketConnectPort<n> failover endpoints
+            _ => {
+                let numbered = |prefix: &str| value.strip_prefix(prefix).and_then(|n| n.parse().ok());
+                if let Some(n) = numbered("SocketConnectHost") {
+                    Ok(Self::SocketConnectHostN(n))
+                } else if let Some(n) = numbered("SocketConnectPort") {
+                    Ok(Self::SocketConnectPortN(n))
+                } else {
+                    Err(Self::Error::NoSuchSetting(value.into()))
+                }
+            }
         }
     }
 }
@@ -233,7 +277,18 @@ impl Into<&'static str> for SettingOption {
             Self::SocketAcceptPort => "SocketAcceptPort",
             Self::SocketConnectHost => "SocketConnectHost",
             Self::SocketConnectPort => "SocketConnectPort",
+            Self::SocketConnectHostN(_) => "SocketConnectHost<n>",
+            Self::SocketConnectPortN(_) => "SocketConnectPort<n>",
+            Self::FailoverStrategy => "FailoverStrategy",
             Self::ReconnectInterval => "ReconnectInterval",
+            Self::ReconnectMaxInterval => "ReconnectMaxInterval",
+            Self::ReconnectJitter => "ReconnectJitter",
+            Self::ReconnectMaxAttempts => "ReconnectMaxAttempts",
+            Self::ProxyType => "ProxyType",
+            Self::ProxyHost => "ProxyHost",
+            Self::ProxyPort => "ProxyPort",
+            Self::ProxyUser => "ProxyUser",
+            Self::ProxyPassword => "ProxyPassword",
             Self::FileLogPath => "FileLogPath",
             Self::DebugFileLogPath => "DebugFileLogPath",
             Self::FileStorePath => "FileStorePath",
@@ -257,7 +312,12 @@ impl Into<&'static str> for SettingOption {
             Self::MillisecondsInTimeStamp => "MillisecondsInTimeStamp",
             Self::TimeStampPrecision => "TimeStampPrecision",
             Self::EnableLastMsgSeqNumProcessed => "EnableLastMsgSeqNumProcessed",
+            Self::EnableNextExpectedMsgSeqNum => "EnableNextExpectedMsgSeqNum",
             Self::MaxMessagesInResendRequest => "MaxMessagesInResendRequest",
+            Self::ResendChunkSize => "ResendChunkSize",
+            Self::MaxResendMessagesPerSecond => "MaxResendMessagesPerSecond",
+            Self::TestRequestDelayMultiplier => "TestRequestDelayMultiplier",
+            Self::HeartBtTimeoutMultiplier => "HeartBtTimeoutMultiplier",
             Self::SendLogoutBeforeDisconnectFromTimeout => "SendLogoutBeforeDisconnectFromTimeout",
             Self::SocketNodelay => "SocketNodelay",
             Self::SocketSendBufferSize => "SocketSendBufferSize",
@@ -283,6 +343,7 @@ impl Into<&'static str> for SettingOption {
             Self::SSLCertificatePassword => "SSLCertificatePassword",
             Self::SSLRequireClientCertificate => "SSLRequireClientCertificate",
             Self::SSLCACertificate => "SSLCACertificate",
+            Self::SSLCertificateCompIDs => "SSLCertificateCompIDs",
         }
     }
 }
@@ -375,10 +436,13 @@ impl SessionSettings {
 
         match (default, settings) {
             (None, _) => Err(SessionSettingsError::NoDefaultSection),
-            (Some(_default), v) => Ok(Self {
-                // default,
-                sessions: v,
-            }),
+            (Some(_default), v) => {
+                validate_endpoints(&v)?;
+                Ok(Self {
+                    // default,
+                    sessions: v,
+                })
+            }
         }
     }
 
@@ -401,16 +465,43 @@ impl SessionSettings {
         self.sessions.as_ref()
     }
 
+    /// Acceptor sessions grouped by `SocketAcceptHost:SocketAcceptPort`.
     pub(crate) fn sessions_by_address(&self) -> BTreeMap<SocketAddr, Vec<SessionSetting>> {
         let mut map = BTreeMap::new();
-        for session in &self.sessions {
-            let port = session.socket_settings().get_endpoint().unwrap();
-            map.entry(port).or_insert(Vec::new()).push(session.clone());
+        for session in self.sessions.iter().filter(|s| s.connection().is_acceptor()) {
+            let addr = *session.connection().socket_addr();
+            map.entry(addr).or_insert(Vec::new()).push(session.clone());
         }
         map
     }
 }
 
+/// Sessions accepted on the same endpoint share one listener, so they need the same socket and
+/// TLS settings: which session a connection is for is only known after the first message.
+fn validate_endpoints(sessions: &[SessionSetting]) -> Result<(), SessionSettingsError> {
+    let mut endpoints: BTreeMap<SocketAddr, &SessionSetting> = BTreeMap::new();
+    let mut errors = Vec::new();
+    for session in sessions.iter().filter(|s| s.connection().is_acceptor()) {
+        let addr = *session.connection().socket_addr();
+        match endpoints.get(&addr) {
+            Some(first) if !first.shares_endpoint_settings(session) => errors.push(format!(
+                "Sessions {} and {} are accepted on {addr} but have different socket or SSL settings",
+                first.session_id(),
+                session.session_id()
+            )),
+            Some(_) => {}
+            None => {
+                endpoints.insert(addr, session);
+            }
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(SessionSettingsError::ValidationErrors(errors))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use dfx_base::session_id::SessionId;
@@ -559,4 +650,174 @@ asdfasd=Y
             Err(SessionSettingsError::NoSuchSetting(_))
         ));
     }
+
+    #[test]
+    fn settings_test_sessions_by_address() {
+        let data = r#"# Comment
+[DEFAULT]
+ConnectionType=acceptor
+BeginString=TEST
+SenderCompID=sender
+SocketAcceptHost=127.0.0.1
+SocketAcceptPort=5000
+[SESSION]
+TargetCompID=target1
+[SESSION]
+TargetCompID=target2
+SocketAcceptPort=5001
+[SESSION]
+TargetCompID=target3
+[SESSION]
+ConnectionType=initiator
+TargetCompID=target4
+SocketConnectHost=127.0.0.1
+SocketConnectPort=5000
+"#;
+        let settings = SessionSettings::from_string(data).unwrap();
+        let by_address = settings.sessions_by_address();
+        assert_eq!(by_address.len(), 2);
+        let targets = |port: u16| -> Vec<String> {
+            by_address[&format!("127.0.0.1:{port}").parse().unwrap()]
+                .iter()
+                .map(|s| s.session_id().target_comp_id().to_string())
+                .collect()
+        };
+        assert_eq!(targets(5000), vec!["target1", "target3"]);
+        assert_eq!(targets(5001), vec!["target2"]);
+    }
+
+    #[test]
+    fn settings_test_conflicting_endpoint_settings() {
+        let data = r#"# Comment
+[DEFAULT]
+ConnectionType=acceptor
+BeginString=TEST
+SenderCompID=sender
+SocketAcceptHost=127.0.0.1
+SocketAcceptPort=5000
+[SESSION]
+TargetCompID=target1
+[SESSION]
+TargetCompID=target2
+SocketNodelay=N
+"#;
+        let settings = SessionSettings::from_string(data);
+        assert!(matches!(
+            settings,
+            Err(SessionSettingsError::ValidationErrors(errors)) if errors.len() == 1
+        ));
+    }
+
+    #[test]
+    fn settings_test_incomplete_proxy() {
+        let data = r#"# Comment
+[DEFAULT]
+ConnectionType=initiator
+BeginString=TEST
+SenderCompID=sender
+SocketConnectHost=127.0.0.1
+SocketConnectPort=5000
+[SESSION]
+TargetCompID=target1
+ProxyType=socks5
+ProxyHost=127.0.0.1
+ProxyUser=user
+"#;
+        let settings = SessionSettings::from_string(data);
+        assert!(matches!(
+            settings,
+            Err(SessionSettingsError::ValidationErrors(errors)) if errors.len() == 2
+        ));
+    }
+
+    #[test]
+    fn settings_test_unresolved_host_behind_proxy() {
+        let data = r#"# Comment
+[DEFAULT]
+ConnectionType=initiator
+BeginString=TEST
+SenderCompID=sender
+SocketConnectHost=fix.invalid
+SocketConnectPort=5000
+[SESSION]
+TargetCompID=target1
+"#;
+        let settings = SessionSettings::from_string(data);
+        assert!(matches!(
+            settings,
+            Err(SessionSettingsError::InvalidValue { setting, .. }) if setting == "SocketConnectHost"
+        ));
+
+        let data = format!("{data}ProxyType=socks5\nProxyHost=127.0.0.1\nProxyPort=1080\n");
+        let settings = SessionSettings::from_string(&data).unwrap();
+        let endpoints = settings.sessions()[0].connect_addrs();
+        assert_eq!(endpoints[0].to_string(), "fix.invalid:5000");
+        assert_eq!(endpoints[0].addr, "127.0.0.1:1080".parse().unwrap());
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "native-tls", feature = "rustls")))]
+    fn settings_test_ssl_enable_without_backend() {
+        let data = r#"# Comment
+[DEFAULT]
+ConnectionType=initiator
+BeginString=TEST
+SenderCompID=sender
+SocketConnectHost=127.0.0.1
+SocketConnectPort=5000
+[SESSION]
+TargetCompID=target1
+[SESSION]
+TargetCompID=target2
+SSLEnable=Y
+"#;
+        match SessionSettings::from_string(data) {
+            Err(SessionSettingsError::TlsError(e)) => assert!(e.contains("rustls feature"), "{e}"),
+            other => panic!("{other:?}"),
+        }
+    }
+
+    #[test]
+    fn settings_test_certificate_comp_ids_requirements() {
+        let errors = |data: &str| match SessionSettings::from_string(data) {
+            Err(SessionSettingsError::ValidationErrors(errors)) => errors,
+            other => panic!("{other:?}"),
+        };
+        // native-tls can't hand the client certificate to the session
+        let backend = if cfg!(feature = "rustls") { 0 } else { 1 };
+
+        let data = r#"# Comment
+[DEFAULT]
+ConnectionType=acceptor
+BeginString=TEST
+SenderCompID=sender
+SocketAcceptPort=5000
+[SESSION]
+TargetCompID=target1
+SSLCertificateCompIDs=CN:TW>TW
+"#;
+        let acceptor = errors(data);
+        assert_eq!(acceptor.len(), 3 + backend, "{acceptor:?}");
+        assert!(acceptor.iter().any(|e| e.contains("SSLEnable=Y")));
+        assert!(acceptor.iter().any(|e| e.contains("SSLRequireClientCertificate=Y")));
+        assert!(acceptor.iter().any(|e| e.contains("SSLCACertificate")));
+
+        let data = r#"# Comment
+[DEFAULT]
+ConnectionType=initiator
+BeginString=TEST
+SenderCompID=sender
+SocketConnectHost=127.0.0.1
+SocketConnectPort=5000
+[SESSION]
+TargetCompID=target1
+SSLEnable=Y
+SSLRequireClientCertificate=Y
+SSLCACertificate=ca.pem
+SSLCertificateCompIDs=CN:TW>TW
+"#;
+        let initiator = errors(data);
+        assert_eq!(initiator.len(), 1 + backend, "{initiator:?}");
+        assert!(initiator[0].contains("only supported by acceptors"));
+    }
 }
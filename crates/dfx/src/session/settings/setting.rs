@@ -7,10 +7,12 @@ use std::{
 use derive_builder::Builder;
 
 use dfx_base::fields::converters::datetime::DateTimeFormat;
-use native_tls::{TlsAcceptor, TlsConnector};
 use crate::{
-    connection::SocketSettings,
-    session::SessionSchedule,
+    connection::{
+        tls::{TlsAcceptor, TlsConnector},
+        SocketSettings,
+    },
+    session::{CertificateCompIds, SessionSchedule},
 };
 
 use dfx_base::session_id::SessionId;
@@ -38,7 +40,109 @@ impl TryFrom<&str> for ConnectionType {
     }
 }
 
+/// Which endpoint an initiator connects to after a disconnect.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) enum FailoverStrategy {
+    /// Move on to the next endpoint, wrapping around to `SocketConnectHost`.
+    #[default]
+    RoundRobin,
+    /// Start over from `SocketConnectHost`, the numbered hosts are only used while it is down.
+    Priority,
+}
+
+impl TryFrom<&str> for FailoverStrategy {
+    type Error = SessionSettingsError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "RoundRobin" => Ok(Self::RoundRobin),
+            "Priority" => Ok(Self::Priority),
+            e => Err(SessionSettingsError::InvalidValue {
+                setting: SettingOption::FailoverStrategy.into(),
+                value: e.into(),
+            }),
+        }
+    }
+}
+
+/// Delays between initiator connection attempts, in the unit of `ReconnectInterval`.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ReconnectOptions {
+    pub(crate) interval: u32,
+    /// Failed attempts double the delay up to this value.
+    pub(crate) max_interval: u32,
+    /// Fraction of the delay randomly added or removed.
+    pub(crate) jitter: f64,
+    /// Consecutive failed attempts before giving up, `0` retries forever.
+    pub(crate) max_attempts: u32,
+    pub(crate) strategy: FailoverStrategy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ProxyType {
+    Socks5,
+    Http,
+}
+
+impl TryFrom<&str> for ProxyType {
+    type Error = SessionSettingsError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "socks5" => Ok(Self::Socks5),
+            "http" => Ok(Self::Http),
+            e => Err(SessionSettingsError::InvalidValue {
+                setting: SettingOption::ProxyType.into(),
+                value: e.into(),
+            }),
+        }
+    }
+}
+
+/// `ProxyUser`/`ProxyPassword`, the password is left out of `Debug` output.
+#[derive(Clone, PartialEq, Eq)]
+pub(crate) struct ProxyCredentials {
+    pub(crate) user: String,
+    pub(crate) password: String,
+}
+
+impl std::fmt::Debug for ProxyCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProxyCredentials")
+            .field("user", &self.user)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The proxy an initiator tunnels its connections through.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ProxyOptions {
+    pub(crate) proxy_type: ProxyType,
+    pub(crate) addr: SocketAddr,
+    pub(crate) credentials: Option<ProxyCredentials>,
+}
+
+/// An endpoint an initiator connects to.
+///
+/// `host` and `port` are kept as configured, a proxy resolves them itself. `addr` is where the
+/// socket connects to: the resolved endpoint, or the proxy when tunneling.
 #[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Endpoint {
+    pub(crate) host: String,
+    pub(crate) port: u16,
+    pub(crate) addr: SocketAddr,
+}
+
+impl std::fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.host.contains(':') {
+            true => write!(f, "[{}]:{}", self.host, self.port),
+            false => write!(f, "{}:{}", self.host, self.port),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub(crate) enum SettingsConnection {
     Acceptor {
         is_dynamic: bool,
@@ -48,10 +152,14 @@ pub(crate) enum SettingsConnection {
         logout_timeout: u32,
     },
     Initiator {
-        connect_addr: SocketAddr,
+        connect_addr: Endpoint,
+        /// `SocketConnectHost<n>:SocketConnectPort<n>` in order of `n`.
+        failover_addrs: Vec<Endpoint>,
+
+        proxy: Option<ProxyOptions>,
 
         // reconnect options
-        reconnect_interval: u32,
+        reconnect: ReconnectOptions,
         heart_bt_int: u32,
         logon_timeout: u32,
         logout_timeout: u32,
@@ -78,7 +186,14 @@ impl SettingsConnection {
     pub(crate) fn socket_addr(&self) -> &SocketAddr {
         match self {
             SettingsConnection::Acceptor { accept_addr, .. } => accept_addr,
-            SettingsConnection::Initiator { connect_addr, .. } => connect_addr,
+            SettingsConnection::Initiator { connect_addr, .. } => &connect_addr.addr,
+        }
+    }
+
+    pub(crate) fn proxy(&self) -> Option<&ProxyOptions> {
+        match self {
+            SettingsConnection::Acceptor { .. } => None,
+            SettingsConnection::Initiator { proxy, .. } => proxy.as_ref(),
         }
     }
 
@@ -182,7 +297,7 @@ pub(crate) enum Persistence {
     None,
 }
 
-#[derive(Builder, Clone, Debug, PartialEq, Eq)]
+#[derive(Builder, Clone, Debug, PartialEq)]
 pub(crate) struct ValidationOptions {
     milliseconds_in_time_stamp: bool,
     refresh_on_logon: bool,
@@ -193,7 +308,13 @@ pub(crate) struct ValidationOptions {
     resend_session_level_rejects: bool,
     time_stamp_precision: DateTimeFormat,
     enable_last_msg_seq_num_processed: bool,
+    enable_next_expected_msg_seq_num: bool,
     max_messages_in_resend_request: u32,
+    resend_chunk_size: u32,
+    max_resend_messages_per_second: u32,
+    // multiples of HeartBtInt without inbound traffic before a TestRequest and a disconnect
+    test_request_delay_multiplier: f64,
+    heart_bt_timeout_multiplier: f64,
     send_logout_before_disconnect_from_timeout: bool,
     ignore_poss_dup_resend_requests: bool,
     requires_orig_sending_time: bool,
@@ -253,10 +374,30 @@ impl ValidationOptions {
         self.enable_last_msg_seq_num_processed
     }
 
+    pub(crate) fn enable_next_expected_msg_seq_num(&self) -> bool {
+        self.enable_next_expected_msg_seq_num
+    }
+
     pub(crate) fn max_messages_in_resend_request(&self) -> u32 {
         self.max_messages_in_resend_request
     }
 
+    pub(crate) fn resend_chunk_size(&self) -> u32 {
+        self.resend_chunk_size
+    }
+
+    pub(crate) fn max_resend_messages_per_second(&self) -> u32 {
+        self.max_resend_messages_per_second
+    }
+
+    pub(crate) fn test_request_delay_multiplier(&self) -> f64 {
+        self.test_request_delay_multiplier
+    }
+
+    pub(crate) fn heart_bt_timeout_multiplier(&self) -> f64 {
+        self.heart_bt_timeout_multiplier
+    }
+
     pub(crate) fn send_logout_before_disconnect_from_timeout(&self) -> bool {
         self.send_logout_before_disconnect_from_timeout
     }
@@ -314,12 +455,25 @@ impl ValidationOptions {
     }
 }
 
+/// The TLS settings [`SslOptions`] are built from, sessions accepted on the same endpoint
+/// must agree on them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct SslSettings {
+    pub(crate) certificate: Option<String>,
+    pub(crate) min_protocol: Option<String>,
+    pub(crate) max_protocol: Option<String>,
+    pub(crate) require_client_certificate: Option<String>,
+    pub(crate) ca_certificate: Option<String>,
+}
+
 #[derive(Builder, Clone, Debug)]
 pub(crate) struct SessionSetting {
     session_id: SessionId,
     connection: SettingsConnection,
     socket_options: SocketOptions,
     ssl_options: Option<SslOptions>,
+    ssl_settings: Option<SslSettings>,
+    certificate_comp_ids: Option<CertificateCompIds>,
     logging: LoggingOptions,
     persistence: Persistence,
     default_appl_ver_id: Option<String>,
@@ -348,6 +502,16 @@ impl SessionSetting {
         self.ssl_options.as_ref()
     }
 
+    /// `SSLCertificateCompIDs`, checked against the client certificate on Logon.
+    pub(crate) fn certificate_comp_ids(&self) -> Option<&CertificateCompIds> {
+        self.certificate_comp_ids.as_ref()
+    }
+
+    /// Whether `other` can be accepted on the same endpoint: the same socket and TLS settings.
+    pub(crate) fn shares_endpoint_settings(&self, other: &SessionSetting) -> bool {
+        self.socket_options == other.socket_options && self.ssl_settings == other.ssl_settings
+    }
+
     pub(crate) fn logging(&self) -> &LoggingOptions {
         &self.logging
     }
@@ -514,13 +678,28 @@ impl SessionSetting {
     }
 
     pub(crate) fn socket_settings(&self) -> SocketSettings {
-        SocketSettings::new(self.connection.socket_addr().clone(), self.socket_options.clone(), self.ssl_options.clone())
+        let socket_settings = SocketSettings::new(self.connection.socket_addr().clone(), self.socket_options.clone(), self.ssl_options.clone())
+            .with_proxy(self.connection.proxy().cloned());
+        match &self.connection {
+            SettingsConnection::Initiator { connect_addr, .. } => socket_settings.with_endpoint(connect_addr.clone()),
+            SettingsConnection::Acceptor { .. } => socket_settings,
+        }
     }
 
-    pub(crate) fn reconnect_interval(&self) -> Option<u32> {
-        match self.connection {
+    pub(crate) fn reconnect_options(&self) -> Option<&ReconnectOptions> {
+        match &self.connection {
             SettingsConnection::Acceptor { .. } => None,
-            SettingsConnection::Initiator { reconnect_interval, .. } => Some(reconnect_interval),
+            SettingsConnection::Initiator { reconnect, .. } => Some(reconnect),
+        }
+    }
+
+    /// `SocketConnectHost` followed by the failover endpoints, empty for acceptors.
+    pub(crate) fn connect_addrs(&self) -> Vec<Endpoint> {
+        match &self.connection {
+            SettingsConnection::Acceptor { .. } => Vec::new(),
+            SettingsConnection::Initiator { connect_addr, failover_addrs, .. } => {
+                std::iter::once(connect_addr.clone()).chain(failover_addrs.iter().cloned()).collect()
+            }
         }
     }
 
@@ -0,0 +1,146 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use dfx_base::field_map::Tag;
+use dfx_base::fix_values::BusinessRejectReason;
+use dfx_base::fix_values::SessionRejectReason;
+use dfx_base::message::Message;
+use dfx_base::session_id::SessionId;
+
+/// Outcome of a failed [`ValidationRule`].
+///
+/// The session answers a `SessionReject` with a Reject (35=3) and a `BusinessReject`
+/// with a BusinessMessageReject (35=j), in the same way as data dictionary failures.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationRuleError {
+    SessionReject {
+        reason: SessionRejectReason,
+        field: Option<Tag>,
+    },
+    BusinessReject {
+        reason: BusinessRejectReason,
+        field: Option<Tag>,
+    },
+}
+
+impl ValidationRuleError {
+    pub fn session_reject(reason: SessionRejectReason, field: Option<Tag>) -> Self {
+        ValidationRuleError::SessionReject { reason, field }
+    }
+
+    pub fn business_reject(reason: BusinessRejectReason, field: Option<Tag>) -> Self {
+        ValidationRuleError::BusinessReject { reason, field }
+    }
+
+    /// Convenience for the most common business rule: `field` must be present.
+    pub fn conditionally_required_field_missing(field: Tag) -> Self {
+        ValidationRuleError::BusinessReject {
+            reason: BusinessRejectReason::CONDITIONALLY_REQUIRED_FIELD_MISSING(),
+            field: Some(field),
+        }
+    }
+}
+
+/// A custom rule that runs after the data dictionary validated an incoming message.
+///
+/// Rules are registered per MsgType in [`ValidationRules`].
+pub trait ValidationRule: Send + Sync {
+    fn validate(&self, message: &Message, session_id: &SessionId) -> Result<(), ValidationRuleError>;
+}
+
+impl<F> ValidationRule for F
+where
+    F: Fn(&Message, &SessionId) -> Result<(), ValidationRuleError> + Send + Sync,
+{
+    fn validate(&self, message: &Message, session_id: &SessionId) -> Result<(), ValidationRuleError> {
+        self(message, session_id)
+    }
+}
+
+/// Set of [`ValidationRule`]s keyed by MsgType.
+///
+/// Cloning is cheap, rules are shared between the sessions created by an acceptor or initiator.
+#[derive(Clone, Default)]
+pub struct ValidationRules {
+    rules: BTreeMap<String, Vec<Arc<dyn ValidationRule>>>,
+}
+
+impl std::fmt::Debug for ValidationRules {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let counts: BTreeMap<&String, usize> = self.rules.iter().map(|(k, v)| (k, v.len())).collect();
+        f.debug_struct("ValidationRules").field("rules", &counts).finish()
+    }
+}
+
+impl ValidationRules {
+    pub fn new() -> Self {
+        ValidationRules::default()
+    }
+
+    /// Register `rule` for messages of type `msg_type`, rules run in registration order.
+    pub fn add<R: ValidationRule + 'static>(&mut self, msg_type: &str, rule: R) -> &mut Self {
+        self.rules
+            .entry(msg_type.into())
+            .or_default()
+            .push(Arc::new(rule));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Runs the rules registered for `msg_type`, stopping at the first failure.
+    pub fn validate(
+        &self,
+        message: &Message,
+        msg_type: &str,
+        session_id: &SessionId,
+    ) -> Result<(), ValidationRuleError> {
+        if let Some(rules) = self.rules.get(msg_type) {
+            for rule in rules {
+                rule.validate(message, session_id)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use dfx_base::fix_values::BusinessRejectReason;
+    use dfx_base::message::Message;
+    use dfx_base::session_id::SessionId;
+    use dfx_base::tags;
+
+    use super::{ValidationRuleError, ValidationRules};
+
+    fn price_required_for_limit(message: &Message, _session_id: &SessionId) -> Result<(), ValidationRuleError> {
+        let is_limit = matches!(message.get_string(tags::OrdType), Ok(v) if v == "2");
+        if is_limit && !message.is_field_set(tags::Price) {
+            return Err(ValidationRuleError::conditionally_required_field_missing(tags::Price));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn rules_run_for_registered_msg_type() {
+        let session_id = SessionId::new("FIX.4.4", "SENDER", "", "", "TARGET", "", "");
+        let mut rules = ValidationRules::new();
+        rules.add("D", price_required_for_limit);
+
+        let mut message = Message::default();
+        message.set_tag_value(tags::OrdType, "2");
+        assert_eq!(
+            rules.validate(&message, "D", &session_id),
+            Err(ValidationRuleError::BusinessReject {
+                reason: BusinessRejectReason::CONDITIONALLY_REQUIRED_FIELD_MISSING(),
+                field: Some(tags::Price),
+            })
+        );
+        assert!(rules.validate(&message, "G", &session_id).is_ok());
+
+        message.set_tag_value(tags::Price, "1.5");
+        assert!(rules.validate(&message, "D", &session_id).is_ok());
+    }
+}
@@ -0,0 +1,26 @@
+use dfx::{
+    data_dictionary::DataDictionary,
+    data_dictionary_provider::{DataDictionaryProvider, DefaultDataDictionaryProvider},
+};
+
+mod common;
+
+const SESSION: &str = "
+BeginString=FIXT.1.1
+UseDataDictionary=Y
+DefaultApplVerID=FIX.5.0SP2
+AppDataDictionary=../../spec/FIX50SP2.xml
+TransportDataDictionary=../../spec/FIXT11.xml
+";
+
+#[test]
+pub fn test_accept_appl_ver_id() {
+    // FIX.4.4 messages on the same session, by ApplVerID
+    let mut data_dictionary_provider = DefaultDataDictionaryProvider::new();
+    data_dictionary_provider.add_application_data_dictionary(
+        "6",
+        DataDictionary::from_file("../../spec/FIX44.xml").unwrap(),
+    );
+    let acceptor = common::acceptor_with_dictionaries(SESSION, data_dictionary_provider);
+    common::run_acceptor(acceptor, "tests/definitions/server/accept_appl_ver_id.def");
+}
@@ -0,0 +1,37 @@
+use std::sync::{Arc, Mutex};
+
+use dfx::session::{LogonDecision, LogonRequest};
+
+mod common;
+
+const SESSION: &str = "
+ResetOnLogon=Y
+UseDataDictionary=Y
+DataDictionary=../../spec/FIX44.xml
+";
+
+#[test]
+pub fn test_accept_authentication() {
+    let peers = Arc::new(Mutex::new(Vec::new()));
+    let authenticator_peers = peers.clone();
+    let authenticator = move |request: &LogonRequest<'_>| {
+        authenticator_peers
+            .lock()
+            .unwrap()
+            .push(request.peer().address());
+        match (request.username().as_deref(), request.password().as_deref()) {
+            (Some("trader"), Some("secret")) => LogonDecision::Accept,
+            (Some("expired"), Some("secret")) => {
+                LogonDecision::require_password_change("Password expired")
+            }
+            _ => LogonDecision::reject("Invalid username or password"),
+        }
+    };
+
+    let acceptor = common::acceptor(SESSION).with_logon_authenticator(authenticator);
+    common::run_acceptor(acceptor, "tests/definitions/server/accept_authentication.def");
+
+    let peers = peers.lock().unwrap();
+    assert_eq!(peers.len(), 3);
+    assert!(peers.iter().all(|peer| matches!(peer, Some(addr) if addr.ip().is_loopback())));
+}
@@ -0,0 +1,92 @@
+use std::{
+    io::{Read, Write},
+    net::TcpStream,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use dfx::{
+    connection::{ConnectionError, ConnectionObserver, SocketAcceptor},
+    data_dictionary_provider::DefaultDataDictionaryProvider,
+    logging::PrintlnLogFactory,
+    message::DefaultMessageFactory,
+    message_store::MemoryStoreFactory,
+    session::{PeerIdentity, SessionSettings},
+    session_id::SessionId,
+};
+
+mod common;
+use common::runner;
+use common::TestApplication;
+
+const CFG: &str = "
+[DEFAULT]
+ConnectionType=acceptor
+SocketAcceptHost=127.0.0.1
+SocketAcceptPort=0
+NonStopSession=Y
+SenderCompID=ISLD
+UseDataDictionary=N
+HeartBtInt=20
+BeginString=FIX.4.4
+[SESSION]
+TargetCompID=*
+IsDynamic=Y
+";
+
+/// Causes of the connections closed before a session was identified.
+#[derive(Clone, Default)]
+struct Causes(Arc<Mutex<Vec<String>>>);
+
+impl ConnectionObserver for Causes {
+    fn on_disconnected(
+        &self,
+        session_id: Option<&SessionId>,
+        _peer: &PeerIdentity,
+        cause: Option<&ConnectionError>,
+    ) {
+        if let (None, Some(cause)) = (session_id, cause) {
+            self.0.lock().unwrap().push(cause.to_string());
+        }
+    }
+}
+
+#[test]
+pub fn test_accept_after_bad_client() {
+    let causes = Causes::default();
+    let session_settings = SessionSettings::from_string(CFG).unwrap();
+    let mut acceptor = SocketAcceptor::new(
+        &session_settings,
+        TestApplication::new(),
+        MemoryStoreFactory::new(),
+        DefaultDataDictionaryProvider::new(),
+        PrintlnLogFactory::new(),
+        DefaultMessageFactory::new(),
+    )
+    .with_connection_observer(causes.clone());
+    acceptor.start().unwrap();
+    while acceptor.endpoints().is_empty() {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    let endpoint = acceptor.endpoints()[0];
+
+    // a message that frames but doesn't parse is dropped with its connection
+    let mut client = TcpStream::connect(endpoint).unwrap();
+    client.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+    client.write_all(b"8=FIX.4.4\x019=8\x01garbage\x0110=000\x01").unwrap();
+    let mut buffer = [0; 64];
+    assert!(matches!(client.read(&mut buffer), Ok(0) | Err(_)));
+
+    // a client that goes away without a word
+    drop(TcpStream::connect(endpoint).unwrap());
+
+    let path = "tests/definitions/server/accept_multiple_endpoints.def";
+    let runner_thread = runner::create_thread(runner::steps(path), endpoint.port().into(), path);
+    let result = runner_thread.join().unwrap();
+    acceptor.stop();
+    if let Err(message) = result {
+        panic!("Steps failed:\n{message}\n");
+    }
+    // the garbled message is reported although no session was created
+    assert_eq!(causes.0.lock().unwrap().len(), 1, "{:?}", causes.0.lock().unwrap());
+}
@@ -0,0 +1,45 @@
+use std::time::{Duration, Instant};
+
+use dfx::{session::SessionHandle, session_id::SessionId};
+
+mod common;
+use common::runner;
+
+const SESSION: &str = "
+TestRequestDelayMultiplier=1.5
+HeartBtTimeoutMultiplier=3
+";
+
+#[test]
+pub fn test_accept_heartbeat_thresholds() {
+    let mut acceptor = common::acceptor(SESSION);
+    acceptor.start().unwrap();
+    let port = acceptor.endpoints()[0].port();
+
+    let path = "tests/definitions/server/accept_heartbeat_thresholds.def";
+    let start = Instant::now();
+    let runner_thread = runner::create_thread(runner::steps(path), port.into(), path);
+
+    // the heartbeat after 1s stays the last one until the disconnect after 3s
+    let handle = SessionHandle::new(SessionId::new("FIX.4.4", "ISLD", "", "", "TW", "", ""));
+    let status = loop {
+        match handle.status() {
+            Ok(status) if status.heartbeat_jitter().is_some() => break status,
+            _ => {
+                assert!(!runner_thread.is_finished(), "no heartbeat sent");
+                std::thread::sleep(Duration::from_millis(10));
+            }
+        }
+    };
+    let jitter = status.heartbeat_jitter().unwrap();
+    assert!(jitter < Duration::from_millis(200), "heartbeat sent {jitter:?} late");
+    assert_eq!(status.max_heartbeat_jitter(), jitter);
+
+    let result = runner_thread.join().unwrap();
+    let elapsed = start.elapsed();
+    acceptor.stop();
+    if let Err(message) = result {
+        panic!("Steps failed:\n{message}\n");
+    }
+    assert!(elapsed >= Duration::from_millis(2900), "disconnected after {elapsed:?}");
+}
@@ -32,7 +32,7 @@ pub fn test_accept() {
     );
 
     let steps = runner::steps("tests/definitions/server/accept_logon.def");
-    acceptor.start();
+    acceptor.start().unwrap();
 
     let runner_thread = runner::create_thread(steps, 40000, "tests/definitions/server/accept_logon.def");
     let start = Instant::now();
@@ -60,7 +60,7 @@ pub fn test_accept() {
     );
 
     let steps = runner::steps("tests/definitions/server/accept_logon.def");
-    acceptor.start();
+    acceptor.start().unwrap();
 
     let runner_thread = runner::create_thread(steps, 40000, "tests/definitions/server/accept_logon.def");
     let start = Instant::now();
@@ -0,0 +1,105 @@
+#![allow(dead_code)]
+use std::net::TcpListener;
+
+use dfx::{
+    connection::{AcceptorError, SocketAcceptor},
+    data_dictionary_provider::DefaultDataDictionaryProvider,
+    logging::PrintlnLogFactory,
+    message::DefaultMessageFactory,
+    message_store::MemoryStoreFactory,
+    session::SessionSettings,
+};
+
+mod common;
+use common::runner;
+use common::TestApplication;
+
+const CFG: &str = "
+[DEFAULT]
+ConnectionType=acceptor
+SocketAcceptHost=127.0.0.1
+SocketAcceptPort=0
+NonStopSession=Y
+SenderCompID=ISLD
+UseDataDictionary=N
+HeartBtInt=20
+BeginString=FIX.4.4
+[SESSION]
+TargetCompID=TW
+[SESSION]
+TargetCompID=TW3
+[SESSION]
+TargetCompID=TW2
+SocketAcceptHost=0.0.0.0
+";
+
+fn run(path: &str, port: u16) {
+    let runner_thread = runner::create_thread(runner::steps(path), port.into(), path);
+    if let Err(message) = runner_thread.join().unwrap() {
+        panic!("Steps failed:\n{message}\n");
+    }
+}
+
+#[test]
+pub fn test_accept_multiple_endpoints() {
+    let session_settings = SessionSettings::from_string(CFG).unwrap();
+    let mut acceptor = SocketAcceptor::new(
+        &session_settings,
+        TestApplication::new(),
+        MemoryStoreFactory::new(),
+        DefaultDataDictionaryProvider::new(),
+        PrintlnLogFactory::new(),
+        DefaultMessageFactory::new(),
+    );
+    acceptor.start().unwrap();
+
+    let endpoints = acceptor.endpoints();
+    assert_eq!(endpoints.len(), 2);
+    let port = |loopback: bool| {
+        endpoints
+            .iter()
+            .find(|e| e.ip().is_loopback() == loopback)
+            .expect("endpoint bound")
+            .port()
+    };
+
+    run("tests/definitions/server/accept_multiple_endpoints.def", port(true));
+    run("tests/definitions/server/accept_multiple_endpoints_wrong.def", port(true));
+    run("tests/definitions/server/accept_multiple_endpoints_second.def", port(false));
+    acceptor.stop();
+}
+
+#[test]
+pub fn test_accept_bind_failure() {
+    let taken = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = taken.local_addr().unwrap().port();
+    let cfg = format!(
+        "
+[DEFAULT]
+ConnectionType=acceptor
+SocketAcceptHost=127.0.0.1
+SocketAcceptPort={port}
+SenderCompID=ISLD
+UseDataDictionary=N
+HeartBtInt=20
+BeginString=FIX.4.4
+[SESSION]
+TargetCompID=TW
+"
+    );
+    let session_settings = SessionSettings::from_string(&cfg).unwrap();
+    let mut acceptor = SocketAcceptor::new(
+        &session_settings,
+        TestApplication::new(),
+        MemoryStoreFactory::new(),
+        DefaultDataDictionaryProvider::new(),
+        PrintlnLogFactory::new(),
+        DefaultMessageFactory::new(),
+    );
+    match acceptor.start() {
+        Err(AcceptorError::BindError(_, addr)) => assert_eq!(addr, taken.local_addr().unwrap()),
+        Err(e) => panic!("unexpected error {e}"),
+        Ok(_) => panic!("acceptor started on a port in use"),
+    }
+    assert!(acceptor.endpoints().is_empty());
+}
@@ -0,0 +1,7 @@
+mod common;
+
+#[test]
+pub fn test_accept_next_expected_msg_seq_num() {
+    let acceptor = common::acceptor("EnableNextExpectedMsgSeqNum=Y\n");
+    common::run_acceptor(acceptor, "tests/definitions/server/accept_next_expected_msg_seq_num.def");
+}
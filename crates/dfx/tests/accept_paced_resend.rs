@@ -0,0 +1,103 @@
+use std::{
+    io::{Read, Write},
+    net::TcpStream,
+    time::{Duration, Instant},
+};
+
+use dfx::parser::Parser;
+
+mod common;
+
+const SESSION: &str = "
+ResendChunkSize=2
+MaxResendMessagesPerSecond=10
+";
+
+/// A counterparty reading every message of a chunk, the runner blocks on the socket while a
+/// chunk's later messages are still buffered.
+struct Counterparty {
+    stream: TcpStream,
+    parser: Parser,
+    seq_num: u32,
+}
+
+impl Counterparty {
+    fn send(&mut self, fields: &str) {
+        let now = chrono::Utc::now().format("%Y%m%d-%H:%M:%S");
+        let body = format!("{fields}34={}|49=TW|52={now}|56=ISLD|", self.seq_num).replace('|', "\x01");
+        let message = format!("8=FIX.4.4\x019={}\x01{body}", body.len());
+        let checksum = message.bytes().map(u32::from).sum::<u32>() % 256;
+        self.stream
+            .write_all(format!("{message}10={checksum:03}\x01").as_bytes())
+            .unwrap();
+        self.seq_num += 1;
+    }
+
+    /// The MsgType, MsgSeqNum and PossDupFlag of the next message.
+    fn receive(&mut self) -> (String, u32, bool) {
+        let mut buffer = [0; 512];
+        let message = loop {
+            if let Some(message) = self.parser.read_fix_message().unwrap() {
+                break String::from_utf8(message).unwrap();
+            }
+            let read = self.stream.read(&mut buffer).unwrap();
+            assert!(read > 0, "disconnected");
+            self.parser.add_to_stream(&buffer[..read]);
+        };
+        let field = |tag: &str| {
+            message
+                .split('\x01')
+                .find_map(|f| f.strip_prefix(tag))
+                .map(str::to_string)
+        };
+        (
+            field("35=").unwrap(),
+            field("34=").unwrap().parse().unwrap(),
+            field("43=").as_deref() == Some("Y"),
+        )
+    }
+}
+
+#[test]
+pub fn test_accept_paced_resend() {
+    let mut acceptor = common::acceptor(SESSION);
+    acceptor.start().unwrap();
+
+    let stream = TcpStream::connect(acceptor.endpoints()[0]).unwrap();
+    stream.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+    let mut counterparty = Counterparty { stream, parser: Parser::default(), seq_num: 1 };
+    counterparty.send("35=A|98=0|108=30|");
+    assert_eq!(counterparty.receive(), ("A".into(), 1, false));
+    counterparty.send("35=R|131=first|");
+    assert_eq!(counterparty.receive(), ("R".into(), 2, false));
+    counterparty.send("35=1|112=HELLO|");
+    assert_eq!(counterparty.receive(), ("0".into(), 3, false));
+    counterparty.send("35=R|131=second|");
+    assert_eq!(counterparty.receive(), ("R".into(), 4, false));
+    counterparty.send("35=R|131=third|");
+    assert_eq!(counterparty.receive(), ("R".into(), 5, false));
+
+    // the reply to the echo of "fourth" is held back until the resend is complete
+    let start = Instant::now();
+    counterparty.send("35=2|7=1|16=0|");
+    counterparty.send("35=R|131=fourth|");
+    let replies: Vec<_> = (0..6).map(|_| counterparty.receive()).collect();
+    let elapsed = start.elapsed();
+    assert_eq!(
+        replies,
+        vec![
+            ("4".into(), 1, true),
+            ("R".into(), 2, true),
+            ("4".into(), 3, true),
+            ("R".into(), 4, true),
+            ("R".into(), 5, true),
+            ("R".into(), 6, false),
+        ]
+    );
+    // MsgSeqNum 1 to 5 at 10 per second
+    assert!(elapsed >= Duration::from_millis(400), "resend was not paced: {elapsed:?}");
+
+    counterparty.send("35=5|");
+    assert_eq!(counterparty.receive(), ("5".into(), 7, false));
+    acceptor.stop();
+}
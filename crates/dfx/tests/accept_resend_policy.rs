@@ -0,0 +1,15 @@
+use std::time::Duration;
+
+use dfx::session::ResendPolicy;
+
+mod common;
+
+#[test]
+pub fn test_accept_resend_policy() {
+    let mut policy = ResendPolicy::new();
+    policy
+        .gap_fill("d")
+        .max_age("R", Duration::from_secs(60 * 60));
+    let acceptor = common::acceptor("").with_resend_policy(policy);
+    common::run_acceptor(acceptor, "tests/definitions/server/accept_resend_policy.def");
+}
@@ -0,0 +1,106 @@
+use std::sync::{Arc, Mutex};
+
+use dfx::{
+    connection::SocketAcceptor,
+    data_dictionary_provider::DefaultDataDictionaryProvider,
+    field_map::FieldMapError,
+    logging::PrintlnLogFactory,
+    message::{DefaultMessageFactory, Message},
+    message_store::MemoryStoreFactory,
+    session::{
+        Application, ApplicationError, DoNotAccept, FromAppError, LogonReject, Session,
+        SessionError, SessionHandle, SessionSettings,
+    },
+    session_id::SessionId,
+    tags,
+};
+
+mod common;
+use common::runner;
+
+const CFG: &str = "
+[DEFAULT]
+ConnectionType=acceptor
+SocketAcceptPort=0
+NonStopSession=Y
+SenderCompID=ISLD
+TargetCompID=TW
+UseDataDictionary=N
+HeartBtInt=30
+[SESSION]
+BeginString=FIX.4.4
+";
+
+/// Replies to news with blocking calls on the session that received it.
+#[derive(Clone, Default)]
+struct BlockingApplication {
+    results: Arc<Mutex<Vec<Result<(), SessionError>>>>,
+}
+
+impl Application for BlockingApplication {
+    fn on_create(&mut self, _session_id: &SessionId) -> Result<(), DoNotAccept> {
+        Ok(())
+    }
+
+    fn on_logon(&mut self, _session_id: &SessionId) -> Result<(), LogonReject> {
+        Ok(())
+    }
+
+    fn on_logout(&mut self, _session_id: &SessionId) -> Result<(), ApplicationError> {
+        Ok(())
+    }
+
+    fn to_admin(&mut self, message: Message, _session_id: &SessionId) -> Result<Message, FieldMapError> {
+        Ok(message)
+    }
+
+    fn from_admin(&mut self, _message: &Message, _session_id: &SessionId) -> Result<(), FieldMapError> {
+        Ok(())
+    }
+
+    fn to_app(&mut self, _message: &mut Message, _session_id: &SessionId) -> Result<(), ApplicationError> {
+        Ok(())
+    }
+
+    fn from_app(&mut self, _message: &Message, session_id: &SessionId) -> Result<(), FromAppError> {
+        let mut reply = Message::default();
+        reply.header_mut().set_tag_value(tags::MsgType, "B");
+        reply.set_tag_value(tags::Headline, "WORLD");
+        let mut results = self.results.lock().unwrap();
+        results.push(Session::send_to_session_sync(session_id, reply).map(|_| ()));
+        results.push(SessionHandle::new(session_id.clone()).status().map(|_| ()));
+        Ok(())
+    }
+}
+
+#[test]
+pub fn test_accept_send_sync_from_callback() {
+    let app = BlockingApplication::default();
+    let session_settings = SessionSettings::from_string(CFG).unwrap();
+    let mut acceptor = SocketAcceptor::new(
+        &session_settings,
+        app.clone(),
+        MemoryStoreFactory::new(),
+        DefaultDataDictionaryProvider::new(),
+        PrintlnLogFactory::new(),
+        DefaultMessageFactory::new(),
+    );
+    acceptor.start().unwrap();
+    let port = acceptor.endpoints()[0].port();
+
+    let path = "tests/definitions/server/accept_send_sync.def";
+    let result = runner::create_thread(runner::steps(path), port.into(), path)
+        .join()
+        .unwrap();
+    acceptor.stop();
+    if let Err(message) = result {
+        panic!("Steps failed:\n{message}\n");
+    }
+
+    let results = app.results.lock().unwrap();
+    assert_eq!(results.len(), 2);
+    assert!(
+        results.iter().all(|r| matches!(r, Err(SessionError::WouldBlock(_)))),
+        "{results:?}"
+    );
+}
@@ -0,0 +1,86 @@
+use std::{sync::mpsc::channel, time::Duration};
+
+use dfx::{
+    connection::{LogoutOutcome, SessionShutdown, SocketAcceptor},
+    data_dictionary_provider::DefaultDataDictionaryProvider,
+    logging::PrintlnLogFactory,
+    message::DefaultMessageFactory,
+    message_store::MemoryStoreFactory,
+    session::{SessionEvent, SessionEvents, SessionSettings},
+};
+
+mod common;
+use common::runner;
+use common::TestApplication;
+
+const CFG: &str = "
+[DEFAULT]
+ConnectionType=acceptor
+SocketAcceptHost=127.0.0.1
+SocketAcceptPort=0
+NonStopSession=Y
+SenderCompID=ISLD
+UseDataDictionary=N
+HeartBtInt=20
+LogoutTimeout=1
+BeginString=FIX.4.4
+[SESSION]
+TargetCompID=TW
+[SESSION]
+TargetCompID=TW2
+";
+
+fn shutdown(path: &str, reason: Option<&str>) -> Vec<SessionShutdown> {
+    let (tx, rx) = channel();
+    let mut events = SessionEvents::new();
+    events.subscribe(tx);
+
+    let session_settings = SessionSettings::from_string(CFG).unwrap();
+    let mut acceptor = SocketAcceptor::new(
+        &session_settings,
+        TestApplication::new(),
+        MemoryStoreFactory::new(),
+        DefaultDataDictionaryProvider::new(),
+        PrintlnLogFactory::new(),
+        DefaultMessageFactory::new(),
+    )
+    .with_session_events(events);
+    acceptor.start().unwrap();
+    while acceptor.endpoints().is_empty() {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    let port = acceptor.endpoints()[0].port();
+    let runner_thread = runner::create_thread(runner::steps(path), port.into(), path);
+
+    while let Ok((_, event)) = rx.recv_timeout(Duration::from_secs(5)) {
+        if event == SessionEvent::LoggedOn {
+            break;
+        }
+    }
+    let mut shutdowns = match reason {
+        Some(reason) => acceptor.shutdown(reason),
+        None => acceptor.stop(),
+    };
+    if let Err(message) = runner_thread.join().unwrap() {
+        panic!("Steps failed:\n{message}\n");
+    }
+    shutdowns.sort_by_key(|s| s.session_id().target_comp_id().to_string());
+    shutdowns
+}
+
+#[test]
+pub fn test_shutdown_logs_out_sessions() {
+    let shutdowns = shutdown(
+        "tests/definitions/server/accept_shutdown.def",
+        Some("Maintenance window"),
+    );
+    let outcomes: Vec<LogoutOutcome> = shutdowns.iter().map(|s| s.outcome()).collect();
+    assert_eq!(outcomes, [LogoutOutcome::LoggedOut, LogoutOutcome::Disconnected]);
+}
+
+#[test]
+pub fn test_shutdown_times_out_unanswered_logout() {
+    let shutdowns = shutdown("tests/definitions/server/accept_shutdown_timeout.def", None);
+    let outcomes: Vec<LogoutOutcome> = shutdowns.iter().map(|s| s.outcome()).collect();
+    assert_eq!(outcomes, [LogoutOutcome::TimedOut, LogoutOutcome::Disconnected]);
+}
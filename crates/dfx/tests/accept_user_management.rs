@@ -0,0 +1,33 @@
+use std::sync::Arc;
+
+use dfx::session::{
+    CredentialError, CredentialStore, LogonDecision, LogonRequest, MemoryCredentialStore,
+};
+
+mod common;
+
+#[test]
+pub fn test_accept_user_management() {
+    let credential_store = Arc::new(MemoryCredentialStore::new());
+    credential_store
+        .add_user("trader", "secret")
+        .expire_password("trader")
+        .add_user("blocked", "secret")
+        .expire_password("blocked");
+    let authenticator = |request: &LogonRequest<'_>| match request.username().as_deref() {
+        Some("blocked") => LogonDecision::reject("User is blocked"),
+        _ => LogonDecision::Accept,
+    };
+
+    let acceptor = common::acceptor("ResetOnLogon=Y\n")
+        .with_credential_store(credential_store.clone())
+        .with_logon_authenticator(authenticator);
+    common::run_acceptor(acceptor, "tests/definitions/server/accept_user_management.def");
+
+    assert_eq!(credential_store.authenticate("trader", "changed"), Ok(()));
+    // the rejected Logon did not change the password
+    assert_eq!(
+        credential_store.authenticate("blocked", "secret"),
+        Err(CredentialError::PasswordExpired)
+    );
+}
@@ -0,0 +1,25 @@
+use dfx::{
+    message::Message,
+    session::{ValidationRuleError, ValidationRules},
+    session_id::SessionId,
+    tags,
+};
+
+mod common;
+
+fn price_required_for_limit(message: &Message, _session_id: &SessionId) -> Result<(), ValidationRuleError> {
+    let is_limit = matches!(message.get_string(tags::OrdType), Ok(v) if v == "2");
+    if is_limit && !message.is_field_set(tags::Price) {
+        return Err(ValidationRuleError::conditionally_required_field_missing(tags::Price));
+    }
+    Ok(())
+}
+
+#[test]
+pub fn test_accept_validation_rules() {
+    let mut validation_rules = ValidationRules::new();
+    validation_rules.add("D", price_required_for_limit);
+
+    let acceptor = common::acceptor("ResetOnLogon=Y\n").with_validation_rules(validation_rules);
+    common::run_acceptor(acceptor, "tests/definitions/server/accept_validation_rules.def");
+}
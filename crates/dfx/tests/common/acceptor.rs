@@ -0,0 +1,74 @@
+use dfx::{
+    connection::SocketAcceptor,
+    data_dictionary_provider::{DataDictionaryProvider, DefaultDataDictionaryProvider},
+    logging::PrintlnLogFactory,
+    message::DefaultMessageFactory,
+    message_store::MemoryStoreFactory,
+    session::SessionSettings,
+};
+
+use super::runner;
+use super::TestApplication;
+
+const CFG: &str = "
+[DEFAULT]
+ConnectionType=acceptor
+SocketAcceptPort=0
+NonStopSession=Y
+SenderCompID=ISLD
+TargetCompID=TW
+UseDataDictionary=N
+HeartBtInt=30
+BeginString=FIX.4.4
+[SESSION]
+";
+
+pub type TestAcceptor<DDP = DefaultDataDictionaryProvider> = SocketAcceptor<
+    TestApplication,
+    MemoryStoreFactory,
+    DDP,
+    PrintlnLogFactory,
+    DefaultMessageFactory,
+>;
+
+/// An acceptor of the FIX.4.4 session ISLD->TW on a free port, `session` holds the settings of
+/// the test on top of the defaults.
+pub fn acceptor(session: &str) -> TestAcceptor {
+    acceptor_with_dictionaries(session, DefaultDataDictionaryProvider::new())
+}
+
+/// An [`acceptor`] looking up the data dictionaries in `data_dictionary_provider`.
+pub fn acceptor_with_dictionaries<DDP>(
+    session: &str,
+    data_dictionary_provider: DDP,
+) -> TestAcceptor<DDP>
+where
+    DDP: DataDictionaryProvider + Send + Clone + 'static,
+{
+    let session_settings = SessionSettings::from_string(&format!("{CFG}{session}")).unwrap();
+    SocketAcceptor::new(
+        &session_settings,
+        TestApplication::new(),
+        MemoryStoreFactory::new(),
+        data_dictionary_provider,
+        PrintlnLogFactory::new(),
+        DefaultMessageFactory::new(),
+    )
+}
+
+/// Starts `acceptor`, runs the steps of the definition at `path` against it and stops it again.
+/// Panics if a step failed.
+pub fn run_acceptor<DDP>(mut acceptor: TestAcceptor<DDP>, path: &str)
+where
+    DDP: DataDictionaryProvider + Send + Clone + 'static,
+{
+    acceptor.start().unwrap();
+    let port = acceptor.endpoints()[0].port();
+    let result = runner::create_thread(runner::steps(path), port.into(), path)
+        .join()
+        .unwrap();
+    acceptor.stop();
+    if let Err(message) = result {
+        panic!("Steps failed:\n{message}\n");
+    }
+}
@@ -1,3 +1,9 @@
+// every test binary compiles its own copy and uses only some of the helpers
+#![allow(dead_code, unused_imports)]
+#[cfg(test)]
+mod acceptor;
+#[cfg(test)]
+pub use acceptor::*;
 #[cfg(test)]
 pub mod runner;
 #[cfg(test)]
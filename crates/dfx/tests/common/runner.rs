@@ -251,6 +251,16 @@ fn do_receive(s: &mut TcpStream, message: String, parser: &mut Parser) -> Result
     let other;
     let start = std::time::Instant::now();
     loop {
+        // messages received in one read are parsed before blocking on the socket again
+        match parser.read_fix_message() {
+            Ok(message) => {
+                if let Some(value) = message {
+                    other = Some(value);
+                    break;
+                }
+            }
+            Err(_) => panic!("Test failed reading fix message."),
+        };
         let read = match s.read(&mut buffer) {
             Ok(read) => Ok(read),
             Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => Ok(0),
@@ -263,15 +273,6 @@ fn do_receive(s: &mut TcpStream, message: String, parser: &mut Parser) -> Result
         if (start - std::time::Instant::now()) > Duration::from_secs(35) {
             panic!("Test failed reading fix message: Timeout");
         }
-        match parser.read_fix_message() {
-            Ok(message) => {
-                if let Some(value) = message {
-                    other = Some(value);
-                    break;
-                }
-            }
-            Err(_) => panic!("Test failed reading fix message."),
-        };
     }
     let other = other.expect("Read a message");
     let other: String = other.iter().map(|b| *b as char).collect();
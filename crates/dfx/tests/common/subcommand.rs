@@ -28,7 +28,7 @@ pub fn intern_main(i: usize, cfg: &str, path: &str) {
     );
 
     let steps = runner::steps(format!("{}", path.display()).as_str());
-    acceptor.start();
+    acceptor.start().unwrap();
 
     while acceptor.endpoints().len() == 0 {
         std::thread::sleep(Duration::from_millis(10));
@@ -0,0 +1,131 @@
+use std::{
+    net::{SocketAddr, TcpListener},
+    sync::mpsc::{channel, Sender},
+    time::Duration,
+};
+
+use dfx::{
+    connection::{ConnectionError, ConnectionObserver, SocketInitiator},
+    data_dictionary_provider::DefaultDataDictionaryProvider,
+    logging::PrintlnLogFactory,
+    message::DefaultMessageFactory,
+    message_store::MemoryStoreFactory,
+    session::{PeerIdentity, SessionSettings},
+    session_id::SessionId,
+};
+
+mod common;
+use common::runner;
+use common::TestApplication;
+
+#[derive(Debug)]
+enum Observed {
+    Attempt(SocketAddr),
+    Connected(Option<SessionId>, Option<SocketAddr>),
+    Failed(Option<SessionId>, SocketAddr, String),
+    Disconnected(Option<SessionId>),
+}
+
+struct Observer(Sender<Observed>);
+
+impl ConnectionObserver for Observer {
+    fn on_connect_attempt(&self, _session_id: &SessionId, address: SocketAddr) {
+        let _ = self.0.send(Observed::Attempt(address));
+    }
+    fn on_connected(&self, session_id: Option<&SessionId>, peer: &PeerIdentity) {
+        let _ = self.0.send(Observed::Connected(session_id.cloned(), peer.address()));
+    }
+    fn on_connect_failed(
+        &self,
+        session_id: Option<&SessionId>,
+        address: SocketAddr,
+        error: &ConnectionError,
+    ) {
+        let failed = Observed::Failed(session_id.cloned(), address, error.to_string());
+        let _ = self.0.send(failed);
+    }
+    fn on_disconnected(
+        &self,
+        session_id: Option<&SessionId>,
+        _peer: &PeerIdentity,
+        _cause: Option<&ConnectionError>,
+    ) {
+        let _ = self.0.send(Observed::Disconnected(session_id.cloned()));
+    }
+}
+
+#[test]
+pub fn test_initiate_connection_observer() {
+    // nothing listens on the primary endpoint
+    let primary = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+    let cfg = format!(
+        "
+[DEFAULT]
+ConnectionType=initiator
+BeginString=FIX.4.4
+SenderCompID=TW
+[SESSION]
+TargetCompID=ISLD
+SocketConnectHost=127.0.0.1
+SocketConnectPort={}
+SocketConnectHost1=127.0.0.1
+SocketConnectPort1=40000
+FailoverStrategy=Priority
+ReconnectInterval=10
+ReconnectMaxInterval=40
+NonStopSession=Y
+HeartBtInt=1
+",
+        primary.port()
+    );
+    let runner_thread = runner::from_filename("tests/definitions/client/initiate_failover.def");
+
+    let (tx, rx) = channel();
+    let session_id = SessionId::new("FIX.4.4", "TW", "", "", "ISLD", "", "");
+    let session_settings = SessionSettings::from_string(&cfg).unwrap();
+    let mut initiator = SocketInitiator::new(
+        session_settings,
+        TestApplication::new(),
+        MemoryStoreFactory::new(),
+        DefaultDataDictionaryProvider::new(),
+        PrintlnLogFactory::new(),
+        DefaultMessageFactory::new(),
+    )
+    .with_connection_observer(Observer(tx));
+    initiator.start();
+
+    if let Err(message) = runner_thread.join().unwrap() {
+        panic!("Steps failed:\n{message}\n");
+    }
+
+    let mut received = Vec::new();
+    while let Ok(observed) = rx.recv_timeout(Duration::from_secs(10)) {
+        let disconnected = matches!(observed, Observed::Disconnected(_));
+        received.push(observed);
+        if disconnected {
+            break;
+        }
+    }
+    initiator.stop();
+
+    assert!(
+        matches!(
+            &received[..],
+            [
+                Observed::Attempt(attempted),
+                Observed::Failed(Some(failed_id), failed, reason),
+                Observed::Attempt(backup),
+                Observed::Connected(Some(connected_id), Some(peer)),
+                Observed::Disconnected(Some(disconnected_id)),
+            ] if *attempted == primary
+                && *failed == primary
+                && reason.starts_with("Connection failed")
+                && backup.port() == 40000
+                && peer.port() == 40000
+                && *failed_id == session_id
+                && *connected_id == session_id
+                && *disconnected_id == session_id
+        ),
+        "{received:?}"
+    );
+}
@@ -0,0 +1,81 @@
+use std::{net::TcpListener, sync::mpsc::channel, time::Duration};
+
+use dfx::{
+    connection::SocketInitiator,
+    data_dictionary_provider::DefaultDataDictionaryProvider,
+    logging::PrintlnLogFactory,
+    message::DefaultMessageFactory,
+    message_store::MemoryStoreFactory,
+    session::{SessionEvent, SessionEvents, SessionSettings},
+};
+
+mod common;
+use common::runner;
+use common::TestApplication;
+
+#[test]
+pub fn test_initiate_failover() {
+    // nothing listens on the primary endpoint
+    let primary = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+    let cfg = format!(
+        "
+[DEFAULT]
+ConnectionType=initiator
+BeginString=FIX.4.4
+SenderCompID=TW
+[SESSION]
+TargetCompID=ISLD
+SocketConnectHost=127.0.0.1
+SocketConnectPort={}
+SocketConnectHost1=127.0.0.1
+SocketConnectPort1=40000
+FailoverStrategy=Priority
+ReconnectInterval=10
+ReconnectMaxInterval=40
+NonStopSession=Y
+HeartBtInt=1
+",
+        primary.port()
+    );
+    let runner_thread = runner::from_filename("tests/definitions/client/initiate_failover.def");
+
+    let (tx, rx) = channel();
+    let mut events = SessionEvents::new();
+    events.subscribe(tx);
+
+    let session_settings = SessionSettings::from_string(&cfg).unwrap();
+    let mut initiator = SocketInitiator::new(
+        session_settings,
+        TestApplication::new(),
+        MemoryStoreFactory::new(),
+        DefaultDataDictionaryProvider::new(),
+        PrintlnLogFactory::new(),
+        DefaultMessageFactory::new(),
+    )
+    .with_session_events(events);
+    initiator.start();
+
+    if let Err(message) = runner_thread.join().unwrap() {
+        panic!("Steps failed:\n{message}\n");
+    }
+
+    let mut received = Vec::new();
+    while let Ok((_, event)) = rx.recv_timeout(Duration::from_secs(5)) {
+        let connected = matches!(event, SessionEvent::Connected);
+        received.push(event);
+        if connected {
+            break;
+        }
+    }
+    initiator.stop();
+
+    assert!(matches!(
+        &received[..2],
+        [SessionEvent::Connecting { address }, SessionEvent::ConnectFailed { address: failed, .. }]
+            if *address == primary && *failed == primary
+    ));
+    assert!(matches!(
+        &received[received.len() - 2..],
+        [SessionEvent::Connecting { address }, SessionEvent::Connected] if address.port() == 40000
+    ));
+}
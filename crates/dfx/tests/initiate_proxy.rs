@@ -0,0 +1,186 @@
+use std::{
+    io::{self, Read, Write},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    thread::{self, JoinHandle},
+};
+
+use dfx::{
+    connection::SocketInitiator,
+    data_dictionary_provider::DefaultDataDictionaryProvider,
+    logging::PrintlnLogFactory,
+    message::DefaultMessageFactory,
+    message_store::MemoryStoreFactory,
+    session::SessionSettings,
+};
+
+mod common;
+use common::runner;
+use common::TestApplication;
+
+#[derive(Clone, Copy)]
+enum Proxy {
+    Socks5,
+    Http,
+}
+
+/// Accepts one connection, checks the `user`/`secret` credentials and tunnels it to the requested
+/// target. Returns the `host:port` the initiator asked for.
+fn proxy_stand_in(proxy: Proxy) -> (SocketAddr, JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let thread = thread::spawn(move || {
+        let (mut client, _) = listener.accept().unwrap();
+        let target = match proxy {
+            Proxy::Socks5 => socks5_handshake(&mut client),
+            Proxy::Http => http_handshake(&mut client),
+        };
+        let server = TcpStream::connect(resolve(&target)).unwrap();
+        let mut upstream = (client.try_clone().unwrap(), server.try_clone().unwrap());
+        let pipe = thread::spawn(move || {
+            let _ = io::copy(&mut upstream.0, &mut upstream.1);
+            let _ = upstream.1.shutdown(Shutdown::Write);
+        });
+        let mut downstream = (server, client);
+        let _ = io::copy(&mut downstream.0, &mut downstream.1);
+        let _ = downstream.1.shutdown(Shutdown::Both);
+        pipe.join().unwrap();
+        target
+    });
+    (addr, thread)
+}
+
+/// The IPv4 address of `target`, the runner listens on 127.0.0.1.
+fn resolve(target: &str) -> SocketAddr {
+    target.to_socket_addrs().unwrap().find(SocketAddr::is_ipv4).unwrap()
+}
+
+fn read_bytes(stream: &mut TcpStream, len: usize) -> Vec<u8> {
+    let mut buf = vec![0; len];
+    stream.read_exact(&mut buf).unwrap();
+    buf
+}
+
+fn socks5_handshake(client: &mut TcpStream) -> String {
+    let greeting = read_bytes(client, 2);
+    assert_eq!(greeting[0], 5);
+    let methods = read_bytes(client, greeting[1] as usize);
+    assert!(methods.contains(&2), "username/password not offered");
+    client.write_all(&[5, 2]).unwrap();
+
+    let version = read_bytes(client, 2);
+    let user = read_bytes(client, version[1] as usize);
+    let len = read_bytes(client, 1);
+    let password = read_bytes(client, len[0] as usize);
+    assert_eq!((&user[..], &password[..]), (&b"user"[..], &b"secret"[..]));
+    client.write_all(&[1, 0]).unwrap();
+
+    // the host name is passed on unresolved
+    let request = read_bytes(client, 4);
+    assert_eq!(request[..], [5, 1, 0, 3]);
+    let len = read_bytes(client, 1);
+    let host = String::from_utf8(read_bytes(client, len[0] as usize)).unwrap();
+    let port = read_bytes(client, 2);
+    client.write_all(&[5, 0, 0, 1, 0, 0, 0, 0, 0, 0]).unwrap();
+    format!("{host}:{}", u16::from_be_bytes([port[0], port[1]]))
+}
+
+fn http_handshake(client: &mut TcpStream) -> String {
+    let mut request = Vec::new();
+    while !request.ends_with(b"\r\n\r\n") {
+        request.extend(read_bytes(client, 1));
+    }
+    let request = String::from_utf8(request).unwrap();
+    assert!(request.contains("Proxy-Authorization: Basic dXNlcjpzZWNyZXQ=\r\n"), "{request}");
+    let target = request.split_whitespace().nth(1).unwrap().to_string();
+    assert!(request.contains(&format!("\r\nHost: {target}\r\n")), "{request}");
+    client
+        .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n")
+        .unwrap();
+    target
+}
+
+/// Starts the initiator, returns how to stop it.
+type Start = fn(SessionSettings) -> Box<dyn FnOnce()>;
+
+fn socket_initiator(session_settings: SessionSettings) -> Box<dyn FnOnce()> {
+    let mut initiator = SocketInitiator::new(
+        session_settings,
+        TestApplication::new(),
+        MemoryStoreFactory::new(),
+        DefaultDataDictionaryProvider::new(),
+        PrintlnLogFactory::new(),
+        DefaultMessageFactory::new(),
+    );
+    initiator.start();
+    Box::new(move || {
+        initiator.stop();
+    })
+}
+
+fn initiate_through(proxy: Proxy, proxy_type: &str, port: u16, start: Start) {
+    let (proxy_addr, proxy_thread) = proxy_stand_in(proxy);
+    let cfg = format!(
+        "
+[DEFAULT]
+ConnectionType=initiator
+BeginString=FIX.4.4
+SenderCompID=TW
+[SESSION]
+TargetCompID=ISLD
+SocketConnectHost=localhost
+SocketConnectPort={port}
+ProxyType={proxy_type}
+ProxyHost=127.0.0.1
+ProxyPort={}
+ProxyUser=user
+ProxyPassword=secret
+ReconnectInterval=10
+NonStopSession=Y
+HeartBtInt=1
+",
+        proxy_addr.port()
+    );
+    let path = "tests/definitions/client/session_events.def";
+    let runner_thread = runner::create_thread(runner::steps(path), port.into(), path);
+
+    let stop = start(SessionSettings::from_string(&cfg).unwrap());
+
+    let result = runner_thread.join().unwrap();
+    stop();
+    if let Err(message) = result {
+        panic!("Steps failed:\n{message}\n");
+    }
+    let target = proxy_thread.join().unwrap();
+    assert_eq!(target, format!("localhost:{port}"));
+}
+
+#[test]
+pub fn test_initiate_through_socks5_proxy() {
+    initiate_through(Proxy::Socks5, "socks5", 40021, socket_initiator);
+}
+
+#[test]
+pub fn test_initiate_through_http_proxy() {
+    initiate_through(Proxy::Http, "http", 40022, socket_initiator);
+}
+
+/// The proxy handshake runs off the reactor thread.
+#[cfg(feature = "mio")]
+#[test]
+pub fn test_mio_initiate_through_socks5_proxy() {
+    fn mio_initiator(session_settings: SessionSettings) -> Box<dyn FnOnce()> {
+        let mut initiator = dfx::connection::MioSocketInitiator::new(
+            session_settings,
+            TestApplication::new(),
+            MemoryStoreFactory::new(),
+            DefaultDataDictionaryProvider::new(),
+            PrintlnLogFactory::new(),
+            DefaultMessageFactory::new(),
+        );
+        initiator.start().unwrap();
+        Box::new(move || {
+            initiator.stop();
+        })
+    }
+    initiate_through(Proxy::Socks5, "socks5", 40023, mio_initiator);
+}
@@ -0,0 +1,211 @@
+use std::{
+    io::{ErrorKind, Read, Write},
+    net::{TcpListener, TcpStream},
+    thread,
+    time::{Duration, Instant},
+};
+
+use dfx::{
+    connection::SocketInitiator,
+    data_dictionary_provider::DefaultDataDictionaryProvider,
+    logging::PrintlnLogFactory,
+    message::{DefaultMessageFactory, Message},
+    message_store::DefaultStoreFactory,
+    session::{Session, SessionError, SessionHandle, SessionSettings},
+    session_id::SessionId,
+    tags,
+};
+
+mod common;
+use common::runner;
+use common::TestApplication;
+
+fn news(headline: &str) -> Message {
+    let mut message = Message::default();
+    message.header_mut().set_tag_value(tags::MsgType, "B");
+    message.set_tag_value(tags::Headline, headline);
+    message
+}
+
+#[test]
+pub fn test_send_sync() {
+    let session_id = SessionId::new("FIX.4.4", "TW", "", "", "ISLD", "", "");
+    assert!(matches!(
+        Session::send_to_session_sync(&session_id, news("too early")),
+        Err(SessionError::SessionNotFound)
+    ));
+
+    let runner_thread = runner::from_filename("tests/definitions/client/send_sync.def");
+
+    let app = TestApplication::new();
+    let session_settings = SessionSettings::from_file("tests/initiator.cfg").unwrap();
+    let mut initiator = SocketInitiator::new(
+        session_settings.clone(),
+        app,
+        DefaultStoreFactory::new(&session_settings),
+        DefaultDataDictionaryProvider::new(),
+        PrintlnLogFactory::new(),
+        DefaultMessageFactory::new(),
+    );
+    initiator.start();
+
+    let handle = SessionHandle::new(session_id.clone());
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !handle.is_logged_on() {
+        assert!(Instant::now() < deadline, "session did not log on");
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    let seq_num = Session::send_to_session_sync(&session_id, news("sync")).unwrap();
+    assert_eq!(seq_num, 2);
+    let receipt = Session::try_send_to_session(&session_id, news("async")).unwrap();
+    assert_eq!(receipt.wait_timeout(Duration::from_secs(10)).unwrap(), 3);
+
+    if let Err(message) = runner_thread.join().unwrap() {
+        panic!("Steps failed:\n{message}\n");
+    }
+    initiator.stop();
+}
+
+/// Frames `body` with the FIX.4.4 BeginString, BodyLength and CheckSum, `<TIME>` is replaced by
+/// the current time.
+fn frame(body: &str) -> Vec<u8> {
+    let now = chrono::Utc::now().format("%Y%m%d-%H:%M:%S").to_string();
+    let body = body.replace("<TIME>", &now).replace('|', "\x01");
+    let message = format!("8=FIX.4.4\x019={}\x01{body}", body.len());
+    let checksum = message.bytes().map(u32::from).sum::<u32>() % 256;
+    format!("{message}10={checksum:03}\x01").into_bytes()
+}
+
+/// Reads from `stream` until a message containing `field` arrived or the read timed out.
+fn read_until(stream: &mut TcpStream, field: &str) -> String {
+    let mut received = String::new();
+    let mut buffer = [0; 512];
+    while !received.replace('\x01', "|").contains(field) {
+        match stream.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(n) => received.push_str(&String::from_utf8_lossy(&buffer[..n])),
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => break,
+            Err(e) => panic!("{e}"),
+        }
+    }
+    received.replace('\x01', "|")
+}
+
+#[test]
+pub fn test_send_before_logon() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let cfg = format!(
+        "
+[DEFAULT]
+ConnectionType=initiator
+BeginString=FIX.4.4
+SenderCompID=TW
+[SESSION]
+TargetCompID=QUEUE
+SocketConnectHost=127.0.0.1
+SocketConnectPort={port}
+NonStopSession=Y
+HeartBtInt=30
+"
+    );
+    let session_settings = SessionSettings::from_string(&cfg).unwrap();
+    let mut initiator = SocketInitiator::new(
+        session_settings.clone(),
+        TestApplication::new(),
+        DefaultStoreFactory::new(&session_settings),
+        DefaultDataDictionaryProvider::new(),
+        PrintlnLogFactory::new(),
+        DefaultMessageFactory::new(),
+    );
+    initiator.start();
+
+    let (mut counterparty, _) = listener.accept().unwrap();
+    counterparty.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+    assert!(read_until(&mut counterparty, "35=A|").contains("34=1|"));
+
+    // the Logon is not answered yet
+    let session_id = SessionId::new("FIX.4.4", "TW", "", "", "QUEUE", "", "");
+    let handle = SessionHandle::new(session_id.clone());
+    Session::send_to_session(&session_id, news("queued")).unwrap();
+    assert!(matches!(
+        Session::send_to_session_sync(&session_id, news("sync")),
+        Err(SessionError::NotLoggedOn(_))
+    ));
+    assert_eq!(handle.status().unwrap().next_sender_msg_seq_num(), 3);
+    counterparty.set_read_timeout(Some(Duration::from_millis(200))).unwrap();
+    assert_eq!(read_until(&mut counterparty, "148="), "", "sent before the logon");
+
+    // the stored message is resent once the counterparty asks for the gap
+    counterparty.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+    counterparty
+        .write_all(&frame("35=A|34=1|49=QUEUE|52=<TIME>|56=TW|98=0|108=30|"))
+        .unwrap();
+    counterparty
+        .write_all(&frame("35=2|34=2|49=QUEUE|52=<TIME>|56=TW|7=2|16=0|"))
+        .unwrap();
+    let resent = read_until(&mut counterparty, "148=queued|");
+    assert!(resent.contains("35=B|34=2|"), "{resent}");
+    assert!(resent.contains("43=Y|"), "{resent}");
+
+    drop(counterparty);
+    initiator.stop();
+}
+
+#[test]
+pub fn test_resend_next_expected_after_logon() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let cfg = format!(
+        "
+[DEFAULT]
+ConnectionType=initiator
+BeginString=FIX.4.4
+SenderCompID=TW
+[SESSION]
+TargetCompID=NEXT
+SocketConnectHost=127.0.0.1
+SocketConnectPort={port}
+NonStopSession=Y
+HeartBtInt=30
+EnableNextExpectedMsgSeqNum=Y
+"
+    );
+    let session_settings = SessionSettings::from_string(&cfg).unwrap();
+    let mut initiator = SocketInitiator::new(
+        session_settings.clone(),
+        TestApplication::new(),
+        DefaultStoreFactory::new(&session_settings),
+        DefaultDataDictionaryProvider::new(),
+        PrintlnLogFactory::new(),
+        DefaultMessageFactory::new(),
+    );
+    initiator.start();
+
+    let (mut counterparty, _) = listener.accept().unwrap();
+    counterparty.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+    assert!(read_until(&mut counterparty, "35=A|").contains("34=1|"));
+
+    // stored behind our Logon while it is not answered
+    let session_id = SessionId::new("FIX.4.4", "TW", "", "", "NEXT", "", "");
+    Session::send_to_session(&session_id, news("queued")).unwrap();
+    let handle = SessionHandle::new(session_id.clone());
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while handle.status().unwrap().next_sender_msg_seq_num() < 3 {
+        assert!(Instant::now() < deadline, "message was not stored");
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    // the reply counts our Logon, the queued message is resent without a ResendRequest
+    counterparty
+        .write_all(&frame("35=A|34=1|49=NEXT|52=<TIME>|56=TW|98=0|108=30|789=2|"))
+        .unwrap();
+    let resent = read_until(&mut counterparty, "148=queued|");
+    assert!(resent.contains("35=B|34=2|"), "{resent}");
+    assert!(resent.contains("43=Y|"), "{resent}");
+    assert!(!resent.contains("35=4|"), "{resent}");
+
+    drop(counterparty);
+    initiator.stop();
+}
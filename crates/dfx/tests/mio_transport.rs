@@ -0,0 +1,83 @@
+#![cfg(feature = "mio")]
+#![allow(dead_code)]
+use std::time::{Duration, Instant};
+
+use dfx::{
+    connection::{MioSocketAcceptor, MioSocketInitiator},
+    data_dictionary_provider::DefaultDataDictionaryProvider,
+    logging::PrintlnLogFactory,
+    message::DefaultMessageFactory,
+    message_store::MemoryStoreFactory,
+    session::SessionSettings,
+};
+
+mod common;
+use common::runner;
+use common::{SendTestApplication, TestApplication};
+
+const ACCEPTOR_CFG: &str = "
+[DEFAULT]
+ConnectionType=acceptor
+SocketAcceptPort=0
+SenderCompID=TEST
+UseDataDictionary=N
+[SESSION]
+BeginString=FIX.4.4
+IsDynamic=Y
+TargetCompID=*
+NonStopSession=Y
+HeartBtInt=20
+";
+
+#[test]
+fn test_mio_accept() {
+    let session_settings = SessionSettings::from_string(ACCEPTOR_CFG).unwrap();
+    let mut acceptor = MioSocketAcceptor::new(
+        &session_settings,
+        TestApplication::new(),
+        MemoryStoreFactory::new(),
+        DefaultDataDictionaryProvider::new(),
+        PrintlnLogFactory::new(),
+        DefaultMessageFactory::new(),
+    );
+    acceptor.start().unwrap();
+    let port = acceptor.endpoints()[0].port();
+
+    // the same reactor thread serves one connection after the other
+    let path = "tests/definitions/server/accept_logon.def";
+    for _ in 0..2 {
+        let runner_thread = runner::create_thread(runner::steps(path), port.into(), path);
+        let start = Instant::now();
+        while !runner_thread.is_finished() {
+            assert!(Instant::now() - start < Duration::from_secs(30), "runner timed out");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        if let Err(message) = runner_thread.join().unwrap() {
+            acceptor.stop();
+            panic!("Steps failed:\n{message}\n");
+        }
+    }
+    acceptor.stop();
+}
+
+#[test]
+fn test_mio_initiate_send() {
+    let runner_thread = runner::from_filename("tests/definitions/client/send.def");
+
+    let session_settings = SessionSettings::from_file("tests/initiator.cfg").unwrap();
+    let mut initiator = MioSocketInitiator::new(
+        session_settings,
+        SendTestApplication::new(),
+        MemoryStoreFactory::new(),
+        DefaultDataDictionaryProvider::new(),
+        PrintlnLogFactory::new(),
+        DefaultMessageFactory::new(),
+    );
+    initiator.start().unwrap();
+
+    let result = runner_thread.join().unwrap();
+    initiator.stop();
+    if let Err(message) = result {
+        panic!("Steps failed:\n{message}\n");
+    }
+}
@@ -84,7 +84,7 @@ macro_rules! acceptor {
                     println!("{:?}", std::env::current_dir());
                     let path = path_from_version(stringify!($cfg));
                     let steps = runner::steps(format!("{path}{}.def", $func).as_str());
-                    acceptor.start();
+                    acceptor.start().unwrap();
 
                     while acceptor.endpoints().len() == 0 {
                         std::thread::sleep(Duration::from_millis(10));
@@ -0,0 +1,65 @@
+use std::{sync::mpsc::channel, time::Duration};
+
+use dfx::{
+    connection::SocketInitiator,
+    data_dictionary_provider::DefaultDataDictionaryProvider,
+    logging::PrintlnLogFactory,
+    message::DefaultMessageFactory,
+    message_store::DefaultStoreFactory,
+    session::{SessionEvent, SessionEvents, SessionSettings},
+};
+
+mod common;
+use common::runner;
+use common::TestApplication;
+
+#[test]
+pub fn test_session_events() {
+    let runner_thread = runner::from_filename("tests/definitions/client/session_events.def");
+
+    let (tx, rx) = channel();
+    let mut events = SessionEvents::new();
+    events.subscribe(tx);
+
+    let app = TestApplication::new();
+    let session_settings = SessionSettings::from_file("tests/initiator.cfg").unwrap();
+    let mut initiator = SocketInitiator::new(
+        session_settings.clone(),
+        app,
+        DefaultStoreFactory::new(&session_settings),
+        DefaultDataDictionaryProvider::new(),
+        PrintlnLogFactory::new(),
+        DefaultMessageFactory::new(),
+    )
+    .with_session_events(events);
+    initiator.start();
+
+    if let Err(message) = runner_thread.join().unwrap() {
+        panic!("Steps failed:\n{message}\n");
+    }
+
+    let mut received = Vec::new();
+    while let Ok((_, event)) = rx.recv_timeout(Duration::from_secs(5)) {
+        let disconnected = matches!(event, SessionEvent::Disconnected { .. });
+        received.push(event);
+        if disconnected {
+            break;
+        }
+    }
+    initiator.stop();
+
+    assert!(matches!(received[0], SessionEvent::Connecting { .. }));
+    assert_eq!(
+        received[1..],
+        [
+            SessionEvent::Connected,
+            SessionEvent::LogonSent,
+            SessionEvent::LoggedOn,
+            SessionEvent::LogoutReceived,
+            SessionEvent::LogoutSent { reason: None },
+            SessionEvent::Disconnected {
+                reason: "Received logout request".into()
+            },
+        ]
+    );
+}
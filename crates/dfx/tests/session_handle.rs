@@ -0,0 +1,54 @@
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use dfx::{
+    connection::SocketInitiator,
+    data_dictionary_provider::DefaultDataDictionaryProvider,
+    logging::PrintlnLogFactory,
+    message::DefaultMessageFactory,
+    message_store::DefaultStoreFactory,
+    session::{SessionHandle, SessionSettings},
+    session_id::SessionId,
+};
+
+mod common;
+use common::runner;
+use common::TestApplication;
+
+#[test]
+pub fn test_session_handle_logout() {
+    let runner_thread = runner::from_filename("tests/definitions/client/session_handle.def");
+
+    let app = TestApplication::new();
+    let session_settings = SessionSettings::from_file("tests/initiator.cfg").unwrap();
+    let mut initiator = SocketInitiator::new(
+        session_settings.clone(),
+        app,
+        DefaultStoreFactory::new(&session_settings),
+        DefaultDataDictionaryProvider::new(),
+        PrintlnLogFactory::new(),
+        DefaultMessageFactory::new(),
+    );
+    initiator.start();
+
+    let handle = SessionHandle::new(SessionId::new("FIX.4.4", "TW", "", "", "ISLD", "", ""));
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !handle.is_logged_on() {
+        assert!(Instant::now() < deadline, "session did not log on");
+        thread::sleep(Duration::from_millis(10));
+    }
+    let status = handle.status().unwrap();
+    assert_eq!(status.next_sender_msg_seq_num(), 2);
+    assert_eq!(status.next_target_msg_seq_num(), 2);
+
+    handle.logout(Some("maintenance")).unwrap();
+    if let Err(message) = runner_thread.join().unwrap() {
+        panic!("Steps failed:\n{message}\n");
+    }
+
+    assert!(!handle.is_enabled());
+    assert!(!handle.is_logged_on());
+    initiator.stop();
+}
@@ -0,0 +1,336 @@
+#![allow(dead_code)]
+use std::{
+    net::SocketAddr,
+    sync::mpsc::channel,
+    time::{Duration, Instant},
+};
+
+use dfx::{
+    connection::{SocketAcceptor, SocketInitiator},
+    data_dictionary_provider::DefaultDataDictionaryProvider,
+    logging::PrintlnLogFactory,
+    message::DefaultMessageFactory,
+    message_store::MemoryStoreFactory,
+    session::{
+        LogonDecision, LogonRequest, PeerCertificate, SessionEvent, SessionEvents, SessionSettings,
+    },
+};
+
+mod common;
+use common::TestApplication;
+
+const ACCEPTOR_CFG: &str = "
+[DEFAULT]
+ConnectionType=acceptor
+SocketAcceptPort=0
+NonStopSession=Y
+SenderCompID=ISLD
+TargetCompID=TW
+UseDataDictionary=N
+HeartBtInt=30
+SSLCertificate=tests/certs/server.pem
+SSLCACertificate=tests/certs/ca.pem
+[SESSION]
+BeginString=FIX.4.4
+SSLEnable=Y
+";
+
+const INITIATOR_CFG: &str = "
+[DEFAULT]
+ConnectionType=initiator
+NonStopSession=Y
+SenderCompID=TW
+TargetCompID=ISLD
+UseDataDictionary=N
+HeartBtInt=30
+ReconnectInterval=1
+SocketConnectHost=127.0.0.1
+SSLServerName=localhost
+SSLCACertificate=tests/certs/ca.pem
+SSLDisableBuiltInRoots=Y
+[SESSION]
+BeginString=FIX.4.4
+SSLEnable=Y
+";
+
+/// The tests run in parallel, each one talks to an acceptor of its own CompID.
+fn acceptor_cfg(sender_comp_id: &str) -> String {
+    ACCEPTOR_CFG.replace("SenderCompID=ISLD", &format!("SenderCompID={sender_comp_id}"))
+}
+
+fn initiator_cfg(target_comp_id: &str) -> String {
+    INITIATOR_CFG.replace("TargetCompID=ISLD", &format!("TargetCompID={target_comp_id}"))
+}
+
+fn acceptor_port(endpoints: impl Fn() -> Vec<SocketAddr>) -> u16 {
+    let start = Instant::now();
+    while endpoints().is_empty() {
+        assert!(Instant::now() - start < Duration::from_secs(10), "acceptor did not start");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    endpoints()[0].port()
+}
+
+/// Logs the initiator on to the acceptor over TLS, returns the certificate the acceptor saw.
+fn tls_logon(acceptor_cfg: &str, initiator_cfg: &str) -> Option<PeerCertificate> {
+    let (tx, rx) = channel();
+    let authenticator = move |request: &LogonRequest<'_>| {
+        let _ = tx.send(request.peer().certificate().cloned());
+        LogonDecision::Accept
+    };
+    let session_settings = SessionSettings::from_string(acceptor_cfg).unwrap();
+    let mut acceptor = SocketAcceptor::new(
+        &session_settings,
+        TestApplication::new(),
+        MemoryStoreFactory::new(),
+        DefaultDataDictionaryProvider::new(),
+        PrintlnLogFactory::new(),
+        DefaultMessageFactory::new(),
+    )
+    .with_logon_authenticator(authenticator);
+    acceptor.start().unwrap();
+    let port = acceptor_port(|| acceptor.endpoints());
+
+    let cfg = format!("{initiator_cfg}SocketConnectPort={port}\n");
+    let session_settings = SessionSettings::from_string(&cfg).unwrap();
+    let mut initiator = SocketInitiator::new(
+        session_settings,
+        TestApplication::new(),
+        MemoryStoreFactory::new(),
+        DefaultDataDictionaryProvider::new(),
+        PrintlnLogFactory::new(),
+        DefaultMessageFactory::new(),
+    );
+    initiator.start();
+
+    let certificate = rx.recv_timeout(Duration::from_secs(30));
+    initiator.stop();
+    acceptor.stop();
+    certificate.expect("no Logon received over TLS")
+}
+
+#[test]
+pub fn test_tls_logon() {
+    let certificate = tls_logon(&acceptor_cfg("ISLD"), &initiator_cfg("ISLD"));
+    assert!(certificate.is_none());
+}
+
+/// native-tls acceptors load with `SSLRequireClientCertificate=Y` as before, without verifying
+/// the client certificate.
+#[cfg(not(feature = "rustls"))]
+#[test]
+pub fn test_native_tls_ignores_client_certificate_requirement() {
+    let acceptor_cfg = acceptor_cfg("NOMTLS").replace(
+        "SSLEnable=Y\n",
+        "SSLEnable=Y\nSSLRequireClientCertificate=Y\n",
+    );
+    let certificate = tls_logon(&acceptor_cfg, &initiator_cfg("NOMTLS"));
+    assert!(certificate.is_none());
+}
+
+#[cfg(feature = "rustls")]
+#[test]
+pub fn test_mutual_tls_logon() {
+    let acceptor_cfg = acceptor_cfg("MTLS").replace(
+        "SSLEnable=Y\n",
+        "SSLEnable=Y\nSSLRequireClientCertificate=Y\n",
+    );
+    let initiator_cfg = initiator_cfg("MTLS").replace(
+        "SSLEnable=Y\n",
+        "SSLEnable=Y\nSSLCertificate=tests/certs/client.pem\n",
+    );
+    let certificate = tls_logon(&acceptor_cfg, &initiator_cfg).expect("no client certificate");
+    let names: Vec<_> = certificate
+        .names()
+        .iter()
+        .map(|name| format!("{}={}", name.key(), name.value()))
+        .collect();
+    assert_eq!(names, ["O=dfx", "CN=TW", "DNS=tw.example.com"]);
+}
+
+/// Logs TW on to an acceptor with `SSLCertificateCompIDs`, returns whether it was logged on or
+/// the Logout text it was rejected with.
+#[cfg(feature = "rustls")]
+fn certificate_comp_ids_logon(sender_comp_id: &str, comp_ids: &str) -> Result<(), Option<String>> {
+    let (tx, rx) = channel();
+    let mut events = SessionEvents::new();
+    events.subscribe(tx);
+    let acceptor_cfg = acceptor_cfg(sender_comp_id).replace(
+        "SSLEnable=Y\n",
+        &format!("SSLEnable=Y\nSSLRequireClientCertificate=Y\nSSLCertificateCompIDs={comp_ids}\n"),
+    );
+    let mut acceptor = SocketAcceptor::new(
+        &SessionSettings::from_string(&acceptor_cfg).unwrap(),
+        TestApplication::new(),
+        MemoryStoreFactory::new(),
+        DefaultDataDictionaryProvider::new(),
+        PrintlnLogFactory::new(),
+        DefaultMessageFactory::new(),
+    )
+    .with_session_events(events);
+    acceptor.start().unwrap();
+    let port = acceptor_port(|| acceptor.endpoints());
+
+    let initiator_cfg = initiator_cfg(sender_comp_id).replace(
+        "SSLEnable=Y\n",
+        "SSLEnable=Y\nSSLCertificate=tests/certs/client.pem\n",
+    );
+    let cfg = format!("{initiator_cfg}SocketConnectPort={port}\n");
+    let mut initiator = SocketInitiator::new(
+        SessionSettings::from_string(&cfg).unwrap(),
+        TestApplication::new(),
+        MemoryStoreFactory::new(),
+        DefaultDataDictionaryProvider::new(),
+        PrintlnLogFactory::new(),
+        DefaultMessageFactory::new(),
+    );
+    initiator.start();
+
+    let outcome = loop {
+        match rx.recv_timeout(Duration::from_secs(30)) {
+            Ok((_, SessionEvent::LoggedOn)) => break Ok(()),
+            Ok((_, SessionEvent::LogonRejected { reason })) => break Err(reason),
+            Ok(_) => continue,
+            Err(_) => panic!("no Logon received over TLS"),
+        }
+    };
+    initiator.stop();
+    acceptor.stop();
+    outcome
+}
+
+#[cfg(feature = "rustls")]
+#[test]
+pub fn test_certificate_comp_ids() {
+    // DNS=tw.example.com is bound to TW
+    let outcome = certificate_comp_ids_logon("BOUND", "CN:GW>GW;DNS:*.example.com>TW,TW2");
+    assert_eq!(outcome, Ok(()));
+
+    let outcome = certificate_comp_ids_logon("UNBOUND", "CN:TW>GW");
+    assert_eq!(outcome, Err(Some("Client certificate not valid for TW".into())));
+}
+
+#[cfg(feature = "rustls")]
+#[test]
+pub fn test_mutual_tls_requires_client_certificate() {
+    let acceptor_cfg = acceptor_cfg("NOCERT").replace(
+        "SSLEnable=Y\n",
+        "SSLEnable=Y\nSSLRequireClientCertificate=Y\n",
+    );
+    let (tx, rx) = channel::<()>();
+    let authenticator = move |_request: &LogonRequest<'_>| {
+        let _ = tx.send(());
+        LogonDecision::Accept
+    };
+    let session_settings = SessionSettings::from_string(&acceptor_cfg).unwrap();
+    let mut acceptor = SocketAcceptor::new(
+        &session_settings,
+        TestApplication::new(),
+        MemoryStoreFactory::new(),
+        DefaultDataDictionaryProvider::new(),
+        PrintlnLogFactory::new(),
+        DefaultMessageFactory::new(),
+    )
+    .with_logon_authenticator(authenticator);
+    acceptor.start().unwrap();
+    let port = acceptor_port(|| acceptor.endpoints());
+
+    // no SSLCertificate, the handshake fails before any Logon
+    let cfg = format!("{}SocketConnectPort={port}\n", initiator_cfg("NOCERT"));
+    let mut initiator = SocketInitiator::new(
+        SessionSettings::from_string(&cfg).unwrap(),
+        TestApplication::new(),
+        MemoryStoreFactory::new(),
+        DefaultDataDictionaryProvider::new(),
+        PrintlnLogFactory::new(),
+        DefaultMessageFactory::new(),
+    );
+    initiator.start();
+
+    let logon = rx.recv_timeout(Duration::from_secs(3));
+    initiator.stop();
+    acceptor.stop();
+    assert!(logon.is_err());
+}
+
+#[cfg(feature = "mio")]
+#[test]
+pub fn test_mio_tls_logon() {
+    use dfx::connection::{MioSocketAcceptor, MioSocketInitiator};
+
+    let (tx, rx) = channel();
+    let authenticator = move |request: &LogonRequest<'_>| {
+        let _ = tx.send(request.peer().certificate().cloned());
+        LogonDecision::Accept
+    };
+    let session_settings = SessionSettings::from_string(&acceptor_cfg("MIO")).unwrap();
+    let mut acceptor = MioSocketAcceptor::new(
+        &session_settings,
+        TestApplication::new(),
+        MemoryStoreFactory::new(),
+        DefaultDataDictionaryProvider::new(),
+        PrintlnLogFactory::new(),
+        DefaultMessageFactory::new(),
+    )
+    .with_logon_authenticator(authenticator);
+    acceptor.start().unwrap();
+    let port = acceptor_port(|| acceptor.endpoints());
+
+    let cfg = format!("{}SocketConnectPort={port}\n", initiator_cfg("MIO"));
+    let mut initiator = MioSocketInitiator::new(
+        SessionSettings::from_string(&cfg).unwrap(),
+        TestApplication::new(),
+        MemoryStoreFactory::new(),
+        DefaultDataDictionaryProvider::new(),
+        PrintlnLogFactory::new(),
+        DefaultMessageFactory::new(),
+    );
+    initiator.start().unwrap();
+
+    let certificate = rx.recv_timeout(Duration::from_secs(30));
+    initiator.stop();
+    acceptor.stop();
+    assert_eq!(certificate, Ok(None));
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn test_tokio_tls_logon() {
+    use dfx::connection::{TokioSocketAcceptor, TokioSocketInitiator};
+
+    let (tx, rx) = channel();
+    let authenticator = move |request: &LogonRequest<'_>| {
+        let _ = tx.send(request.peer().certificate().cloned());
+        LogonDecision::Accept
+    };
+    let session_settings = SessionSettings::from_string(&acceptor_cfg("TOKIO")).unwrap();
+    let mut acceptor = TokioSocketAcceptor::new(
+        &session_settings,
+        TestApplication::new(),
+        MemoryStoreFactory::new(),
+        DefaultDataDictionaryProvider::new(),
+        PrintlnLogFactory::new(),
+        DefaultMessageFactory::new(),
+    )
+    .with_logon_authenticator(authenticator);
+    acceptor.start().await.unwrap();
+    let port = acceptor.endpoints()[0].port();
+
+    let cfg = format!("{}SocketConnectPort={port}\n", initiator_cfg("TOKIO"));
+    let mut initiator = TokioSocketInitiator::new(
+        SessionSettings::from_string(&cfg).unwrap(),
+        TestApplication::new(),
+        MemoryStoreFactory::new(),
+        DefaultDataDictionaryProvider::new(),
+        PrintlnLogFactory::new(),
+        DefaultMessageFactory::new(),
+    );
+    initiator.start();
+
+    let certificate = tokio::task::spawn_blocking(move || rx.recv_timeout(Duration::from_secs(30)))
+        .await
+        .unwrap();
+    initiator.stop().await;
+    acceptor.stop().await;
+    assert_eq!(certificate, Ok(None));
+}
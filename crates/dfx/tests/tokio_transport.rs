@@ -0,0 +1,87 @@
+#![cfg(feature = "tokio")]
+#![allow(dead_code)]
+use std::time::Duration;
+
+use dfx::{
+    connection::{TokioSocketAcceptor, TokioSocketInitiator},
+    data_dictionary_provider::DefaultDataDictionaryProvider,
+    logging::PrintlnLogFactory,
+    message::DefaultMessageFactory,
+    message_store::MemoryStoreFactory,
+    session::SessionSettings,
+};
+
+mod common;
+use common::runner;
+use common::{SendTestApplication, TestApplication};
+
+const ACCEPTOR_CFG: &str = "
+[DEFAULT]
+ConnectionType=acceptor
+SocketAcceptPort=0
+SenderCompID=TEST
+UseDataDictionary=N
+[SESSION]
+BeginString=FIX.4.4
+IsDynamic=Y
+TargetCompID=*
+NonStopSession=Y
+HeartBtInt=20
+";
+
+#[tokio::test]
+async fn test_tokio_accept() {
+    let session_settings = SessionSettings::from_string(ACCEPTOR_CFG).unwrap();
+    let mut acceptor = TokioSocketAcceptor::new(
+        &session_settings,
+        TestApplication::new(),
+        MemoryStoreFactory::new(),
+        DefaultDataDictionaryProvider::new(),
+        PrintlnLogFactory::new(),
+        DefaultMessageFactory::new(),
+    );
+    acceptor.start().await.unwrap();
+    let port = acceptor.endpoints()[0].port();
+
+    let path = "tests/definitions/server/accept_logon.def";
+    let runner_thread = runner::create_thread(runner::steps(path), port.into(), path);
+    let result = tokio::time::timeout(
+        Duration::from_secs(30),
+        tokio::task::spawn_blocking(move || runner_thread.join().unwrap()),
+    )
+    .await
+    .expect("runner timed out")
+    .unwrap();
+    acceptor.stop().await;
+    if let Err(message) = result {
+        panic!("Steps failed:\n{message}\n");
+    }
+}
+
+#[tokio::test]
+async fn test_tokio_initiate_send() {
+    let runner_thread = runner::from_filename("tests/definitions/client/send.def");
+
+    let session_settings = SessionSettings::from_file("tests/initiator.cfg").unwrap();
+    let mut initiator = TokioSocketInitiator::new(
+        session_settings,
+        SendTestApplication::new(),
+        MemoryStoreFactory::new(),
+        DefaultDataDictionaryProvider::new(),
+        PrintlnLogFactory::new(),
+        DefaultMessageFactory::new(),
+    );
+    initiator.start();
+
+    let result = tokio::time::timeout(
+        Duration::from_secs(30),
+        tokio::task::spawn_blocking(move || runner_thread.join().unwrap()),
+    )
+    .await
+    .expect("runner timed out")
+    .unwrap();
+    initiator.stop().await;
+    if let Err(message) = result {
+        panic!("Steps failed:\n{message}\n");
+    }
+}